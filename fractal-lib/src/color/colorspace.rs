@@ -0,0 +1,271 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between sRGB and the other color spaces that a `Palette` can interpolate in.
+//!
+//! Every function works on three-component `[f64; 3]` triples. sRGB components are in the range
+//! 0.0 to 1.0 (gamma-encoded, as stored in a `ColorU8`). Hues are in degrees from 0.0 to 360.0,
+//! and saturation, value, and lightness are from 0.0 to 1.0. CIELAB uses the D65 white point, with
+//! L from 0.0 to 100.0.
+
+/// Converts gamma-encoded sRGB to HSV (hue, saturation, value).
+pub fn rgb_to_hsv(rgb: [f64; 3]) -> [f64; 3] {
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let chroma = max - min;
+    let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+    [hue(rgb, max, chroma), saturation, max]
+}
+
+/// Converts HSV (hue, saturation, value) to gamma-encoded sRGB.
+pub fn hsv_to_rgb(hsv: [f64; 3]) -> [f64; 3] {
+    let chroma = hsv[2] * hsv[1];
+    from_hue_chroma(hsv[0], chroma, hsv[2] - chroma)
+}
+
+/// Converts gamma-encoded sRGB to HSL (hue, saturation, lightness).
+pub fn rgb_to_hsl(rgb: [f64; 3]) -> [f64; 3] {
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let chroma = max - min;
+    let lightness = (max + min) / 2.0;
+    let saturation = if lightness > 0.0 && lightness < 1.0 {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    } else {
+        0.0
+    };
+    [hue(rgb, max, chroma), saturation, lightness]
+}
+
+/// Converts HSL (hue, saturation, lightness) to gamma-encoded sRGB.
+pub fn hsl_to_rgb(hsl: [f64; 3]) -> [f64; 3] {
+    let chroma = (1.0 - (2.0 * hsl[2] - 1.0).abs()) * hsl[1];
+    from_hue_chroma(hsl[0], chroma, hsl[2] - chroma / 2.0)
+}
+
+/// Converts gamma-encoded sRGB to CIELAB (L*, a*, b*).
+pub fn rgb_to_lab(rgb: [f64; 3]) -> [f64; 3] {
+    let lin = [
+        srgb_to_linear(rgb[0]),
+        srgb_to_linear(rgb[1]),
+        srgb_to_linear(rgb[2]),
+    ];
+    let xyz = [
+        (0.412_456_4 * lin[0] + 0.357_576_1 * lin[1] + 0.180_437_5 * lin[2]) / WHITE_D65[0],
+        (0.212_672_9 * lin[0] + 0.715_152_2 * lin[1] + 0.072_175_0 * lin[2]) / WHITE_D65[1],
+        (0.019_333_9 * lin[0] + 0.119_192_0 * lin[1] + 0.950_304_1 * lin[2]) / WHITE_D65[2],
+    ];
+    let f = [lab_f(xyz[0]), lab_f(xyz[1]), lab_f(xyz[2])];
+    [
+        116.0 * f[1] - 16.0,
+        500.0 * (f[0] - f[1]),
+        200.0 * (f[1] - f[2]),
+    ]
+}
+
+/// Converts CIELAB (L*, a*, b*) to gamma-encoded sRGB. Out of gamut colors are clamped.
+pub fn lab_to_rgb(lab: [f64; 3]) -> [f64; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+    let xyz = [
+        lab_f_inv(fx) * WHITE_D65[0],
+        lab_f_inv(fy) * WHITE_D65[1],
+        lab_f_inv(fz) * WHITE_D65[2],
+    ];
+    let lin = [
+        3.240_454_2 * xyz[0] - 1.537_138_5 * xyz[1] - 0.498_531_4 * xyz[2],
+        -0.969_266_0 * xyz[0] + 1.876_010_8 * xyz[1] + 0.041_556_0 * xyz[2],
+        0.055_643_4 * xyz[0] - 0.204_025_9 * xyz[1] + 1.057_225_2 * xyz[2],
+    ];
+    [
+        linear_to_srgb(lin[0]),
+        linear_to_srgb(lin[1]),
+        linear_to_srgb(lin[2]),
+    ]
+}
+
+/// Converts gamma-encoded sRGB to OKLab (L, a, b).
+///
+/// See <https://bottosson.github.io/posts/oklab/> for the definition of the color space.
+pub fn rgb_to_oklab(rgb: [f64; 3]) -> [f64; 3] {
+    let lin = [
+        srgb_to_linear(rgb[0]),
+        srgb_to_linear(rgb[1]),
+        srgb_to_linear(rgb[2]),
+    ];
+    let l = (0.412_221_470_8 * lin[0] + 0.536_332_536_3 * lin[1] + 0.051_445_992_9 * lin[2]).cbrt();
+    let m = (0.211_903_498_2 * lin[0] + 0.680_699_545_1 * lin[1] + 0.107_396_956_6 * lin[2]).cbrt();
+    let s = (0.088_302_461_9 * lin[0] + 0.281_718_837_6 * lin[1] + 0.629_978_700_5 * lin[2]).cbrt();
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    ]
+}
+
+/// Converts OKLab (L, a, b) to gamma-encoded sRGB. Out of gamut colors are clamped.
+pub fn oklab_to_rgb(lab: [f64; 3]) -> [f64; 3] {
+    let l = lab[0] + 0.396_337_777_4 * lab[1] + 0.215_803_757_3 * lab[2];
+    let m = lab[0] - 0.105_561_345_8 * lab[1] - 0.063_854_172_8 * lab[2];
+    let s = lab[0] - 0.089_484_177_5 * lab[1] - 1.291_485_548_0 * lab[2];
+    let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+    [
+        linear_to_srgb(4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s),
+        linear_to_srgb(-1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s),
+        linear_to_srgb(-0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s),
+    ]
+}
+
+/// Reference white (D65) used by the CIELAB conversions.
+const WHITE_D65: [f64; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// Removes the sRGB gamma encoding from a single component.
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Applies the sRGB gamma encoding to a single component, clamping it to 0.0 to 1.0.
+fn linear_to_srgb(c: f64) -> f64 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn lab_f(t: f64) -> f64 {
+    const DELTA: f64 = 6.0 / 29.0;
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_f_inv(t: f64) -> f64 {
+    const DELTA: f64 = 6.0 / 29.0;
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+/// The hue shared by HSV and HSL, in degrees.
+fn hue(rgb: [f64; 3], max: f64, chroma: f64) -> f64 {
+    if chroma <= 0.0 {
+        return 0.0;
+    }
+    #[allow(clippy::float_cmp)]
+    let sextant = if max == rgb[0] {
+        ((rgb[1] - rgb[2]) / chroma).rem_euclid(6.0)
+    } else if max == rgb[1] {
+        (rgb[2] - rgb[0]) / chroma + 2.0
+    } else {
+        (rgb[0] - rgb[1]) / chroma + 4.0
+    };
+    sextant * 60.0
+}
+
+/// Builds an RGB triple from a hue, a chroma, and the amount to add to every component.
+fn from_hue_chroma(hue: f64, chroma: f64, m: f64) -> [f64; 3] {
+    let sextant = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sextant % 2.0 - 1.0).abs());
+    let (r, g, b) = match sextant as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLES: [[f64; 3]; 7] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.2, 0.4, 0.6],
+        [0.9, 0.5, 0.1],
+    ];
+
+    fn assert_rgb_eq(lhs: [f64; 3], rhs: [f64; 3]) {
+        for i in 0..3 {
+            assert_approx_eq!(lhs[i], rhs[i], 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for rgb in SAMPLES.iter() {
+            assert_rgb_eq(hsv_to_rgb(rgb_to_hsv(*rgb)), *rgb);
+        }
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        for rgb in SAMPLES.iter() {
+            assert_rgb_eq(hsl_to_rgb(rgb_to_hsl(*rgb)), *rgb);
+        }
+    }
+
+    #[test]
+    fn test_lab_round_trip() {
+        for rgb in SAMPLES.iter() {
+            assert_rgb_eq(lab_to_rgb(rgb_to_lab(*rgb)), *rgb);
+        }
+    }
+
+    #[test]
+    fn test_oklab_round_trip() {
+        for rgb in SAMPLES.iter() {
+            assert_rgb_eq(oklab_to_rgb(rgb_to_oklab(*rgb)), *rgb);
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        let hsv = rgb_to_hsv([0.0, 0.0, 1.0]);
+        assert_approx_eq!(hsv[0], 240.0, 0.0001);
+        assert_approx_eq!(hsv[1], 1.0, 0.0001);
+        assert_approx_eq!(hsv[2], 1.0, 0.0001);
+
+        let hsl = rgb_to_hsl([0.0, 1.0, 0.0]);
+        assert_approx_eq!(hsl[0], 120.0, 0.0001);
+        assert_approx_eq!(hsl[2], 0.5, 0.0001);
+
+        let lab = rgb_to_lab([1.0, 1.0, 1.0]);
+        assert_approx_eq!(lab[0], 100.0, 0.01);
+        assert_approx_eq!(lab[1], 0.0, 0.01);
+        assert_approx_eq!(lab[2], 0.0, 0.01);
+
+        let oklab = rgb_to_oklab([1.0, 1.0, 1.0]);
+        assert_approx_eq!(oklab[0], 1.0, 0.001);
+        assert_approx_eq!(oklab[1], 0.0, 0.001);
+        assert_approx_eq!(oklab[2], 0.0, 0.001);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing Fractint `.map` palette files.
+//!
+//! A `.map` file is plain text with one color per line, written as three decimal red, green, and
+//! blue values from 0 to 255. Anything after the third value on a line is a comment. Fractint
+//! itself uses exactly 256 colors, but many programs that share the format write fewer.
+
+use super::palette::Palette;
+use super::palettefile::{PaletteError, PaletteFormat};
+use super::ColorU8;
+
+/// The number of colors in a Fractint palette.
+pub const FRACTINT_COLORS: usize = 256;
+
+/// Parses the contents of a `.map` file into a palette with its colors evenly spaced.
+pub fn parse_map(text: &str) -> Result<Palette, PaletteError> {
+    let mut colors = Vec::with_capacity(FRACTINT_COLORS);
+    for (index, line) in text.lines().enumerate() {
+        let line_num = index + 1;
+        let mut fields = line.split_whitespace().peekable();
+        if fields.peek().is_none() {
+            // blank lines are skipped
+            continue;
+        }
+        let mut rgb = [0u8; 3];
+        for (channel, field) in ["red", "green", "blue"].iter().zip(rgb.iter_mut()) {
+            let value = fields.next().ok_or_else(|| {
+                parse_error(line_num, format!("missing the {} component", channel))
+            })?;
+            *field = value.parse::<u8>().map_err(|_| {
+                parse_error(
+                    line_num,
+                    format!(
+                        "the {} component must be a whole number from 0 to 255, found {:?}",
+                        channel, value
+                    ),
+                )
+            })?;
+        }
+        if colors.len() == FRACTINT_COLORS {
+            return Err(parse_error(
+                line_num,
+                format!("a map may contain at most {} colors", FRACTINT_COLORS),
+            ));
+        }
+        colors.push(ColorU8([rgb[0], rgb[1], rgb[2], 255]));
+    }
+    if colors.is_empty() {
+        return Err(PaletteError::Parse {
+            format: PaletteFormat::FractintMap,
+            line: None,
+            message: "the map does not contain any colors".to_string(),
+        });
+    }
+    Ok(Palette::uniform(&colors))
+}
+
+/// Writes `palette` as a 256 color `.map` file. The `name` is added as a comment on the first
+/// line, with any line breaks in it replaced by spaces so that it stays a comment. Alpha is
+/// discarded, since the format does not support it.
+pub fn write_map(palette: &Palette, name: &str) -> String {
+    let mut out = String::new();
+    for (index, color) in palette.color_range(FRACTINT_COLORS).iter().enumerate() {
+        out.push_str(&format!(
+            "{:3} {:3} {:3}",
+            color.0[0], color.0[1], color.0[2]
+        ));
+        if index == 0 && !name.is_empty() {
+            out.push_str("  ");
+            out.push_str(&name.replace(['\r', '\n'], " "));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn parse_error(line: usize, message: String) -> PaletteError {
+    PaletteError::Parse {
+        format: PaletteFormat::FractintMap,
+        line: Some(line),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_map() {
+        let palette =
+            parse_map("0 0 0 black\n\n  128 64 32\n255 255 255 white, with comments\n").unwrap();
+        assert_eq!(palette.stops().len(), 3);
+        assert_eq!(palette.color_at(0.0), ColorU8([0, 0, 0, 255]));
+        assert_eq!(palette.color_at(0.5), ColorU8([128, 64, 32, 255]));
+        assert_eq!(palette.color_at(1.0), ColorU8([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = write_map(&crate::color::palette::NamedPalette::Fire.palette(), "fire");
+        assert_eq!(text.lines().count(), FRACTINT_COLORS);
+        assert!(text.starts_with("  0   0   0  fire\n"));
+        let palette = parse_map(&text).unwrap();
+        assert_eq!(palette.stops().len(), FRACTINT_COLORS);
+        assert_eq!(palette.color_at(1.0), ColorU8([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_round_trip_multiline_name() {
+        let fire = crate::color::palette::NamedPalette::Fire.palette();
+        let text = write_map(&fire, "fire\nand\r\nice");
+        assert_eq!(text.lines().count(), FRACTINT_COLORS);
+        assert!(text.starts_with("  0   0   0  fire and  ice\n"));
+        let palette = parse_map(&text).unwrap();
+        assert_eq!(palette, parse_map(&write_map(&fire, "")).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_map("0 0 0\n1 2\n").unwrap_err().to_string(),
+            "Fractint map, line 2: missing the blue component"
+        );
+        assert_eq!(
+            parse_map("0 0 256\n").unwrap_err().to_string(),
+            "Fractint map, line 1: the blue component must be a whole number from 0 to 255, \
+             found \"256\""
+        );
+        assert_eq!(
+            parse_map("\n\n").unwrap_err().to_string(),
+            "Fractint map: the map does not contain any colors"
+        );
+        let too_many = "1 2 3\n".repeat(FRACTINT_COLORS + 1);
+        assert_eq!(
+            parse_map(&too_many).unwrap_err().to_string(),
+            "Fractint map, line 257: a map may contain at most 256 colors"
+        );
+    }
+}
@@ -0,0 +1,461 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing GIMP `.ggr` gradient files.
+//!
+//! A gradient file starts with a `GIMP Gradient` header, an optional `Name:` line, and the number
+//! of segments. Each segment is a line of the form:
+//!
+//! ```text
+//! left middle right r0 g0 b0 a0 r1 g1 b1 a1 blend coloring [left-type right-type]
+//! ```
+//!
+//! where the positions and color components are floating point numbers between 0 and 1. The
+//! blend function controls how quickly the segment moves from its left color to its right color,
+//! and the coloring controls whether it moves through RGB or around the HSV color wheel.
+//!
+//! Segments that blend linearly in RGB are converted exactly. Every other kind of segment is
+//! sampled, since a `Palette` only interpolates one way between all of its stops.
+
+use super::colorspace;
+use super::palette::{ColorStop, Interpolation, Palette};
+use super::palettefile::{PaletteError, PaletteFormat};
+use super::ColorU8;
+use std::f64::consts::PI;
+
+/// The number of stops used to approximate a segment that cannot be converted exactly.
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// The number of colors a non-RGB palette is resampled to when it is written out.
+const WRITE_SAMPLES: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Blend {
+    Linear,
+    Curved,
+    Sine,
+    SphereIncreasing,
+    SphereDecreasing,
+    Step,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Coloring {
+    Rgb,
+    HsvCounterClockwise,
+    HsvClockwise,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Segment {
+    left: f64,
+    middle: f64,
+    right: f64,
+    left_color: [f64; 4],
+    right_color: [f64; 4],
+    blend: Blend,
+    coloring: Coloring,
+}
+
+impl Segment {
+    /// Computes how far between the left and right colors the segment is at `position`, which
+    /// must be within the segment.
+    fn blend_factor(&self, position: f64) -> f64 {
+        let width = self.right - self.left;
+        if width <= 0.0 {
+            return 0.0;
+        }
+        let pos = (position - self.left) / width;
+        let middle = ((self.middle - self.left) / width).clamp(0.0, 1.0);
+        let linear = if pos <= middle {
+            if middle > 0.0 {
+                0.5 * pos / middle
+            } else {
+                0.0
+            }
+        } else if middle < 1.0 {
+            0.5 + 0.5 * (pos - middle) / (1.0 - middle)
+        } else {
+            1.0
+        };
+        match self.blend {
+            Blend::Linear => linear,
+            Blend::Curved => {
+                if middle <= 0.0 {
+                    1.0
+                } else if middle >= 1.0 {
+                    0.0
+                } else {
+                    pos.powf(0.5f64.ln() / middle.ln())
+                }
+            }
+            Blend::Sine => ((-PI / 2.0 + PI * linear).sin() + 1.0) / 2.0,
+            Blend::SphereIncreasing => (1.0 - (linear - 1.0) * (linear - 1.0)).sqrt(),
+            Blend::SphereDecreasing => 1.0 - (1.0 - linear * linear).sqrt(),
+            Blend::Step => {
+                if pos >= middle {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    fn color_at(&self, position: f64) -> ColorU8 {
+        let factor = self.blend_factor(position);
+        let (a, b) = (self.left_color, self.right_color);
+        let rgb = match self.coloring {
+            Coloring::Rgb => [
+                a[0] + (b[0] - a[0]) * factor,
+                a[1] + (b[1] - a[1]) * factor,
+                a[2] + (b[2] - a[2]) * factor,
+            ],
+            Coloring::HsvCounterClockwise | Coloring::HsvClockwise => {
+                let hsv_a = colorspace::rgb_to_hsv([a[0], a[1], a[2]]);
+                let hsv_b = colorspace::rgb_to_hsv([b[0], b[1], b[2]]);
+                let mut delta = hsv_b[0] - hsv_a[0];
+                if self.coloring == Coloring::HsvCounterClockwise && delta < 0.0 {
+                    delta += 360.0;
+                } else if self.coloring == Coloring::HsvClockwise && delta > 0.0 {
+                    delta -= 360.0;
+                }
+                colorspace::hsv_to_rgb([
+                    (hsv_a[0] + delta * factor).rem_euclid(360.0),
+                    hsv_a[1] + (hsv_b[1] - hsv_a[1]) * factor,
+                    hsv_a[2] + (hsv_b[2] - hsv_a[2]) * factor,
+                ])
+            }
+        };
+        let alpha = a[3] + (b[3] - a[3]) * factor;
+        to_color_u8([rgb[0], rgb[1], rgb[2], alpha])
+    }
+
+    /// Converts the segment into the stops that reproduce it.
+    fn stops(&self) -> Vec<ColorStop> {
+        if self.blend == Blend::Linear && self.coloring == Coloring::Rgb {
+            let mid = [0, 1, 2, 3].map(|i| (self.left_color[i] + self.right_color[i]) / 2.0);
+            vec![
+                ColorStop::new(self.left, to_color_u8(self.left_color)),
+                ColorStop::new(self.middle, to_color_u8(mid)),
+                ColorStop::new(self.right, to_color_u8(self.right_color)),
+            ]
+        } else {
+            (0..=SAMPLES_PER_SEGMENT)
+                .map(|i| {
+                    let position = self.left
+                        + (self.right - self.left) * i as f64 / SAMPLES_PER_SEGMENT as f64;
+                    ColorStop::new(position, self.color_at(position))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Parses the contents of a `.ggr` file into a palette.
+pub fn parse_ggr(text: &str) -> Result<Palette, PaletteError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()));
+
+    match lines.next() {
+        Some((_, "GIMP Gradient")) => {}
+        _ => {
+            return Err(parse_error(
+                1,
+                "expected the file to start with \"GIMP Gradient\"".to_string(),
+            ))
+        }
+    }
+
+    let (mut line_num, mut line) = lines
+        .next()
+        .ok_or_else(|| parse_error(1, "missing the number of segments".to_string()))?;
+    if line.starts_with("Name:") {
+        let next = lines
+            .next()
+            .ok_or_else(|| parse_error(line_num, "missing the number of segments".to_string()))?;
+        line_num = next.0;
+        line = next.1;
+    }
+    let count: usize = line.parse().map_err(|_| {
+        parse_error(
+            line_num,
+            format!("expected the number of segments, found {:?}", line),
+        )
+    })?;
+    if count == 0 {
+        return Err(parse_error(
+            line_num,
+            "a gradient needs at least one segment".to_string(),
+        ));
+    }
+
+    let mut stops = Vec::new();
+    for segment_num in 0..count {
+        let (line_num, line) = lines.next().ok_or_else(|| {
+            parse_error(
+                line_num,
+                format!(
+                    "expected {} segments, but the file only contains {}",
+                    count, segment_num
+                ),
+            )
+        })?;
+        stops.extend(parse_segment(line_num, line)?.stops());
+    }
+    Ok(Palette::new(stops).with_interpolation(Interpolation::Rgb))
+}
+
+fn parse_segment(line_num: usize, line: &str) -> Result<Segment, PaletteError> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 13 {
+        return Err(parse_error(
+            line_num,
+            format!("a segment needs at least 13 fields, found {}", fields.len()),
+        ));
+    }
+    let mut numbers = [0.0; 11];
+    for (number, field) in numbers.iter_mut().zip(&fields) {
+        *number = field
+            .parse()
+            .map_err(|_| parse_error(line_num, format!("expected a number, found {:?}", field)))?;
+    }
+    let blend = match fields[11] {
+        "0" => Blend::Linear,
+        "1" => Blend::Curved,
+        "2" => Blend::Sine,
+        "3" => Blend::SphereIncreasing,
+        "4" => Blend::SphereDecreasing,
+        "5" => Blend::Step,
+        other => {
+            return Err(parse_error(
+                line_num,
+                format!("unknown blend function {:?}", other),
+            ))
+        }
+    };
+    let coloring = match fields[12] {
+        "0" => Coloring::Rgb,
+        "1" => Coloring::HsvCounterClockwise,
+        "2" => Coloring::HsvClockwise,
+        other => {
+            return Err(parse_error(
+                line_num,
+                format!("unknown coloring type {:?}", other),
+            ))
+        }
+    };
+    let (left, middle, right) = (numbers[0], numbers[1], numbers[2]);
+    if !(0.0..=1.0).contains(&left)
+        || !(0.0..=1.0).contains(&right)
+        || left > right
+        || middle < left
+        || middle > right
+    {
+        return Err(parse_error(
+            line_num,
+            format!(
+                "segment positions must satisfy 0 <= left <= middle <= right <= 1, found {} {} {}",
+                left, middle, right
+            ),
+        ));
+    }
+    Ok(Segment {
+        left,
+        middle,
+        right,
+        left_color: [numbers[3], numbers[4], numbers[5], numbers[6]],
+        right_color: [numbers[7], numbers[8], numbers[9], numbers[10]],
+        blend,
+        coloring,
+    })
+}
+
+/// Writes `palette` as a `.ggr` file made of linear RGB segments.
+///
+/// Palettes that interpolate in some other color space are resampled first, and positions
+/// outside of 0.0 to 1.0 are lost, since GIMP gradients do not repeat.
+pub fn write_ggr(palette: &Palette, name: &str) -> String {
+    let resampled;
+    let palette = if palette.interpolation() == Interpolation::Rgb {
+        palette
+    } else {
+        resampled = Palette::uniform(&palette.color_range(WRITE_SAMPLES));
+        &resampled
+    };
+
+    // Extend the first and last stops out to the edges, the same way a clamped palette would.
+    let mut stops = palette.stops().to_vec();
+    if stops[0].position > 0.0 {
+        stops.insert(0, ColorStop::new(0.0, stops[0].color));
+    }
+    if stops[stops.len() - 1].position < 1.0 {
+        stops.push(ColorStop::new(1.0, stops[stops.len() - 1].color));
+    }
+    let mut segments: Vec<(ColorStop, ColorStop)> = stops
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|(left, right)| {
+            left.position < right.position && left.position < 1.0 && right.position > 0.0
+        })
+        .collect();
+    if segments.is_empty() {
+        // Every stop is at the same position.
+        segments.push((
+            ColorStop::new(0.0, stops[0].color),
+            ColorStop::new(1.0, stops[stops.len() - 1].color),
+        ));
+    }
+
+    let mut out = format!("GIMP Gradient\nName: {}\n{}\n", name, segments.len());
+    for (left, right) in segments {
+        let (l, r) = (left.position.max(0.0), right.position.min(1.0));
+        out.push_str(&format!("{:.6} {:.6} {:.6}", l, (l + r) / 2.0, r));
+        for component in left.color.0.iter().chain(right.color.0.iter()) {
+            out.push_str(&format!(" {:.6}", f64::from(*component) / 255.0));
+        }
+        out.push_str(" 0 0\n");
+    }
+    out
+}
+
+fn to_color_u8(color: [f64; 4]) -> ColorU8 {
+    let to_u8 = |c: f64| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+    ColorU8([
+        to_u8(color[0]),
+        to_u8(color[1]),
+        to_u8(color[2]),
+        to_u8(color[3]),
+    ])
+}
+
+fn parse_error(line: usize, message: String) -> PaletteError {
+    PaletteError::Parse {
+        format: PaletteFormat::GimpGradient,
+        line: Some(line),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::palette::NamedPalette;
+
+    const BLACK: ColorU8 = ColorU8([0, 0, 0, 255]);
+    const RED: ColorU8 = ColorU8([255, 0, 0, 255]);
+    const WHITE: ColorU8 = ColorU8([255, 255, 255, 255]);
+
+    #[test]
+    fn test_parse_linear_rgb() {
+        let palette = parse_ggr(
+            "GIMP Gradient\n\
+             Name: Black to red to white\n\
+             2\n\
+             0.0 0.25 0.5 0 0 0 1 1 0 0 1 0 0\n\
+             0.5 0.75 1.0 1 0 0 1 1 1 1 1 0 0\n",
+        )
+        .unwrap();
+        assert_eq!(palette.stops().len(), 6);
+        assert_eq!(palette.color_at(0.0), BLACK);
+        assert_eq!(palette.color_at(0.25), ColorU8([128, 0, 0, 255]));
+        assert_eq!(palette.color_at(0.5), RED);
+        assert_eq!(palette.color_at(1.0), WHITE);
+    }
+
+    #[test]
+    fn test_parse_without_name() {
+        let palette = parse_ggr("GIMP Gradient\n1\n0 0.5 1 0 0 0 1 1 1 1 1 0 0 0 0\n").unwrap();
+        assert_eq!(palette.color_at(1.0), WHITE);
+    }
+
+    #[test]
+    fn test_parse_sampled_segments() {
+        // A step at the midpoint
+        let palette =
+            parse_ggr("GIMP Gradient\nName: Step\n1\n0 0.5 1 0 0 0 1 1 1 1 1 5 0\n").unwrap();
+        assert_eq!(palette.stops().len(), SAMPLES_PER_SEGMENT + 1);
+        assert_eq!(palette.color_at(0.25), BLACK);
+        assert_eq!(palette.color_at(0.75), WHITE);
+
+        // Going counter-clockwise from red to blue passes through green.
+        let palette =
+            parse_ggr("GIMP Gradient\nName: Hues\n1\n0 0.5 1 1 0 0 1 0 0 1 1 0 1\n").unwrap();
+        assert_eq!(palette.color_at(0.5), ColorU8([0, 255, 0, 255]));
+        // Going clockwise from red to blue passes through magenta.
+        let palette =
+            parse_ggr("GIMP Gradient\nName: Hues\n1\n0 0.5 1 1 0 0 1 0 0 1 1 0 2\n").unwrap();
+        assert_eq!(palette.color_at(0.5), ColorU8([255, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original = Palette::uniform(&[BLACK, RED, WHITE]);
+        let text = write_ggr(&original, "Black to red to white");
+        assert!(text.starts_with("GIMP Gradient\nName: Black to red to white\n2\n"));
+        let palette = parse_ggr(&text).unwrap();
+        for i in 0..=10 {
+            let position = f64::from(i) / 10.0;
+            assert_eq!(palette.color_at(position), original.color_at(position));
+        }
+
+        let original = NamedPalette::Sunset.palette();
+        let palette = parse_ggr(&write_ggr(&original, "Sunset")).unwrap();
+        for i in 0..=10 {
+            let position = f64::from(i) / 10.0;
+            let (a, b) = (palette.color_at(position), original.color_at(position));
+            for c in 0..4 {
+                assert!((i16::from(a.0[c]) - i16::from(b.0[c])).abs() <= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let expect_error = |text: &str, expected: &str| {
+            assert_eq!(parse_ggr(text).unwrap_err().to_string(), expected);
+        };
+        expect_error(
+            "Gradient\n",
+            "GIMP gradient, line 1: expected the file to start with \"GIMP Gradient\"",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\nmany\n",
+            "GIMP gradient, line 3: expected the number of segments, found \"many\"",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\n2\n0 0.25 0.5 0 0 0 1 1 1 1 1 0 0\n",
+            "GIMP gradient, line 3: expected 2 segments, but the file only contains 1",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\n1\n0 0.5 1 0 0 0 1 1 1 1 1\n",
+            "GIMP gradient, line 4: a segment needs at least 13 fields, found 11",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\n1\n0 0.5 1 0 0 zero 1 1 1 1 1 0 0\n",
+            "GIMP gradient, line 4: expected a number, found \"zero\"",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\n1\n0 0.5 1 0 0 0 1 1 1 1 1 9 0\n",
+            "GIMP gradient, line 4: unknown blend function \"9\"",
+        );
+        expect_error(
+            "GIMP Gradient\nName: x\n1\n0.5 0.25 1 0 0 0 1 1 1 1 1 0 0\n",
+            "GIMP gradient, line 4: segment positions must satisfy \
+             0 <= left <= middle <= right <= 1, found 0.5 0.25 1",
+        );
+    }
+}
@@ -14,6 +14,12 @@
 
 //! Color-related constants and functions.
 
+pub mod colorspace;
+pub mod fractint;
+pub mod ggr;
+pub mod palette;
+pub mod palettefile;
+
 /// Colors that work with `graphics` functions, which want color as vectors of f32.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ColorF32(pub [f32; 4]);
@@ -22,6 +28,29 @@ pub struct ColorF32(pub [f32; 4]);
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ColorU8(pub [u8; 4]);
 
+impl From<ColorU8> for ColorF32 {
+    fn from(color: ColorU8) -> ColorF32 {
+        ColorF32([
+            f32::from(color.0[0]) / 255.0,
+            f32::from(color.0[1]) / 255.0,
+            f32::from(color.0[2]) / 255.0,
+            f32::from(color.0[3]) / 255.0,
+        ])
+    }
+}
+
+impl From<ColorF32> for ColorU8 {
+    fn from(color: ColorF32) -> ColorU8 {
+        let to_u8 = |c: f32| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+        ColorU8([
+            to_u8(color.0[0]),
+            to_u8(color.0[1]),
+            to_u8(color.0[2]),
+            to_u8(color.0[3]),
+        ])
+    }
+}
+
 /// Black for use with `graphics`' functions
 pub const BLACK_F32: ColorF32 = ColorF32([0.0, 0.0, 0.0, 1.0]);
 /// Grey for use with `graphics`' functions
@@ -117,6 +146,13 @@ mod test {
         assert!(range.len() == 1);
     }
 
+    #[test]
+    fn test_convert_between_u8_and_f32() {
+        assert_eq!(ColorF32::from(WHITE_U8), WHITE_F32);
+        assert_eq!(ColorU8::from(BLACK_F32), BLACK_U8);
+        assert_eq!(ColorU8::from(GREY_F32), ColorU8([128, 128, 128, 255]));
+    }
+
     #[test]
     fn test_linear_two() {
         let black = ColorU8([0, 0, 0, 255]);
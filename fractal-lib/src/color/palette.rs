@@ -0,0 +1,482 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Multi-stop color gradients.
+//!
+//! A `Palette` is a sorted list of `ColorStop`s that can be sampled at any continuous position.
+//! Positions between two stops are interpolated in one of several color spaces, and positions
+//! outside of 0.0 to 1.0 are folded back into that range according to the palette's
+//! `RepeatMode`.
+
+use super::colorspace;
+use super::ColorU8;
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The color space used to blend between two neighboring stops.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum Interpolation {
+    /// Blend the gamma-encoded red, green, and blue components directly.
+    Rgb,
+    /// Blend hue (along the shorter way around the color wheel), saturation, and value.
+    Hsv,
+    /// Blend hue (along the shorter way around the color wheel), saturation, and lightness.
+    Hsl,
+    /// Blend in CIELAB, which is closer to perceptually uniform than RGB.
+    Lab,
+    /// Blend in OKLab, a more recent perceptually uniform color space.
+    Oklab,
+}
+
+/// How positions outside of 0.0 to 1.0 are mapped back onto the palette.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum RepeatMode {
+    /// Positions below 0.0 use the first stop and positions above 1.0 use the last stop.
+    Clamp,
+    /// The palette repeats cyclically: 1.25 looks up the same color as 0.25.
+    Repeat,
+    /// The palette reverses direction every time it repeats: 1.25 looks up the same color as
+    /// 0.75.
+    Mirror,
+}
+
+/// A color placed at a position along a `Palette`. Positions are expected to be between 0.0 and
+/// 1.0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    pub position: f64,
+    pub color: ColorU8,
+}
+
+impl ColorStop {
+    pub fn new(position: f64, color: ColorU8) -> ColorStop {
+        ColorStop { position, color }
+    }
+}
+
+/// A gradient made up of an arbitrary number of color stops.
+///
+/// ```
+/// use fractal_lib::color::palette::{ColorStop, Interpolation, Palette, RepeatMode};
+/// use fractal_lib::color::ColorU8;
+///
+/// let palette = Palette::new(vec![
+///     ColorStop::new(0.0, ColorU8([0, 0, 0, 255])),
+///     ColorStop::new(1.0, ColorU8([255, 255, 255, 255])),
+/// ])
+/// .with_interpolation(Interpolation::Rgb)
+/// .with_repeat(RepeatMode::Mirror);
+///
+/// assert_eq!(palette.color_at(0.5), ColorU8([128, 128, 128, 255]));
+/// assert_eq!(palette.color_at(1.5), ColorU8([128, 128, 128, 255]));
+/// assert_eq!(palette.color_at(2.0), ColorU8([0, 0, 0, 255]));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    stops: Vec<ColorStop>,
+    interpolation: Interpolation,
+    repeat: RepeatMode,
+}
+
+impl Palette {
+    /// Creates a new palette from a set of stops, which do not need to be sorted. It defaults to
+    /// interpolating in RGB and to clamping positions outside of 0.0 to 1.0.
+    ///
+    /// Panics if `stops` is empty.
+    pub fn new(stops: Vec<ColorStop>) -> Palette {
+        if stops.is_empty() {
+            panic!("A palette needs at least one color stop");
+        }
+        let mut stops = stops;
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Palette {
+            stops,
+            interpolation: Interpolation::Rgb,
+            repeat: RepeatMode::Clamp,
+        }
+    }
+
+    /// Creates a palette that spaces `colors` evenly between 0.0 and 1.0.
+    ///
+    /// Panics if `colors` is empty.
+    pub fn uniform(colors: &[ColorU8]) -> Palette {
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+        Palette::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| ColorStop::new(i as f64 / last, *color))
+                .collect(),
+        )
+    }
+
+    /// Set the color space used between stops.
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Palette {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Set how positions outside of 0.0 to 1.0 are handled.
+    pub fn with_repeat(mut self, repeat: RepeatMode) -> Palette {
+        self.repeat = repeat;
+        self
+    }
+
+    /// The palette's stops, sorted by position.
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    /// Looks up the color at `position`.
+    pub fn color_at(&self, position: f64) -> ColorU8 {
+        let t = match self.repeat {
+            RepeatMode::Clamp => position.clamp(0.0, 1.0),
+            RepeatMode::Repeat => position.rem_euclid(1.0),
+            RepeatMode::Mirror => {
+                let t = position.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        };
+        if t.is_nan() {
+            return self.stops[0].color;
+        }
+        match self.stops.iter().position(|stop| stop.position > t) {
+            Some(0) => self.stops[0].color,
+            None => self.stops[self.stops.len() - 1].color,
+            Some(i) => {
+                let before = &self.stops[i - 1];
+                let after = &self.stops[i];
+                let fraction = (t - before.position) / (after.position - before.position);
+                mix(before.color, after.color, fraction, self.interpolation)
+            }
+        }
+    }
+
+    /// Samples `count` evenly spaced colors from 0.0 to 1.0 (inclusive), for renderers that want
+    /// to index into a lookup table.
+    pub fn color_range(&self, count: usize) -> Vec<ColorU8> {
+        match count {
+            0 => vec![],
+            1 => vec![self.color_at(0.0)],
+            _ => (0..count)
+                .map(|i| self.color_at(i as f64 / (count - 1) as f64))
+                .collect(),
+        }
+    }
+}
+
+/// Blends two colors, where a `fraction` of 0.0 is `a` and 1.0 is `b`.
+fn mix(a: ColorU8, b: ColorU8, fraction: f64, interpolation: Interpolation) -> ColorU8 {
+    let rgb_a = [
+        f64::from(a.0[0]) / 255.0,
+        f64::from(a.0[1]) / 255.0,
+        f64::from(a.0[2]) / 255.0,
+    ];
+    let rgb_b = [
+        f64::from(b.0[0]) / 255.0,
+        f64::from(b.0[1]) / 255.0,
+        f64::from(b.0[2]) / 255.0,
+    ];
+    let rgb = match interpolation {
+        Interpolation::Rgb => lerp3(rgb_a, rgb_b, fraction),
+        Interpolation::Hsv => colorspace::hsv_to_rgb(lerp_hue(
+            colorspace::rgb_to_hsv(rgb_a),
+            colorspace::rgb_to_hsv(rgb_b),
+            fraction,
+        )),
+        Interpolation::Hsl => colorspace::hsl_to_rgb(lerp_hue(
+            colorspace::rgb_to_hsl(rgb_a),
+            colorspace::rgb_to_hsl(rgb_b),
+            fraction,
+        )),
+        Interpolation::Lab => colorspace::lab_to_rgb(lerp3(
+            colorspace::rgb_to_lab(rgb_a),
+            colorspace::rgb_to_lab(rgb_b),
+            fraction,
+        )),
+        Interpolation::Oklab => colorspace::oklab_to_rgb(lerp3(
+            colorspace::rgb_to_oklab(rgb_a),
+            colorspace::rgb_to_oklab(rgb_b),
+            fraction,
+        )),
+    };
+    let alpha = lerp(f64::from(a.0[3]), f64::from(b.0[3]), fraction);
+    ColorU8([
+        to_u8(rgb[0] * 255.0),
+        to_u8(rgb[1] * 255.0),
+        to_u8(rgb[2] * 255.0),
+        to_u8(alpha),
+    ])
+}
+
+fn lerp(a: f64, b: f64, fraction: f64) -> f64 {
+    a + (b - a) * fraction
+}
+
+fn lerp3(a: [f64; 3], b: [f64; 3], fraction: f64) -> [f64; 3] {
+    [
+        lerp(a[0], b[0], fraction),
+        lerp(a[1], b[1], fraction),
+        lerp(a[2], b[2], fraction),
+    ]
+}
+
+/// Interpolates a hue-based triple. The hue takes the shorter way around the color wheel, and
+/// greys (which have no meaningful hue) borrow the hue of the other color.
+fn lerp_hue(a: [f64; 3], b: [f64; 3], fraction: f64) -> [f64; 3] {
+    let (hue_a, hue_b) = match (a[1] > 0.0, b[1] > 0.0) {
+        (false, true) => (b[0], b[0]),
+        (true, false) => (a[0], a[0]),
+        _ => (a[0], b[0]),
+    };
+    let mut delta = hue_b - hue_a;
+    if delta > 180.0 {
+        delta -= 360.0;
+    } else if delta < -180.0 {
+        delta += 360.0;
+    }
+    [
+        (hue_a + delta * fraction).rem_euclid(360.0),
+        lerp(a[1], b[1], fraction),
+        lerp(a[2], b[2], fraction),
+    ]
+}
+
+fn to_u8(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// The palettes that ship with the library.
+///
+/// Like `SelectedFractal`, these can be listed with `NamedPalette::iter()` and parsed from their
+/// lowercase name with `NamedPalette::from_str()`.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum NamedPalette {
+    Fire,
+    Greyscale,
+    Ocean,
+    Rainbow,
+    Sunset,
+    UltraFractal,
+    Viridis,
+}
+
+impl NamedPalette {
+    /// The full display name for each palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedPalette::Fire => "Fire",
+            NamedPalette::Greyscale => "Greyscale",
+            NamedPalette::Ocean => "Ocean",
+            NamedPalette::Rainbow => "Rainbow",
+            NamedPalette::Sunset => "Sunset",
+            NamedPalette::UltraFractal => "Ultra Fractal",
+            NamedPalette::Viridis => "Viridis",
+        }
+    }
+
+    /// Builds the palette.
+    pub fn palette(self) -> Palette {
+        fn stop(position: f64, r: u8, g: u8, b: u8) -> ColorStop {
+            ColorStop::new(position, ColorU8([r, g, b, 255]))
+        }
+        match self {
+            NamedPalette::Fire => Palette::new(vec![
+                stop(0.0, 0, 0, 0),
+                stop(0.3, 128, 0, 0),
+                stop(0.6, 255, 128, 0),
+                stop(0.85, 255, 255, 0),
+                stop(1.0, 255, 255, 255),
+            ]),
+            NamedPalette::Greyscale => {
+                Palette::new(vec![stop(0.0, 0, 0, 0), stop(1.0, 255, 255, 255)])
+            }
+            NamedPalette::Ocean => Palette::new(vec![
+                stop(0.0, 0, 0, 48),
+                stop(0.4, 0, 96, 160),
+                stop(0.75, 64, 200, 200),
+                stop(1.0, 255, 255, 255),
+            ])
+            .with_interpolation(Interpolation::Oklab),
+            NamedPalette::Rainbow => Palette::new(vec![
+                stop(0.0, 255, 0, 0),
+                stop(1.0 / 3.0, 0, 255, 0),
+                stop(2.0 / 3.0, 0, 0, 255),
+                stop(1.0, 255, 0, 0),
+            ])
+            .with_interpolation(Interpolation::Hsv)
+            .with_repeat(RepeatMode::Repeat),
+            NamedPalette::Sunset => Palette::new(vec![
+                stop(0.0, 11, 13, 58),
+                stop(0.3, 91, 42, 134),
+                stop(0.6, 224, 69, 123),
+                stop(0.85, 255, 179, 71),
+                stop(1.0, 255, 243, 176),
+            ])
+            .with_interpolation(Interpolation::Lab),
+            NamedPalette::UltraFractal => Palette::new(vec![
+                stop(0.0, 0, 7, 100),
+                stop(0.16, 32, 107, 203),
+                stop(0.42, 237, 255, 255),
+                stop(0.6425, 255, 170, 0),
+                stop(0.8575, 0, 2, 0),
+                stop(1.0, 0, 7, 100),
+            ])
+            .with_repeat(RepeatMode::Repeat),
+            NamedPalette::Viridis => Palette::new(vec![
+                stop(0.0, 68, 1, 84),
+                stop(0.25, 59, 82, 139),
+                stop(0.5, 33, 145, 140),
+                stop(0.75, 94, 201, 98),
+                stop(1.0, 253, 231, 37),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    const BLACK: ColorU8 = ColorU8([0, 0, 0, 255]);
+    const WHITE: ColorU8 = ColorU8([255, 255, 255, 255]);
+    const RED: ColorU8 = ColorU8([255, 0, 0, 255]);
+    const BLUE: ColorU8 = ColorU8([0, 0, 255, 255]);
+
+    #[test]
+    #[should_panic(expected = "at least one color stop")]
+    fn test_empty_palette() {
+        Palette::new(vec![]);
+    }
+
+    #[test]
+    fn test_single_stop() {
+        let palette = Palette::new(vec![ColorStop::new(0.5, RED)]);
+        assert_eq!(palette.color_at(0.0), RED);
+        assert_eq!(palette.color_at(0.5), RED);
+        assert_eq!(palette.color_at(1.0), RED);
+    }
+
+    #[test]
+    fn test_stops_are_sorted() {
+        let palette = Palette::new(vec![ColorStop::new(1.0, WHITE), ColorStop::new(0.0, BLACK)]);
+        assert_eq!(palette.stops()[0].color, BLACK);
+        assert_eq!(palette.color_at(0.0), BLACK);
+        assert_eq!(palette.color_at(1.0), WHITE);
+    }
+
+    #[test]
+    fn test_multiple_stops() {
+        let palette = Palette::uniform(&[BLACK, RED, WHITE]);
+        assert_eq!(palette.color_at(0.0), BLACK);
+        assert_eq!(palette.color_at(0.25), ColorU8([128, 0, 0, 255]));
+        assert_eq!(palette.color_at(0.5), RED);
+        assert_eq!(palette.color_at(0.75), ColorU8([255, 128, 128, 255]));
+        assert_eq!(palette.color_at(1.0), WHITE);
+    }
+
+    #[test]
+    fn test_repeat_modes() {
+        let clamp = Palette::uniform(&[BLACK, WHITE]);
+        assert_eq!(clamp.color_at(-1.0), BLACK);
+        assert_eq!(clamp.color_at(1.75), WHITE);
+
+        let repeat = clamp.clone().with_repeat(RepeatMode::Repeat);
+        assert_eq!(repeat.color_at(1.25), repeat.color_at(0.25));
+        assert_eq!(repeat.color_at(-0.75), repeat.color_at(0.25));
+
+        let mirror = clamp.with_repeat(RepeatMode::Mirror);
+        assert_eq!(mirror.color_at(1.25), mirror.color_at(0.75));
+        assert_eq!(mirror.color_at(-0.25), mirror.color_at(0.25));
+        assert_eq!(mirror.color_at(3.0), WHITE);
+    }
+
+    #[test]
+    fn test_nan_position() {
+        let palette = Palette::uniform(&[RED, BLUE]);
+        assert_eq!(palette.color_at(f64::NAN), RED);
+    }
+
+    #[test]
+    fn test_hsv_takes_the_short_way_around() {
+        // Red to blue through magenta rather than through green.
+        let palette = Palette::uniform(&[RED, BLUE]).with_interpolation(Interpolation::Hsv);
+        assert_eq!(palette.color_at(0.5), ColorU8([255, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_hsl_grey_borrows_hue() {
+        let palette = Palette::uniform(&[WHITE, RED]).with_interpolation(Interpolation::Hsl);
+        let mid = palette.color_at(0.5);
+        // A pinkish red, not a shade of some other hue.
+        assert!(mid.0[0] > mid.0[1]);
+        assert_eq!(mid.0[1], mid.0[2]);
+    }
+
+    #[test]
+    fn test_perceptual_interpolation_endpoints() {
+        for interpolation in Interpolation::iter() {
+            let palette = Palette::uniform(&[RED, BLUE]).with_interpolation(interpolation);
+            assert_eq!(palette.color_at(0.0), RED, "{:?}", interpolation);
+            assert_eq!(palette.color_at(1.0), BLUE, "{:?}", interpolation);
+        }
+    }
+
+    #[test]
+    fn test_alpha_is_interpolated() {
+        let palette = Palette::uniform(&[ColorU8([0, 0, 0, 0]), ColorU8([0, 0, 0, 255])]);
+        assert_eq!(palette.color_at(0.5).0[3], 128);
+    }
+
+    #[test]
+    fn test_color_range() {
+        let palette = NamedPalette::Greyscale.palette();
+        let range = palette.color_range(256);
+        assert_eq!(range.len(), 256);
+        assert_eq!(range[0], BLACK);
+        assert_eq!(range[10], ColorU8([10, 10, 10, 255]));
+        assert_eq!(range[255], WHITE);
+        assert!(palette.color_range(0).is_empty());
+        assert_eq!(palette.color_range(1), vec![BLACK]);
+    }
+
+    #[test]
+    fn test_named_palettes() {
+        use std::str::FromStr;
+        for named in NamedPalette::iter() {
+            let slug: &'static str = named.into();
+            assert_eq!(NamedPalette::from_str(slug).unwrap(), named);
+            assert!(!named.palette().stops().is_empty());
+        }
+        assert_eq!(
+            NamedPalette::from_str("ultrafractal").unwrap(),
+            NamedPalette::UltraFractal
+        );
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading and saving palettes from files.
+//!
+//! The file format is chosen by extension:
+//!
+//! * `.map`: Fractint maps (see `fractint`)
+//! * `.ggr`: GIMP gradients (see `ggr`)
+//! * `.toml`: this library's own format, which stores a `Palette` exactly:
+//!
+//! ```toml
+//! name = "Black to red"
+//! interpolation = "oklab"  # optional, defaults to "rgb"
+//! repeat = "mirror"        # optional, defaults to "clamp"
+//!
+//! [[stops]]
+//! position = 0.0
+//! color = "#000000"
+//!
+//! [[stops]]
+//! position = 1.0
+//! color = "#ff000080"      # an optional fourth byte is alpha
+//! ```
+
+use super::fractint;
+use super::ggr;
+use super::palette::{ColorStop, Interpolation, Palette, RepeatMode};
+use super::ColorU8;
+use serde::{Deserialize, Serialize};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The palette file formats that can be read and written.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PaletteFormat {
+    FractintMap,
+    GimpGradient,
+    Toml,
+}
+
+impl PaletteFormat {
+    /// Determines the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<PaletteFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "map" => Some(PaletteFormat::FractintMap),
+            "ggr" => Some(PaletteFormat::GimpGradient),
+            "toml" => Some(PaletteFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Parses the contents of a palette file.
+    pub fn parse(self, text: &str) -> Result<Palette, PaletteError> {
+        match self {
+            PaletteFormat::FractintMap => fractint::parse_map(text),
+            PaletteFormat::GimpGradient => ggr::parse_ggr(text),
+            PaletteFormat::Toml => parse_toml(text),
+        }
+    }
+
+    /// Writes a palette in this format. Formats that cannot represent everything about a palette
+    /// write the closest approximation they can.
+    pub fn write(self, palette: &Palette, name: &str) -> String {
+        match self {
+            PaletteFormat::FractintMap => fractint::write_map(palette, name),
+            PaletteFormat::GimpGradient => ggr::write_ggr(palette, name),
+            PaletteFormat::Toml => write_toml(palette, name),
+        }
+    }
+}
+
+impl fmt::Display for PaletteFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PaletteFormat::FractintMap => "Fractint map",
+            PaletteFormat::GimpGradient => "GIMP gradient",
+            PaletteFormat::Toml => "TOML palette",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Everything that can go wrong while loading or saving a palette.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// The file could not be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// The file's extension does not match any supported format.
+    UnknownFormat { path: PathBuf },
+    /// The file's contents are malformed. `line` starts at 1.
+    Parse {
+        format: PaletteFormat,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaletteError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            PaletteError::UnknownFormat { path } => write!(
+                f,
+                "{}: unknown palette format, expected a .map, .ggr, or .toml file",
+                path.display()
+            ),
+            PaletteError::Parse {
+                format,
+                line: Some(line),
+                message,
+            } => write!(f, "{}, line {}: {}", format, line, message),
+            PaletteError::Parse {
+                format,
+                line: None,
+                message,
+            } => write!(f, "{}: {}", format, message),
+        }
+    }
+}
+
+impl error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PaletteError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Loads a palette, choosing the format based on the file's extension.
+pub fn load_palette(path: &Path) -> Result<Palette, PaletteError> {
+    let format = PaletteFormat::from_path(path).ok_or_else(|| PaletteError::UnknownFormat {
+        path: path.to_path_buf(),
+    })?;
+    let text = fs::read_to_string(path).map_err(|source| PaletteError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    format.parse(&text)
+}
+
+/// Saves a palette, choosing the format based on the file's extension.
+pub fn save_palette(path: &Path, palette: &Palette, name: &str) -> Result<(), PaletteError> {
+    let format = PaletteFormat::from_path(path).ok_or_else(|| PaletteError::UnknownFormat {
+        path: path.to_path_buf(),
+    })?;
+    fs::write(path, format.write(palette, name)).map_err(|source| PaletteError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[derive(Deserialize, Serialize)]
+struct TomlPalette {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    interpolation: Option<String>,
+    #[serde(default)]
+    repeat: Option<String>,
+    stops: Vec<TomlStop>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct TomlStop {
+    position: f64,
+    color: String,
+}
+
+fn parse_toml(text: &str) -> Result<Palette, PaletteError> {
+    let document: TomlPalette = toml::from_str(text).map_err(|e| PaletteError::Parse {
+        format: PaletteFormat::Toml,
+        line: e
+            .span()
+            .map(|span| text[..span.start].matches('\n').count() + 1),
+        message: e.message().to_string(),
+    })?;
+    let error = |message: String| PaletteError::Parse {
+        format: PaletteFormat::Toml,
+        line: None,
+        message,
+    };
+
+    if document.stops.is_empty() {
+        return Err(error("a palette needs at least one stop".to_string()));
+    }
+    let stops = document
+        .stops
+        .iter()
+        .enumerate()
+        .map(|(i, stop)| {
+            if !stop.position.is_finite() {
+                return Err(error(format!(
+                    "stop {} has a position that is not finite",
+                    i + 1
+                )));
+            }
+            let color = parse_hex_color(&stop.color).ok_or_else(|| {
+                error(format!(
+                    "stop {} has color {:?}, expected \"#rrggbb\" or \"#rrggbbaa\"",
+                    i + 1,
+                    stop.color
+                ))
+            })?;
+            Ok(ColorStop::new(stop.position, color))
+        })
+        .collect::<Result<Vec<ColorStop>, PaletteError>>()?;
+
+    let mut palette = Palette::new(stops);
+    if let Some(interpolation) = &document.interpolation {
+        palette =
+            palette.with_interpolation(Interpolation::from_str(interpolation).map_err(|_| {
+                error(format!(
+                    "unknown interpolation {:?}, expected one of: {}",
+                    interpolation,
+                    <Interpolation as strum::VariantNames>::VARIANTS.join(", ")
+                ))
+            })?);
+    }
+    if let Some(repeat) = &document.repeat {
+        palette = palette.with_repeat(RepeatMode::from_str(repeat).map_err(|_| {
+            error(format!(
+                "unknown repeat mode {:?}, expected one of: {}",
+                repeat,
+                <RepeatMode as strum::VariantNames>::VARIANTS.join(", ")
+            ))
+        })?);
+    }
+    Ok(palette)
+}
+
+fn write_toml(palette: &Palette, name: &str) -> String {
+    let interpolation: &'static str = palette.interpolation().into();
+    let repeat: &'static str = palette.repeat().into();
+    let document = TomlPalette {
+        name: name.to_string(),
+        interpolation: Some(interpolation.to_string()),
+        repeat: Some(repeat.to_string()),
+        stops: palette
+            .stops()
+            .iter()
+            .map(|stop| TomlStop {
+                position: stop.position,
+                color: format_hex_color(stop.color),
+            })
+            .collect(),
+    };
+    toml::to_string(&document).expect("a palette can always be serialized")
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`.
+fn parse_hex_color(text: &str) -> Option<ColorU8> {
+    let hex = text.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let mut color = ColorU8([0, 0, 0, 255]);
+    for (i, component) in color.0.iter_mut().enumerate().take(hex.len() / 2) {
+        *component = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(color)
+}
+
+fn format_hex_color(color: ColorU8) -> String {
+    let [r, g, b, a] = color.0;
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::palette::NamedPalette;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            PaletteFormat::from_path(Path::new("a/b.map")),
+            Some(PaletteFormat::FractintMap)
+        );
+        assert_eq!(
+            PaletteFormat::from_path(Path::new("b.GGR")),
+            Some(PaletteFormat::GimpGradient)
+        );
+        assert_eq!(
+            PaletteFormat::from_path(Path::new("b.toml")),
+            Some(PaletteFormat::Toml)
+        );
+        assert_eq!(PaletteFormat::from_path(Path::new("b.png")), None);
+        assert_eq!(PaletteFormat::from_path(Path::new("toml")), None);
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let palette = PaletteFormat::Toml
+            .parse(
+                "name = \"Test\"\n\
+                 interpolation = \"hsv\"\n\
+                 repeat = \"mirror\"\n\
+                 [[stops]]\n\
+                 position = 1.0\n\
+                 color = \"#FF000080\"\n\
+                 [[stops]]\n\
+                 position = 0.0\n\
+                 color = \"#000000\"\n",
+            )
+            .unwrap();
+        assert_eq!(palette.interpolation(), Interpolation::Hsv);
+        assert_eq!(palette.repeat(), RepeatMode::Mirror);
+        assert_eq!(palette.color_at(0.0), ColorU8([0, 0, 0, 255]));
+        assert_eq!(palette.color_at(1.0), ColorU8([255, 0, 0, 128]));
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        for named in NamedPalette::iter() {
+            let text = PaletteFormat::Toml.write(&named.palette(), named.name());
+            assert!(text.starts_with(&format!("name = \"{}\"\n", named.name())));
+            assert_eq!(PaletteFormat::Toml.parse(&text).unwrap(), named.palette());
+        }
+    }
+
+    #[test]
+    fn test_toml_errors() {
+        let expect_error = |text: &str, expected: &str| {
+            assert_eq!(
+                PaletteFormat::Toml.parse(text).unwrap_err().to_string(),
+                expected
+            );
+        };
+        expect_error(
+            "name = \"x\"\nstops = []\n",
+            "TOML palette: a palette needs at least one stop",
+        );
+        expect_error(
+            "[[stops]]\nposition = 0.0\ncolor = \"red\"\n",
+            "TOML palette: stop 1 has color \"red\", expected \"#rrggbb\" or \"#rrggbbaa\"",
+        );
+        expect_error(
+            "interpolation = \"cmyk\"\n[[stops]]\nposition = 0.0\ncolor = \"#000000\"\n",
+            "TOML palette: unknown interpolation \"cmyk\", expected one of: \
+             rgb, hsv, hsl, lab, oklab",
+        );
+        expect_error(
+            "name = \"x\"\n[[stops]]\nposition = \"zero\"\ncolor = \"#000000\"\n",
+            "TOML palette, line 3: invalid type: string \"zero\", expected f64",
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = std::env::temp_dir().join(format!("fractal-palettes-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let palette = NamedPalette::Ocean.palette();
+        for extension in &["map", "ggr", "toml"] {
+            let path = dir.join(format!("ocean.{}", extension));
+            save_palette(&path, &palette, "Ocean").unwrap();
+            let loaded = load_palette(&path).unwrap();
+            let (a, b) = (loaded.color_at(0.5), palette.color_at(0.5));
+            for c in 0..4 {
+                assert!((i16::from(a.0[c]) - i16::from(b.0[c])).abs() <= 2);
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            load_palette(Path::new("palette.png")),
+            Err(PaletteError::UnknownFormat { .. })
+        ));
+        assert!(matches!(
+            load_palette(&dir.join("missing.map")),
+            Err(PaletteError::Io { .. })
+        ));
+    }
+}
@@ -23,7 +23,7 @@ pub struct CesaroFractal {
     iterations: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LSA {
     F, // move forward
     Q, // corner of the square
@@ -83,12 +83,12 @@ impl LindenmayerSystemDrawingParameters<LSA> for CesaroFractal {
         Point { x: 0.0, y: -0.5 }
     }
 
-    fn interpret_symbol(&self, symbol: LSA) -> TurtleStep {
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
         match symbol {
-            LSA::F => TurtleStep::Forward(self.distance_forward()),
-            LSA::Q => TurtleStep::TurnRad(deg2rad(90.0)),
-            LSA::L => TurtleStep::TurnRad(deg2rad(85.0)),
-            LSA::R => TurtleStep::TurnRad(deg2rad(-85.0)),
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::Q => Some(TurtleStep::TurnRad(deg2rad(90.0))),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(85.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-85.0))),
         }
     }
 }
@@ -23,7 +23,7 @@ pub struct CesaroTriFractal {
     iterations: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LSA {
     F1, // side 1
     F2, // side 2
@@ -89,7 +89,7 @@ impl LindenmayerSystemDrawingParameters<LSA> for CesaroTriFractal {
     }
 
     // It is cleaner to read the Lyndenmayer System Alphabet matcher arms separately.
-    fn interpret_symbol(&self, symbol: LSA) -> TurtleStep {
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
         // use std::f64::consts::SQRT_2;
         use std::f64::consts::PI;
 
@@ -115,14 +115,14 @@ impl LindenmayerSystemDrawingParameters<LSA> for CesaroTriFractal {
         let top_angle = 180.0_f64 - (2.0_f64 * side_angle);
 
         match symbol {
-            LSA::F1 => TurtleStep::Forward(hyp_unit),
-            LSA::F2 => TurtleStep::Forward(side_unit),
-            LSA::F3 => TurtleStep::Forward(side_unit),
-            LSA::Q1 => TurtleStep::TurnRad(deg2rad(180.0_f64 - side_angle)),
-            LSA::Q2 => TurtleStep::TurnRad(deg2rad(180.0_f64 - top_angle)),
-            LSA::Q3 => TurtleStep::TurnRad(deg2rad(180.0_f64 - side_angle)),
-            LSA::L => TurtleStep::TurnRad(deg2rad(base_angle)),
-            LSA::R => TurtleStep::TurnRad(deg2rad(-base_angle)),
+            LSA::F1 => Some(TurtleStep::Forward(hyp_unit)),
+            LSA::F2 => Some(TurtleStep::Forward(side_unit)),
+            LSA::F3 => Some(TurtleStep::Forward(side_unit)),
+            LSA::Q1 => Some(TurtleStep::TurnRad(deg2rad(180.0_f64 - side_angle))),
+            LSA::Q2 => Some(TurtleStep::TurnRad(deg2rad(180.0_f64 - top_angle))),
+            LSA::Q3 => Some(TurtleStep::TurnRad(deg2rad(180.0_f64 - side_angle))),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(base_angle))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-base_angle))),
         }
     }
 }
@@ -13,17 +13,30 @@
 // limitations under the License.
 
 //! Computations and abstractions needed for rendering a dragon fractal.
+//!
+//! The dragon is the paperfolding curve (see `curves::folding`) made by always folding in the same
+//! direction.
 
 use std::f64::consts::PI;
 use std::f64::consts::SQRT_2;
 
+use num::complex::Complex;
+
+use super::folding::{FoldPattern, FoldingCurve};
 use crate::geometry::Point;
 use crate::turtle::*;
 
+pub use super::folding::Turn;
+
+/// Where the turtle is on the integer lattice after drawing some number of unit-length segments
+/// of the dragon curve, when it starts at the origin facing along the positive X axis.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Turn {
-    Left,
-    Right,
+pub struct DragonLatticePoint {
+    /// The end of the last segment, as a Gaussian integer (`x + yi`).
+    pub position: Complex<i64>,
+    /// The direction of the next segment, in quarter turns counter-clockwise from the positive X
+    /// axis (0 to 3).
+    pub direction: u8,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -96,67 +109,111 @@ impl DragonFractal {
             _ => SQRT_2.powf(self.iterations as f64),
         }
     }
+
+    /// Finds where the dragon curve is after `segments` segments in O(log(segments)) time,
+    /// without following each step.
+    ///
+    /// The turns of the dragon curve do not depend on its iteration, so neither does the result,
+    /// as long as the curve has at least `segments` segments. Every pair of segments `2k - 1` and
+    /// `2k` makes a single segment of the dragon scaled by `1 + i`, so each binary digit of
+    /// `segments`, from the most significant, multiplies the position so far by `1 + i` and then
+    /// adds the next segment if the digit is 1. That makes the position a sum of powers of
+    /// `1 + i`, each rotated by a multiple of a quarter turn.
+    pub fn lattice_point(segments: u64) -> DragonLatticePoint {
+        // the number of segments drawn so far, the end of the last of them, and the sum of the
+        // turns after each of them in quarter turns
+        let mut drawn = 0u64;
+        let mut position = Complex::new(0i64, 0);
+        let mut direction = 0u8;
+        for bit in (0..u64::BITS - segments.leading_zeros()).rev() {
+            // the odd turns alternate left and right, and the even turn 2k is the same as turn k
+            position *= Complex::new(1, 1);
+            direction = (direction + (drawn % 2) as u8) % 4;
+            let odd_turn = match drawn % 2 {
+                0 => 1,
+                _ => 3,
+            };
+            drawn *= 2;
+            if segments >> bit & 1 == 1 {
+                position += Complex::i().powu(u32::from(direction));
+                direction = (direction + odd_turn) % 4;
+                drawn += 1;
+            }
+        }
+        DragonLatticePoint {
+            position,
+            direction,
+        }
+    }
+
+    /// The paperfolding curve that always folds in the same direction by a right angle.
+    pub fn folding_curve(self) -> FoldingCurve {
+        FoldingCurve::new(self.iterations, FoldPattern::regular(), PI / 2.0)
+    }
 }
 
 impl TurtleProgram for DragonFractal {
     /// Starts at (0.0, 0.0) and facing 0 degrees along the X axis. Tries to end at (1.0, 0.0).
     fn init_turtle(&self) -> Vec<TurtleStep> {
-        vec![
-            TurtleStep::SetPos(Point { x: 0.0, y: 0.0 }),
-            TurtleStep::SetRad(PI / 4.0 * -(self.iterations as f64)),
-            TurtleStep::Down,
-        ]
+        self.folding_curve().init_turtle()
     }
 
     fn turtle_program_iter(&self) -> TurtleProgramIterator {
-        TurtleProgramIterator::new(Box::new(DragonFractalTurtleProgramIterator {
-            dragon: *self,
-            curr_step: 1,
-            move_next: true,
-        }))
+        self.folding_curve().turtle_program_iter()
     }
-}
-
-/// Iterator that emits the dragon fractal's turtle program as `TurtleStep`s.
-pub struct DragonFractalTurtleProgramIterator {
-    dragon: DragonFractal,
-    curr_step: u64,
-    move_next: bool,
-}
 
-impl Iterator for DragonFractalTurtleProgramIterator {
-    type Item = TurtleStep;
+    /// Uses `lattice_point()` to find where the turtle is, instead of following each step.
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        // seeking past the last step also skips the turn after it
+        let past_the_end = moves > self.number_of_steps();
+        let segments = moves.min(self.number_of_steps());
+        let mut state = TurtleState::new();
+        for step in self.init_turtle() {
+            state.perform(step);
+        }
+        let initial_rad = state.angle;
 
-    fn next(&mut self) -> Option<TurtleStep> {
-        if self.curr_step > self.dragon.number_of_steps() {
-            return None;
+        let lattice = DragonFractal::lattice_point(segments);
+        let mut direction = lattice.direction;
+        // the turn after the last skipped step is left for the rest of the program
+        if segments > 0 && !past_the_end {
+            direction = match DragonFractal::turn_after_step(segments) {
+                Turn::Left => (direction + 3) % 4,
+                Turn::Right => (direction + 1) % 4,
+            };
         }
-        if self.move_next {
-            self.move_next = false;
-            log::debug!("curr_step: {}, Forward", self.curr_step);
-            Some(TurtleStep::Forward(
-                1.0 / (self.dragon.lines_between_endpoints() as f64),
-            ))
-        } else {
-            let turn = DragonFractal::turn_after_step(self.curr_step);
-            log::debug!("curr_step :{}, {:?}", self.curr_step, turn);
-            self.move_next = true;
-            self.curr_step += 1;
-            match turn {
-                Turn::Left => Some(TurtleStep::TurnRad(PI / 2.0_f64)),
-                Turn::Right => Some(TurtleStep::TurnRad(-PI / 2.0_f64)),
-            }
+        let distance = 1.0 / self.lines_between_endpoints();
+        let along = lattice.position.re as f64 * distance;
+        let across = lattice.position.im as f64 * distance;
+        state.position = Point {
+            x: state.position.x + along * initial_rad.cos() - across * initial_rad.sin(),
+            y: state.position.y + along * initial_rad.sin() + across * initial_rad.cos(),
+        };
+        state.angle = initial_rad + f64::from(direction) * PI / 2.0;
+
+        TurtleSeek {
+            state,
+            rest: self.folding_curve().steps_after(moves),
         }
     }
+
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        self.folding_curve().segment_info()
+    }
+
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        self.folding_curve().predicted_size()
+    }
 }
 
 #[cfg(test)]
 mod test {
 
-    use super::DragonFractal;
     use super::Turn::{Left, Right};
+    use super::{DragonFractal, DragonLatticePoint};
     use crate::geometry::Point;
-    use crate::turtle::{TurtleProgram, TurtleStep};
+    use crate::turtle::{seek_by_replay, TurtleProgram, TurtleStep};
+    use num::complex::Complex;
     use std::f64::consts::PI;
     use std::f64::consts::SQRT_2;
 
@@ -245,4 +302,111 @@ mod test {
         check_init_turtle(3, -3.0 * PI / 4.0);
         check_init_turtle(4, -PI);
     }
+
+    #[test]
+    fn test_segment_info() {
+        let depths = |iteration| {
+            DragonFractal::new(iteration)
+                .segment_info()
+                .unwrap()
+                .map(|info| info.depth)
+                .collect::<Vec<u64>>()
+        };
+        assert_eq!(depths(0), [0]);
+        assert_eq!(depths(1), [0, 1]);
+        assert_eq!(depths(2), [0, 2, 1, 2]);
+        assert_eq!(depths(3), [0, 3, 2, 3, 1, 3, 2, 3]);
+    }
+
+    #[test]
+    fn test_seek() {
+        for iteration in 0..7 {
+            let dragon = DragonFractal::new(iteration);
+            for moves in 0..=dragon.number_of_steps() + 1 {
+                let seek = dragon.seek(moves);
+                let replay = seek_by_replay(&dragon, moves);
+                assert_point_eq!(seek.state.position, replay.state.position, 0.000000001);
+                let turned = (seek.state.angle - replay.state.angle).rem_euclid(2.0 * PI);
+                assert!(
+                    turned < 0.000000001 || 2.0 * PI - turned < 0.000000001,
+                    "iteration {} moves {} faces the wrong way",
+                    iteration,
+                    moves
+                );
+                assert_eq!(
+                    seek.rest.collect::<Vec<TurtleStep>>(),
+                    replay.rest.collect::<Vec<TurtleStep>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lattice_point() {
+        let dragon = DragonFractal::new(8);
+        let mut position = Complex::new(0i64, 0);
+        let mut direction = 0u8;
+        let mut segments = 0;
+        assert_eq!(
+            DragonFractal::lattice_point(0),
+            DragonLatticePoint {
+                position,
+                direction
+            }
+        );
+        for step in dragon.turtle_program_iter() {
+            match step {
+                TurtleStep::Forward(_) => {
+                    position += Complex::i().powu(u32::from(direction));
+                    segments += 1;
+                }
+                TurtleStep::TurnRad(angle) if angle > 0.0 => direction = (direction + 1) % 4,
+                TurtleStep::TurnRad(_) => direction = (direction + 3) % 4,
+                _ => panic!("unexpected step {:?}", step),
+            }
+            if let TurtleStep::TurnRad(_) = step {
+                assert_eq!(
+                    DragonFractal::lattice_point(segments),
+                    DragonLatticePoint {
+                        position,
+                        direction
+                    },
+                    "after {} segments",
+                    segments
+                );
+            }
+        }
+        assert_eq!(segments, dragon.number_of_steps());
+    }
+
+    #[test]
+    fn test_lattice_point_folds() {
+        // each fold doubles the curve and turns the line between its endpoints by 45 degrees
+        let mut end = Complex::new(1i64, 0);
+        for iteration in 0..62 {
+            assert_eq!(
+                DragonFractal::lattice_point(1 << iteration).position,
+                end,
+                "iteration {}",
+                iteration
+            );
+            end *= Complex::new(1, 1);
+        }
+    }
+
+    #[test]
+    fn test_seek_high_iteration() {
+        let dragon = DragonFractal::new(50);
+        let seek = dragon.seek(dragon.number_of_steps());
+        assert_point_eq!(seek.state.position, Point { x: 1.0, y: 0.0 }, 0.000000001);
+        let midpoint = dragon.seek(dragon.number_of_steps() / 2);
+        assert_approx_eq!(
+            midpoint
+                .state
+                .position
+                .distance_to(Point { x: 0.0, y: 0.0 }),
+            SQRT_2 / 2.0,
+            0.000000001
+        );
+    }
 }
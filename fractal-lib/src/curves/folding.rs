@@ -0,0 +1,439 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering paperfolding curves.
+//!
+//! Folding a strip of paper in half `n` times and then unfolding it so that every crease makes
+//! the same angle leaves a curve of `2^n` segments. Each fold can be made in either direction,
+//! and the sequence of fold directions determines the curve: always folding the same way makes
+//! the Heighway dragon (see `curves::dragon`), while alternating directions makes the alternate
+//! paperfolding curve.
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::geometry::Point;
+use crate::turtle::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Turn {
+    pub fn opposite(self) -> Turn {
+        match self {
+            Turn::Left => Turn::Right,
+            Turn::Right => Turn::Left,
+        }
+    }
+}
+
+/// The direction of each fold, as a word that repeats for as many folds as are needed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FoldPattern {
+    folds: Vec<Turn>,
+}
+
+impl FoldPattern {
+    /// Panics if `folds` is empty.
+    pub fn new(folds: Vec<Turn>) -> FoldPattern {
+        assert!(!folds.is_empty(), "a fold pattern needs at least one fold");
+        FoldPattern { folds }
+    }
+
+    /// Every fold is made in the same direction, which makes the Heighway dragon.
+    pub fn regular() -> FoldPattern {
+        FoldPattern::new(vec![Turn::Left])
+    }
+
+    /// Folds alternate between directions.
+    pub fn alternate() -> FoldPattern {
+        FoldPattern::new(vec![Turn::Left, Turn::Right])
+    }
+
+    /// The direction of the `fold`th fold, counting from 0.
+    pub fn fold(&self, fold: u64) -> Turn {
+        self.folds[(fold % self.folds.len() as u64) as usize]
+    }
+}
+
+/// Parses `regular`, `alternate`, or a word of `L`s and `R`s, such as `LLR`.
+impl FromStr for FoldPattern {
+    type Err = FoldPatternError;
+
+    fn from_str(s: &str) -> Result<FoldPattern, FoldPatternError> {
+        match s {
+            "regular" => Ok(FoldPattern::regular()),
+            "alternate" => Ok(FoldPattern::alternate()),
+            "" => Err(FoldPatternError::Empty),
+            _ => s
+                .chars()
+                .map(|c| match c.to_ascii_uppercase() {
+                    'L' => Ok(Turn::Left),
+                    'R' => Ok(Turn::Right),
+                    _ => Err(FoldPatternError::UnknownFold(c)),
+                })
+                .collect::<Result<Vec<Turn>, FoldPatternError>>()
+                .map(FoldPattern::new),
+        }
+    }
+}
+
+impl fmt::Display for FoldPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for fold in &self.folds {
+            match fold {
+                Turn::Left => write!(f, "L")?,
+                Turn::Right => write!(f, "R")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a string is not a `FoldPattern`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FoldPatternError {
+    Empty,
+    UnknownFold(char),
+}
+
+impl fmt::Display for FoldPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FoldPatternError::Empty => write!(f, "a fold pattern needs at least one fold"),
+            FoldPatternError::UnknownFold(c) => write!(
+                f,
+                "{:?} is not a fold, expected regular, alternate, or a word of L and R",
+                c
+            ),
+        }
+    }
+}
+
+/// A paperfolding curve of a particular iteration (number of folds), fold pattern, and angle.
+#[derive(Clone, Debug)]
+pub struct FoldingCurve {
+    iterations: u64,
+    pattern: FoldPattern,
+    fold_rad: f64,
+}
+
+impl FoldingCurve {
+    /// `fold_rad` is how far the turtle turns at each crease. A left fold turns counter-clockwise
+    /// and a right fold turns clockwise. Panics unless it is strictly between -π and π, since the
+    /// curve folds back onto itself at π.
+    pub fn new(iterations: u64, pattern: FoldPattern, fold_rad: f64) -> FoldingCurve {
+        assert!(
+            fold_rad.abs() < PI,
+            "a fold of {} radians folds the curve onto itself",
+            fold_rad
+        );
+        FoldingCurve {
+            iterations,
+            pattern,
+            fold_rad,
+        }
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn pattern(&self) -> &FoldPattern {
+        &self.pattern
+    }
+
+    pub fn fold_rad(&self) -> f64 {
+        self.fold_rad
+    }
+
+    /// The number of lines that will be drawn: 2^iterations, or `u64::MAX` from 64 folds on, which
+    /// is more lines than could ever be drawn anyway.
+    pub fn number_of_steps(&self) -> u64 {
+        u32::try_from(self.iterations)
+            .ok()
+            .and_then(|iterations| 1u64.checked_shl(iterations))
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Whether to turn left or right after a move forward, where the first step is step 1.
+    ///
+    /// The turn after step `k` is made by fold `iterations - v - 1` (counting from 0), where `2^v`
+    /// is the largest power of two that divides `k`. Odd multiples of `2^v` alternate between
+    /// turning with and against that fold.
+    pub fn turn_after_step(&self, step: u64) -> Turn {
+        let twos = u64::from(step.trailing_zeros());
+        let fold = self.pattern.fold(self.iterations.saturating_sub(twos + 1));
+        match (step >> twos) % 4 {
+            1 => fold,
+            _ => fold.opposite(),
+        }
+    }
+
+    /// How far the turtle turns for `turn`.
+    fn turn_rad(&self, turn: Turn) -> f64 {
+        match turn {
+            Turn::Left => self.fold_rad,
+            Turn::Right => -self.fold_rad,
+        }
+    }
+
+    /// How many line segments long the line between the starting and end points is.
+    ///
+    /// Each fold `j` joins two copies of the curve made by the folds after it, with the second
+    /// copy turned by the fold's angle `t_j`. Doing so multiplies the line between the endpoints
+    /// by `1 + e^(i t_j)`, which is `2 cos(t_j / 2)` long and turns it by `t_j / 2`.
+    pub fn lines_between_endpoints(&self) -> f64 {
+        (2.0 * (self.fold_rad / 2.0).cos()).powf(self.iterations as f64)
+    }
+
+    /// The steps of the turtle program that come after its `moves`th move forward. Seeking past
+    /// the last move leaves no steps.
+    pub(crate) fn steps_after(&self, moves: u64) -> TurtleProgramIterator {
+        let past_the_end = moves > self.number_of_steps();
+        let moves = moves.min(self.number_of_steps());
+        TurtleProgramIterator::new(Box::new(FoldingCurveTurtleProgramIterator {
+            curve: self.clone(),
+            curr_step: match past_the_end {
+                true => moves + 1,
+                false => moves.max(1),
+            },
+            move_next: moves == 0,
+        }))
+    }
+
+    /// The angle of the line between the starting and end points when the turtle starts facing
+    /// along the X axis (see `lines_between_endpoints()`).
+    fn endpoint_rad(&self) -> f64 {
+        (0..self.iterations)
+            .map(|fold| self.turn_rad(self.pattern.fold(fold)) / 2.0)
+            .sum()
+    }
+}
+
+impl TurtleProgram for FoldingCurve {
+    /// Starts at (0.0, 0.0), and faces whichever direction makes the curve end at (1.0, 0.0).
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        vec![
+            TurtleStep::SetPos(Point { x: 0.0, y: 0.0 }),
+            TurtleStep::SetRad(-self.endpoint_rad()),
+            TurtleStep::Down,
+        ]
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        TurtleProgramIterator::new(Box::new(FoldingCurveTurtleProgramIterator {
+            curve: self.clone(),
+            curr_step: 1,
+            move_next: true,
+        }))
+    }
+
+    /// Each fold only turns by a multiple of the fold angle, so instead of following each step,
+    /// this counts how many steps are taken facing each direction using `turn_after_step()`.
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        let rest = self.steps_after(moves);
+        // seeking past the last step also skips the turn after it
+        let past_the_end = moves > self.number_of_steps();
+        let moves = moves.min(self.number_of_steps());
+        let mut state = TurtleState::new();
+        for step in self.init_turtle() {
+            state.perform(step);
+        }
+        let initial_rad = state.angle;
+
+        let mut steps_facing: BTreeMap<i64, u64> = BTreeMap::new();
+        let mut facing = 0;
+        for step in 1..=moves {
+            *steps_facing.entry(facing).or_insert(0) += 1;
+            // the turn after the last skipped step is left for the rest of the program
+            if step < moves || past_the_end {
+                facing += match self.turn_after_step(step) {
+                    Turn::Left => 1,
+                    Turn::Right => -1,
+                };
+            }
+        }
+        let distance = 1.0 / self.lines_between_endpoints();
+        for (facing, steps) in steps_facing {
+            let angle = initial_rad + facing as f64 * self.fold_rad;
+            state.position = Point {
+                x: state.position.x + angle.cos() * steps as f64 * distance,
+                y: state.position.y + angle.sin() * steps as f64 * distance,
+            };
+        }
+        state.angle = initial_rad + facing as f64 * self.fold_rad;
+
+        TurtleSeek { state, rest }
+    }
+
+    /// The turn after step `k` is made by fold `iterations - v`, where `2^v` is the largest power
+    /// of two that divides `k`. The first fold makes the turn in the middle of the curve, and the
+    /// last fold makes every other turn.
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        let iterations = self.iterations;
+        Some(Box::new((0..self.number_of_steps()).map(move |step| {
+            SegmentInfo {
+                symbol: 0,
+                depth: match step {
+                    0 => 0,
+                    _ => iterations - u64::from(step.trailing_zeros()),
+                },
+            }
+        })))
+    }
+
+    /// Every one of the 2^iterations segments is followed by a turn.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let moves = match self.iterations {
+            iterations if iterations < u64::from(u128::BITS) => 1u128 << iterations,
+            _ => u128::MAX,
+        };
+        Some(ProgramSize {
+            steps: moves.saturating_mul(2),
+            moves,
+            growth_rate: Some(2.0),
+        })
+    }
+}
+
+/// Iterator that emits a folding curve's turtle program as `TurtleStep`s.
+pub struct FoldingCurveTurtleProgramIterator {
+    curve: FoldingCurve,
+    curr_step: u64,
+    move_next: bool,
+}
+
+impl Iterator for FoldingCurveTurtleProgramIterator {
+    type Item = TurtleStep;
+
+    fn next(&mut self) -> Option<TurtleStep> {
+        if self.curr_step > self.curve.number_of_steps() {
+            return None;
+        }
+        if self.move_next {
+            self.move_next = false;
+            log::debug!("curr_step: {}, Forward", self.curr_step);
+            Some(TurtleStep::Forward(
+                1.0 / self.curve.lines_between_endpoints(),
+            ))
+        } else {
+            let turn = self.curve.turn_after_step(self.curr_step);
+            log::debug!("curr_step :{}, {:?}", self.curr_step, turn);
+            self.move_next = true;
+            self.curr_step += 1;
+            Some(TurtleStep::TurnRad(self.curve.turn_rad(turn)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!("regular".parse(), Ok(FoldPattern::regular()));
+        assert_eq!("alternate".parse(), Ok(FoldPattern::alternate()));
+        assert_eq!(
+            "LlR".parse(),
+            Ok(FoldPattern::new(vec![Turn::Left, Turn::Left, Turn::Right]))
+        );
+        assert_eq!("".parse::<FoldPattern>(), Err(FoldPatternError::Empty));
+        assert_eq!(
+            "LX".parse::<FoldPattern>(),
+            Err(FoldPatternError::UnknownFold('X'))
+        );
+        assert_eq!(FoldPattern::alternate().to_string(), "LR");
+    }
+
+    #[test]
+    fn test_turns() {
+        let turns = |pattern: &str| {
+            let curve = FoldingCurve::new(3, pattern.parse().unwrap(), PI / 2.0);
+            (1..8)
+                .map(|step| match curve.turn_after_step(step) {
+                    Turn::Left => 'L',
+                    Turn::Right => 'R',
+                })
+                .collect::<String>()
+        };
+        assert_eq!(turns("regular"), "LLRLLRR");
+        // the middle turn is the first fold, and every other turn is the last fold
+        assert_eq!(turns("alternate"), "LRRLLLR");
+        assert_eq!(turns("RLL"), "LLRRLRR");
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let patterns = ["regular", "alternate", "LLR", "RRLRL"];
+        let angles = [PI / 2.0, PI / 3.0, 2.0 * PI / 3.0, -PI / 4.0, 0.0];
+        for pattern in patterns.iter() {
+            for angle in angles.iter() {
+                for iteration in 0..8 {
+                    let curve = FoldingCurve::new(iteration, pattern.parse().unwrap(), *angle);
+                    let stats = TurtleStats::of_program(&curve);
+                    assert_eq!(stats.segments as u64, curve.number_of_steps());
+                    assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+                    assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_predicted_size() {
+        for iteration in 0..6 {
+            let curve = FoldingCurve::new(iteration, FoldPattern::alternate(), PI / 2.0);
+            let size = curve.predicted_size().unwrap();
+            assert_eq!(size.steps, curve.turtle_program_iter().count() as u128);
+            assert_eq!(size.moves, curve.number_of_steps() as u128);
+        }
+        let huge = FoldingCurve::new(200, FoldPattern::regular(), PI / 2.0);
+        assert_eq!(huge.predicted_size().unwrap().moves, u128::MAX);
+    }
+
+    #[test]
+    fn test_number_of_steps() {
+        let steps = |iterations| {
+            FoldingCurve::new(iterations, FoldPattern::alternate(), PI / 2.0).number_of_steps()
+        };
+        assert_eq!(steps(0), 1);
+        assert_eq!(steps(63), 1 << 63);
+        // more lines than a u64 counts
+        assert_eq!(steps(64), u64::MAX);
+        assert_eq!(steps(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_seek() {
+        let curve = FoldingCurve::new(6, "LRR".parse().unwrap(), PI / 3.0);
+        for moves in 0..=curve.number_of_steps() + 1 {
+            let seek = curve.seek(moves);
+            let replay = seek_by_replay(&curve, moves);
+            assert_point_eq!(seek.state.position, replay.state.position, 0.000000001);
+            assert_approx_eq!(seek.state.angle, replay.state.angle, 0.000000001);
+            assert_eq!(
+                seek.rest.collect::<Vec<TurtleStep>>(),
+                replay.rest.collect::<Vec<TurtleStep>>()
+            );
+        }
+    }
+}
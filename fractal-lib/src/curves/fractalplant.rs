@@ -0,0 +1,146 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering the branching fractal plant from *The
+//! Algorithmic Beauty of Plants*.
+
+use crate::geometry::{deg2rad, Point};
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+/// How far the plant's stem extends, once all of its growth is drawn.
+const STEM_LENGTH: f64 = 0.65;
+
+#[derive(Copy, Clone, Debug)]
+pub struct FractalPlant {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    X,    // controls how the plant grows, but does not draw anything
+    F,    // move forward
+    L,    // turn left 25 degrees
+    R,    // turn right 25 degrees
+    Push, // start a branch
+    Pop,  // end a branch
+}
+
+impl FractalPlant {
+    pub fn new(iterations: u64) -> FractalPlant {
+        FractalPlant { iterations }
+    }
+
+    /// Each iteration doubles the length of the stem.
+    fn distance_forward(self) -> f64 {
+        STEM_LENGTH / 2f64.powf(self.iterations as f64)
+    }
+}
+
+impl LindenmayerSystem<LSA> for FractalPlant {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::X]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        match lstr {
+            LSA::X => vec![
+                LSA::F,
+                LSA::L,
+                LSA::Push,
+                LSA::Push,
+                LSA::X,
+                LSA::Pop,
+                LSA::R,
+                LSA::X,
+                LSA::Pop,
+                LSA::R,
+                LSA::F,
+                LSA::Push,
+                LSA::R,
+                LSA::F,
+                LSA::X,
+                LSA::Pop,
+                LSA::L,
+                LSA::X,
+            ],
+            LSA::F => vec![LSA::F, LSA::F],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for FractalPlant {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn initial_pos(&self) -> Point {
+        Point { x: -0.1, y: -0.72 }
+    }
+
+    fn initial_rad(&self) -> f64 {
+        deg2rad(65.0)
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::X => None,
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(25.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-25.0))),
+            LSA::Push => Some(TurtleStep::Push),
+            LSA::Pop => Some(TurtleStep::Pop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::TurtleProgram;
+
+    #[test]
+    fn test_branches() {
+        let program = LindenmayerSystemTurtleProgram::new(FractalPlant::new(1));
+        let steps: Vec<TurtleStep> = program.turtle_program_iter().collect();
+        // the four Xs only control how the plant grows, so the turtle skips them
+        assert_eq!(steps.len(), 14);
+        assert_eq!(
+            steps
+                .iter()
+                .filter(|step| **step == TurtleStep::Push)
+                .count(),
+            3
+        );
+        assert_eq!(
+            steps
+                .iter()
+                .filter(|step| **step == TurtleStep::Pop)
+                .count(),
+            3
+        );
+        assert_eq!(steps[0], TurtleStep::Forward(STEM_LENGTH / 2.0));
+    }
+
+    #[test]
+    fn test_stem_length() {
+        // the stem doubles in length with each iteration, so each segment must halve
+        let lengths: Vec<f64> = (0..4)
+            .map(|i| FractalPlant::new(i).distance_forward())
+            .collect();
+        assert_eq!(lengths, vec![0.65, 0.325, 0.1625, 0.08125]);
+    }
+}
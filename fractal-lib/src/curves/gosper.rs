@@ -0,0 +1,100 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Gosper curve (also known as a
+//! flowsnake), which fills a hexagonal region.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct GosperCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    A, // move forward
+    B, // move forward
+    L, // turn left 60 degrees
+    R, // turn right 60 degrees
+}
+
+impl GosperCurve {
+    pub fn new(iterations: u64) -> GosperCurve {
+        GosperCurve { iterations }
+    }
+
+    /// Each iteration replaces every line with 7 lines that span a distance of sqrt(7) lines.
+    fn distance_forward(self) -> f64 {
+        1.0 / 7f64.sqrt().powf(self.iterations as f64)
+    }
+}
+
+impl LindenmayerSystem<LSA> for GosperCurve {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::A]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            A => vec![A, R, B, R, R, B, L, A, L, L, A, A, L, B, R],
+            B => vec![L, A, R, B, B, R, R, B, R, A, L, L, A, L, B],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for GosperCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Each iteration turns the line between the curve's endpoints clockwise by atan(sqrt(3)/5),
+    /// so start turned the other way to always end at (1, 0).
+    fn initial_rad(&self) -> f64 {
+        (3f64.sqrt() / 5.0).atan() * self.iterations as f64
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::A | LSA::B => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(60.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-60.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GosperCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 0..4 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                GosperCurve::new(iteration),
+            ));
+            assert_eq!(stats.segments, 7usize.pow(iteration as u32));
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+            assert_eq!(stats.intersections, 0);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Hilbert curve, which visits every point of
+//! a square grid.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct HilbertCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    A, // controls how the curve grows, but does not draw anything
+    B, // controls how the curve grows, but does not draw anything
+    F, // move forward
+    L, // turn left 90 degrees
+    R, // turn right 90 degrees
+}
+
+impl HilbertCurve {
+    pub fn new(iterations: u64) -> HilbertCurve {
+        HilbertCurve { iterations }
+    }
+
+    /// The curve visits a grid with 2^iterations points along each side, and ends at (1, 0).
+    fn distance_forward(self) -> f64 {
+        1.0 / (2f64.powf(self.iterations as f64) - 1.0).max(1.0)
+    }
+}
+
+impl LindenmayerSystem<LSA> for HilbertCurve {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::A]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            A => vec![L, B, F, R, A, F, A, R, F, B, L],
+            B => vec![R, A, F, L, B, F, B, L, F, A, R],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for HilbertCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::A | LSA::B => None,
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(90.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-90.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HilbertCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 1..5 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                HilbertCurve::new(iteration),
+            ));
+            assert_eq!(stats.segments, 4usize.pow(iteration as u32) - 1);
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+            // a curve that visits every point once never touches itself
+            assert_eq!(stats.intersections, 0);
+        }
+    }
+}
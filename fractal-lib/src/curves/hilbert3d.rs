@@ -0,0 +1,151 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a 3D Hilbert curve, which visits every
+//! point of a cubic grid, using the L-system from *The Algorithmic Beauty of Plants*.
+
+use crate::geometry::Point3;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystem3dDrawingParameters};
+use crate::turtle3d::{Frame, Turtle3dStep};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Hilbert3d {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    A, // these four control how the curve grows, but do not draw anything
+    B,
+    C,
+    D,
+    F,          // move forward
+    Left,       // yaw left 90 degrees (+)
+    Right,      // yaw right 90 degrees (-)
+    PitchDown,  // pitch down 90 degrees (&)
+    PitchUp,    // pitch up 90 degrees (^)
+    RollLeft,   // roll counter-clockwise 90 degrees (\)
+    RollRight,  // roll clockwise 90 degrees (/)
+    TurnAround, // yaw 180 degrees (|)
+}
+
+/// Converts a rule written with the book's notation into symbols.
+fn symbols(rule: &str) -> Vec<LSA> {
+    rule.chars()
+        .map(|c| match c {
+            'A' => LSA::A,
+            'B' => LSA::B,
+            'C' => LSA::C,
+            'D' => LSA::D,
+            'F' => LSA::F,
+            '+' => LSA::Left,
+            '-' => LSA::Right,
+            '&' => LSA::PitchDown,
+            '^' => LSA::PitchUp,
+            '\\' => LSA::RollLeft,
+            '/' => LSA::RollRight,
+            '|' => LSA::TurnAround,
+            _ => unreachable!("unknown symbol {}", c),
+        })
+        .collect()
+}
+
+impl Hilbert3d {
+    pub fn new(iterations: u64) -> Hilbert3d {
+        Hilbert3d { iterations }
+    }
+
+    /// The curve visits a grid with 2^iterations points along each side, which is scaled to fit
+    /// in a unit cube.
+    fn distance_forward(self) -> f64 {
+        1.0 / (2f64.powf(self.iterations as f64) - 1.0).max(1.0)
+    }
+}
+
+impl LindenmayerSystem<LSA> for Hilbert3d {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::A]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        match lstr {
+            LSA::A => symbols("B-F+CFC+F-D&F^D-F+&&CFC+F+B//"),
+            LSA::B => symbols("A&F^CFB^F^D^^-F-D^|F^B|FC^F^A//"),
+            LSA::C => symbols("|D^|F^B-F+C^F^A&&FA&F^C+F+B^F^D//"),
+            LSA::D => symbols("|CFB-F+B|FA&F^A&&FB-F+B|FC//"),
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystem3dDrawingParameters<LSA> for Hilbert3d {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The book's curve grows towards the turtle's right and down, so start with the turtle
+    /// upside down to keep the curve within the positive octant.
+    fn initial_frame(&self) -> Frame {
+        Frame::from_heading_left(Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, -1.0, 0.0))
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<Turtle3dStep> {
+        match symbol {
+            LSA::A | LSA::B | LSA::C | LSA::D => None,
+            LSA::F => Some(Turtle3dStep::Forward(self.distance_forward())),
+            LSA::Left => Some(Turtle3dStep::yaw_deg(90.0)),
+            LSA::Right => Some(Turtle3dStep::yaw_deg(-90.0)),
+            LSA::PitchDown => Some(Turtle3dStep::pitch_deg(-90.0)),
+            LSA::PitchUp => Some(Turtle3dStep::pitch_deg(90.0)),
+            LSA::RollLeft => Some(Turtle3dStep::roll_deg(-90.0)),
+            LSA::RollRight => Some(Turtle3dStep::roll_deg(90.0)),
+            LSA::TurnAround => Some(Turtle3dStep::yaw_deg(180.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::LindenmayerSystemTurtle3dProgram;
+    use crate::turtle3d::{record, Turtle3dProgram};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_visits_every_grid_point() {
+        for iteration in 1..4 {
+            let side = 2i64.pow(iteration as u32);
+            let program = LindenmayerSystemTurtle3dProgram::new(Hilbert3d::new(iteration));
+            let polylines = record(&program);
+            assert_eq!(polylines.len(), 1);
+
+            let scale = (side - 1) as f64;
+            let mut visited = HashSet::new();
+            for point in &polylines[0] {
+                let grid = [point.x, point.y, point.z].map(|v| (v * scale).round());
+                for (v, g) in [point.x, point.y, point.z].iter().zip(grid.iter()) {
+                    assert!((v * scale - g).abs() < 1e-9);
+                    assert!(*g >= 0.0 && *g < side as f64);
+                }
+                visited.insert(grid.map(|g| g as i64));
+            }
+            assert_eq!(polylines[0].len() as i64, side * side * side);
+            assert_eq!(visited.len() as i64, side * side * side);
+
+            let size = program.predicted_size().unwrap();
+            assert_eq!(size.moves, (side * side * side - 1) as u128);
+            assert_eq!(size.steps, program.turtle_program_iter().count() as u128);
+        }
+    }
+}
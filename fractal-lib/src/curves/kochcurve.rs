@@ -23,7 +23,7 @@ pub struct KochCurve {
     iterations: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LSA {
     F, // move forward
     L, // turn left X degrees
@@ -70,11 +70,11 @@ impl LindenmayerSystemDrawingParameters<LSA> for KochCurve {
         self.iterations
     }
 
-    fn interpret_symbol(&self, symbol: LSA) -> TurtleStep {
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
         match symbol {
-            LSA::F => TurtleStep::Forward(self.distance_forward()),
-            LSA::L => TurtleStep::TurnRad(deg2rad(60.0)),
-            LSA::R => TurtleStep::TurnRad(deg2rad(-60.0)),
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(60.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-60.0))),
         }
     }
 }
@@ -25,7 +25,7 @@ pub struct LevyCCurve {
     iterations: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LSA {
     F, // move forward
     L, // turn left 45 degrees
@@ -67,11 +67,11 @@ impl LindenmayerSystemDrawingParameters<LSA> for LevyCCurve {
         self.iterations
     }
 
-    fn interpret_symbol(&self, symbol: LSA) -> TurtleStep {
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
         match symbol {
-            LSA::F => TurtleStep::Forward(self.distance_forward()),
-            LSA::L => TurtleStep::TurnRad(deg2rad(45.0)),
-            LSA::R => TurtleStep::TurnRad(deg2rad(-45.0)),
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(45.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-45.0))),
         }
     }
 }
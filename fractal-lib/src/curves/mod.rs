@@ -15,6 +15,19 @@
 pub mod cesaro;
 pub mod cesarotri;
 pub mod dragon;
+pub mod folding;
+pub mod fractalplant;
+pub mod gosper;
+pub mod hilbert;
+pub mod hilbert3d;
 pub mod kochcurve;
 pub mod levyccurve;
+pub mod moore;
+pub mod parametrictree;
+pub mod peano;
+pub mod quadratickoch;
+pub mod sierpinskiarrowhead;
+pub mod sierpinskicurve;
+pub mod spacefilling;
 pub mod terdragon;
+pub mod tree3d;
@@ -0,0 +1,106 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Moore curve, a looped variant of the
+//! Hilbert curve that ends next to where it starts.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct MooreCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    X, // controls how the curve grows, but does not draw anything
+    Y, // controls how the curve grows, but does not draw anything
+    F, // move forward
+    L, // turn left 90 degrees
+    R, // turn right 90 degrees
+}
+
+impl MooreCurve {
+    pub fn new(iterations: u64) -> MooreCurve {
+        MooreCurve { iterations }
+    }
+
+    /// The curve visits a grid with 2^(iterations + 1) points along each side, which is scaled
+    /// to be 1 wide.
+    fn distance_forward(self) -> f64 {
+        1.0 / (2f64.powf(self.iterations as f64 + 1.0) - 1.0)
+    }
+}
+
+impl LindenmayerSystem<LSA> for MooreCurve {
+    fn initial(&self) -> Vec<LSA> {
+        use LSA::*;
+        vec![X, F, X, L, F, L, X, F, X]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            X => vec![R, Y, F, L, X, F, X, L, F, Y, R],
+            Y => vec![L, X, F, R, Y, F, Y, R, F, X, L],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for MooreCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::X | LSA::Y => None,
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(90.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-90.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MooreCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 0..5 {
+            let curve = MooreCurve::new(iteration);
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(curve));
+            assert_eq!(stats.segments, 4usize.pow(iteration as u32 + 1) - 1);
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            // the curve ends one step away from where it started
+            assert_point_eq!(
+                stats.end.unwrap(),
+                Point {
+                    x: 0.0,
+                    y: curve.distance_forward()
+                },
+                0.000000001
+            );
+            assert_approx_eq!(stats.bounds.unwrap().width(), 1.0, 0.000000001);
+            assert_eq!(stats.intersections, 0);
+        }
+    }
+}
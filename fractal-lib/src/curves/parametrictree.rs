@@ -0,0 +1,164 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A parametric L-system tree, based on the binary trees in chapter 2.2 of *The Algorithmic
+//! Beauty of Plants*, whose branches carry their own length and width.
+
+use crate::geometry::{deg2rad, Point};
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+/// How long the trunk is.
+const TRUNK_LENGTH: f64 = 0.5;
+/// How wide the trunk is, in pixels.
+const TRUNK_WIDTH: f64 = 8.0;
+/// How much shorter each branch is than its parent, on the left and the right.
+const CONTRACTION: (f64, f64) = (0.75, 0.65);
+/// How far each branch turns away from its parent, on the left and the right, in degrees.
+const BRANCH_ANGLE: (f64, f64) = (30.0, -20.0);
+/// How much narrower each branch is than its parent.
+const WIDTH_DECREASE: f64 = 0.707;
+/// Apexes shorter than this no longer grow.
+const MIN_LENGTH: f64 = 0.01;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ParametricTree {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    Apex { length: f64, width: f64 }, // grows into a branch with two apexes
+    Forward(f64),                     // move forward this far
+    Turn(f64),                        // turn this many degrees counter-clockwise
+    Width(f64),                       // set the pen width to this many pixels
+    Push,                             // start a branch
+    Pop,                              // end a branch
+}
+
+impl ParametricTree {
+    pub fn new(iterations: u64) -> ParametricTree {
+        ParametricTree { iterations }
+    }
+}
+
+impl LindenmayerSystem<LSA> for ParametricTree {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::Apex {
+            length: TRUNK_LENGTH,
+            width: TRUNK_WIDTH,
+        }]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        match lstr {
+            LSA::Apex { length, width } if length >= MIN_LENGTH => {
+                let branch = |contraction: f64, angle: f64| {
+                    vec![
+                        LSA::Push,
+                        LSA::Turn(angle),
+                        LSA::Apex {
+                            length: length * contraction,
+                            width: width * WIDTH_DECREASE,
+                        },
+                        LSA::Pop,
+                    ]
+                };
+                [
+                    vec![LSA::Width(width), LSA::Forward(length)],
+                    branch(CONTRACTION.0, BRANCH_ANGLE.0),
+                    branch(CONTRACTION.1, BRANCH_ANGLE.1),
+                ]
+                .concat()
+            }
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for ParametricTree {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn initial_pos(&self) -> Point {
+        Point { x: 0.0, y: -1.0 }
+    }
+
+    fn initial_rad(&self) -> f64 {
+        deg2rad(90.0)
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::Apex { .. } => None,
+            LSA::Forward(length) => Some(TurtleStep::Forward(length)),
+            LSA::Turn(angle) => Some(TurtleStep::TurnRad(deg2rad(angle))),
+            LSA::Width(width) => Some(TurtleStep::SetPenWidth(width)),
+            LSA::Push => Some(TurtleStep::Push),
+            LSA::Pop => Some(TurtleStep::Pop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_branches() {
+        let tree = ParametricTree::new(2);
+        let second = tree.generate(2);
+        let widths: Vec<f64> = second
+            .iter()
+            .filter_map(|symbol| match symbol {
+                LSA::Width(width) => Some(*width),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            widths,
+            vec![
+                TRUNK_WIDTH,
+                TRUNK_WIDTH * WIDTH_DECREASE,
+                TRUNK_WIDTH * WIDTH_DECREASE
+            ]
+        );
+        let lengths: Vec<f64> = second
+            .iter()
+            .filter_map(|symbol| match symbol {
+                LSA::Forward(length) => Some(*length),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            lengths,
+            vec![
+                TRUNK_LENGTH,
+                TRUNK_LENGTH * CONTRACTION.0,
+                TRUNK_LENGTH * CONTRACTION.1
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stops_growing() {
+        let tree = ParametricTree::new(0);
+        // the longest branches contract the least, so they are the last to stop growing
+        let growing = (MIN_LENGTH / TRUNK_LENGTH).ln() / CONTRACTION.0.ln();
+        let last = growing.floor() as u64 + 1;
+        assert_ne!(tree.generate(last - 1), tree.generate(last));
+        assert_eq!(tree.generate(last), tree.generate(last + 1));
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Peano curve, the first space-filling
+//! curve, which visits every point of a square grid.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct PeanoCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    X, // controls how the curve grows, but does not draw anything
+    Y, // controls how the curve grows, but does not draw anything
+    F, // move forward
+    L, // turn left 90 degrees
+    R, // turn right 90 degrees
+}
+
+impl PeanoCurve {
+    pub fn new(iterations: u64) -> PeanoCurve {
+        PeanoCurve { iterations }
+    }
+
+    /// The curve visits a grid with 3^iterations points along each side, and ends at (1, 1).
+    fn distance_forward(self) -> f64 {
+        1.0 / (3f64.powf(self.iterations as f64) - 1.0).max(1.0)
+    }
+}
+
+impl LindenmayerSystem<LSA> for PeanoCurve {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::X]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            X => vec![
+                X, F, Y, F, X, L, F, L, Y, F, X, F, Y, R, F, R, X, F, Y, F, X,
+            ],
+            Y => vec![
+                Y, F, X, F, Y, R, F, R, X, F, Y, F, X, L, F, L, Y, F, X, F, Y,
+            ],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for PeanoCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::X | LSA::Y => None,
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(90.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-90.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PeanoCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 1..4 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                PeanoCurve::new(iteration),
+            ));
+            assert_eq!(stats.segments, 9usize.pow(iteration as u32) - 1);
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 1.0 }, 0.000000001);
+            assert_eq!(stats.intersections, 0);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a quadratic Koch curve, which replaces
+//! every line with square bumps instead of the Koch curve's triangular ones.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct QuadraticKochCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    F, // move forward
+    L, // turn left 90 degrees
+    R, // turn right 90 degrees
+}
+
+impl QuadraticKochCurve {
+    pub fn new(iterations: u64) -> QuadraticKochCurve {
+        QuadraticKochCurve { iterations }
+    }
+
+    /// Each iteration replaces every line with 5 lines that span a distance of 3 lines.
+    fn distance_forward(self) -> f64 {
+        1.0 / 3f64.powf(self.iterations as f64)
+    }
+}
+
+impl LindenmayerSystem<LSA> for QuadraticKochCurve {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::F]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            F => vec![F, L, F, R, F, R, F, L, F],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for QuadraticKochCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::F => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(90.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-90.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QuadraticKochCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 0..5 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                QuadraticKochCurve::new(iteration),
+            ));
+            assert_eq!(stats.segments, 5usize.pow(iteration as u32));
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+            assert_approx_eq!(
+                stats.length,
+                (5.0f64 / 3.0).powi(iteration as i32),
+                0.000000001
+            );
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Sierpiński arrowhead curve, which
+//! approximates the Sierpiński triangle with a single line.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+
+#[derive(Copy, Clone, Debug)]
+pub struct SierpinskiArrowhead {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    A, // move forward
+    B, // move forward
+    L, // turn left 60 degrees
+    R, // turn right 60 degrees
+}
+
+impl SierpinskiArrowhead {
+    pub fn new(iterations: u64) -> SierpinskiArrowhead {
+        SierpinskiArrowhead { iterations }
+    }
+
+    /// Each iteration replaces every line with 3 lines that span a distance of 2 lines.
+    fn distance_forward(self) -> f64 {
+        1.0 / 2f64.powf(self.iterations as f64)
+    }
+}
+
+impl LindenmayerSystem<LSA> for SierpinskiArrowhead {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::A]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            A => vec![B, R, A, R, B],
+            B => vec![A, L, B, L, A],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for SierpinskiArrowhead {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Odd iterations end 60 degrees clockwise of where even iterations end, and they would draw
+    /// the triangle upside down. Starting at 60 degrees keeps the triangle upright and ends the
+    /// curve at (1, 0).
+    fn initial_rad(&self) -> f64 {
+        match self.iterations % 2 {
+            0 => 0.0,
+            _ => deg2rad(60.0),
+        }
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::A | LSA::B => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(60.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-60.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SierpinskiArrowhead;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 0..6 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                SierpinskiArrowhead::new(iteration),
+            ));
+            assert_eq!(stats.segments, 3usize.pow(iteration as u32));
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+            // the triangle is always above its base
+            assert_approx_eq!(stats.bounds.unwrap().min.y, 0.0, 0.000000001);
+        }
+    }
+}
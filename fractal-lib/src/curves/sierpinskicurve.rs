@@ -0,0 +1,101 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a Sierpiński curve, a closed curve that
+//! fills a square.
+
+use crate::geometry::deg2rad;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::turtle::TurtleStep;
+use std::f64::consts::SQRT_2;
+
+#[derive(Copy, Clone, Debug)]
+pub struct SierpinskiCurve {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    X, // controls how the curve grows, but does not draw anything
+    F, // move forward
+    G, // move forward, but does not grow
+    L, // turn left 45 degrees
+    R, // turn right 45 degrees
+}
+
+impl SierpinskiCurve {
+    pub fn new(iterations: u64) -> SierpinskiCurve {
+        SierpinskiCurve { iterations }
+    }
+
+    /// The curve is (2^(iterations + 1) - 1) straight lines plus (2^iterations - 1) diagonal
+    /// lines wide, which is scaled to be 1 wide.
+    fn distance_forward(self) -> f64 {
+        let straight = 2f64.powf(self.iterations as f64 + 1.0) - 1.0;
+        let diagonal = 2f64.powf(self.iterations as f64) - 1.0;
+        1.0 / (straight + diagonal * SQRT_2)
+    }
+}
+
+impl LindenmayerSystem<LSA> for SierpinskiCurve {
+    fn initial(&self) -> Vec<LSA> {
+        use LSA::*;
+        vec![F, R, R, X, F, R, R, F, R, R, X, F]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        use LSA::*;
+        match lstr {
+            X => vec![X, F, L, G, L, X, F, R, R, F, R, R, X, F, L, G, L, X],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<LSA> for SierpinskiCurve {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
+        match symbol {
+            LSA::X => None,
+            LSA::F | LSA::G => Some(TurtleStep::Forward(self.distance_forward())),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(45.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-45.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SierpinskiCurve;
+    use crate::geometry::Point;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::TurtleStats;
+
+    #[test]
+    fn test_endpoints_and_segments() {
+        for iteration in 0..5 {
+            let stats = TurtleStats::of_program(&LindenmayerSystemTurtleProgram::new(
+                SierpinskiCurve::new(iteration),
+            ));
+            assert_eq!(stats.segments, 4usize.pow(iteration as u32 + 1));
+            // the curve is closed
+            assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+            assert_approx_eq!(stats.bounds.unwrap().width(), 1.0, 0.000000001);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maps between positions along a space-filling curve and cells of the square grid that it fills.
+//!
+//! A curve of order `n` visits every cell of a `2^n` by `2^n` grid exactly once, so each cell has
+//! a 1-D index along the curve. Cells that are close together along a Hilbert or Moore curve are
+//! also close together in the grid, which makes these indices useful for spatial indexing.
+//!
+//! ```
+//! use fractal_lib::curves::spacefilling::{IndexedCurve, SpaceFillingCurve};
+//!
+//! let hilbert = IndexedCurve::new(SpaceFillingCurve::Hilbert, 2);
+//! assert_eq!(hilbert.coordinates(5), (0, 3));
+//! assert_eq!(hilbert.index(0, 3), 5);
+//! ```
+
+use crate::geometry::Point;
+use crate::turtle::{TurtleProgram, TurtleProgramIterator, TurtleStep};
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The largest supported order. Every index of an order 31 curve fits in a `u64`, and every
+/// coordinate fits in a `u32`.
+pub const MAX_ORDER: u32 = 31;
+
+/// The space-filling curves that `IndexedCurve` can map.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum SpaceFillingCurve {
+    /// Starts at (0, 0) and ends at (2^n - 1, 0), like `curves::hilbert::HilbertCurve`.
+    Hilbert,
+    /// A closed loop of four Hilbert curves that starts at (2^(n-1) - 1, 0) and ends next to it at
+    /// (2^(n-1), 0).
+    Moore,
+    /// Also known as the Morton order. Its index interleaves the bits of the coordinates, which is
+    /// cheap to compute, but consecutive cells are not always adjacent.
+    ZOrder,
+}
+
+/// A space-filling curve of a particular order.
+///
+/// Converting between indices and coordinates takes O(order) time. As a `TurtleProgram`, it draws
+/// a line through the cells in order, scaled so that the grid is 1 wide with its lower left cell
+/// at (0, 0).
+#[derive(Copy, Clone, Debug)]
+pub struct IndexedCurve {
+    curve: SpaceFillingCurve,
+    order: u32,
+}
+
+impl IndexedCurve {
+    /// Panics if `order` is greater than `MAX_ORDER`.
+    pub fn new(curve: SpaceFillingCurve, order: u32) -> IndexedCurve {
+        assert!(
+            order <= MAX_ORDER,
+            "order {} is greater than {}",
+            order,
+            MAX_ORDER
+        );
+        IndexedCurve { curve, order }
+    }
+
+    pub fn curve(self) -> SpaceFillingCurve {
+        self.curve
+    }
+
+    pub fn order(self) -> u32 {
+        self.order
+    }
+
+    /// The width and height of the grid: 2^order.
+    pub fn side(self) -> u64 {
+        1 << self.order
+    }
+
+    /// The number of cells in the grid, and the number of indices along the curve: 4^order.
+    pub fn cells(self) -> u64 {
+        1 << (2 * self.order)
+    }
+
+    /// The (x, y) coordinates of the cell at `index` along the curve.
+    ///
+    /// Panics if `index` is not less than `cells()`.
+    pub fn coordinates(self, index: u64) -> (u32, u32) {
+        assert!(
+            index < self.cells(),
+            "index {} is outside of an order {} curve",
+            index,
+            self.order
+        );
+        let (x, y) = match self.curve {
+            SpaceFillingCurve::Hilbert => hilbert_coordinates(self.order, index),
+            SpaceFillingCurve::Moore => moore_coordinates(self.order, index),
+            SpaceFillingCurve::ZOrder => z_order_coordinates(self.order, index),
+        };
+        (x as u32, y as u32)
+    }
+
+    /// The index along the curve of the cell at (x, y).
+    ///
+    /// Panics if either coordinate is not less than `side()`.
+    pub fn index(self, x: u32, y: u32) -> u64 {
+        let (x, y) = (u64::from(x), u64::from(y));
+        assert!(
+            x < self.side() && y < self.side(),
+            "({}, {}) is outside of an order {} curve",
+            x,
+            y,
+            self.order
+        );
+        match self.curve {
+            SpaceFillingCurve::Hilbert => hilbert_index(self.order, x, y),
+            SpaceFillingCurve::Moore => moore_index(self.order, x, y),
+            SpaceFillingCurve::ZOrder => z_order_index(self.order, x, y),
+        }
+    }
+
+    /// Where the turtle draws the cell at `index`.
+    fn point(self, index: u64) -> Point {
+        let (x, y) = self.coordinates(index);
+        let scale = match self.side() {
+            1 => 1.0,
+            side => 1.0 / (side - 1) as f64,
+        };
+        Point {
+            x: f64::from(x) * scale,
+            y: f64::from(y) * scale,
+        }
+    }
+}
+
+/// Moves a quadrant's coordinates into the orientation of a Hilbert curve of size `side`, or
+/// back: the lower left quadrant is mirrored across its diagonal, and the lower right quadrant is
+/// mirrored across its anti-diagonal.
+fn hilbert_rotate(side: u64, x: u64, y: u64, rx: u64, ry: u64) -> (u64, u64) {
+    match (rx, ry) {
+        (0, 0) => (y, x),
+        (1, 0) => (side - 1 - y, side - 1 - x),
+        _ => (x, y),
+    }
+}
+
+fn hilbert_coordinates(order: u32, index: u64) -> (u64, u64) {
+    let (mut x, mut y) = (0, 0);
+    let mut remaining = index;
+    for level in 0..order {
+        let side = 1 << level;
+        let rx = 1 & (remaining / 2);
+        let ry = 1 & (remaining ^ rx);
+        let (rotated_x, rotated_y) = hilbert_rotate(side, x, y, rx, ry);
+        x = rotated_x + side * rx;
+        y = rotated_y + side * ry;
+        remaining /= 4;
+    }
+    (x, y)
+}
+
+fn hilbert_index(order: u32, x: u64, y: u64) -> u64 {
+    let (mut x, mut y) = (x, y);
+    let mut index = 0;
+    for level in (0..order).rev() {
+        let side = 1 << level;
+        let rx = u64::from(x & side != 0);
+        let ry = u64::from(y & side != 0);
+        index += side * side * ((3 * rx) ^ ry);
+        // only the bits below `side` matter from here on
+        let (rotated_x, rotated_y) = hilbert_rotate(side, x & (side - 1), y & (side - 1), rx, ry);
+        x = rotated_x;
+        y = rotated_y;
+    }
+    index
+}
+
+/// A Moore curve draws a Hilbert curve of one lower order in each quadrant: up the left half, and
+/// then back down the right half.
+fn moore_coordinates(order: u32, index: u64) -> (u64, u64) {
+    if order == 0 {
+        return (0, 0);
+    }
+    let half = 1 << (order - 1);
+    let (x, y) = hilbert_coordinates(order - 1, index % (half * half));
+    match index / (half * half) {
+        0 => (half - 1 - y, x),
+        1 => (half - 1 - y, x + half),
+        2 => (half + y, 2 * half - 1 - x),
+        _ => (half + y, half - 1 - x),
+    }
+}
+
+fn moore_index(order: u32, x: u64, y: u64) -> u64 {
+    if order == 0 {
+        return 0;
+    }
+    let half = 1 << (order - 1);
+    let (quadrant, hilbert_x, hilbert_y) = match (x < half, y < half) {
+        (true, true) => (0, y, half - 1 - x),
+        (true, false) => (1, y - half, half - 1 - x),
+        (false, false) => (2, 2 * half - 1 - y, x - half),
+        (false, true) => (3, half - 1 - y, x - half),
+    };
+    quadrant * half * half + hilbert_index(order - 1, hilbert_x, hilbert_y)
+}
+
+fn z_order_coordinates(order: u32, index: u64) -> (u64, u64) {
+    (0..order).fold((0, 0), |(x, y), bit| {
+        (
+            x | ((index >> (2 * bit)) & 1) << bit,
+            y | ((index >> (2 * bit + 1)) & 1) << bit,
+        )
+    })
+}
+
+fn z_order_index(order: u32, x: u64, y: u64) -> u64 {
+    (0..order).fold(0, |index, bit| {
+        index | ((x >> bit) & 1) << (2 * bit) | ((y >> bit) & 1) << (2 * bit + 1)
+    })
+}
+
+impl TurtleProgram for IndexedCurve {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        vec![
+            TurtleStep::SetPos(self.point(0)),
+            TurtleStep::SetRad(0.0),
+            TurtleStep::Down,
+        ]
+    }
+
+    /// Points the turtle at each cell before moving to it, since Z-order curves can jump in any
+    /// direction.
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        let curve = *self;
+        TurtleProgramIterator::new(Box::new((1..curve.cells()).flat_map(move |index| {
+            let from = curve.point(index - 1);
+            let to = curve.point(index);
+            vec![
+                TurtleStep::SetRad((to.y - from.y).atan2(to.x - from.x)),
+                TurtleStep::Forward(from.distance_to(to)),
+            ]
+        })))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::turtle::polyline::TurtleStats;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    fn adjacent(a: (u32, u32), b: (u32, u32)) -> bool {
+        let dx = (i64::from(a.0) - i64::from(b.0)).abs();
+        let dy = (i64::from(a.1) - i64::from(b.1)).abs();
+        dx + dy == 1
+    }
+
+    #[test]
+    fn test_known_coordinates() {
+        let order_1 = |curve| {
+            let curve = IndexedCurve::new(curve, 1);
+            (0..4).map(|i| curve.coordinates(i)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            order_1(SpaceFillingCurve::Hilbert),
+            [(0, 0), (0, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(
+            order_1(SpaceFillingCurve::Moore),
+            [(0, 0), (0, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(
+            order_1(SpaceFillingCurve::ZOrder),
+            [(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+
+        let moore = IndexedCurve::new(SpaceFillingCurve::Moore, 3);
+        assert_eq!(moore.coordinates(0), (3, 0));
+        assert_eq!(moore.coordinates(63), (4, 0));
+    }
+
+    #[test]
+    fn test_bijection() {
+        for curve in SpaceFillingCurve::iter() {
+            for order in 0..6 {
+                let indexed = IndexedCurve::new(curve, order);
+                let mut seen = HashSet::new();
+                for index in 0..indexed.cells() {
+                    let (x, y) = indexed.coordinates(index);
+                    assert!(u64::from(x) < indexed.side() && u64::from(y) < indexed.side());
+                    assert!(
+                        seen.insert((x, y)),
+                        "{:?} visits ({}, {}) twice",
+                        curve,
+                        x,
+                        y
+                    );
+                    assert_eq!(indexed.index(x, y), index, "{:?} order {}", curve, order);
+                }
+                assert_eq!(seen.len() as u64, indexed.cells());
+            }
+        }
+    }
+
+    #[test]
+    fn test_bijection_at_random() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for curve in SpaceFillingCurve::iter() {
+            for _ in 0..1000 {
+                let indexed = IndexedCurve::new(curve, rng.gen_range(0..=MAX_ORDER));
+                let index = rng.gen_range(0..indexed.cells());
+                let (x, y) = indexed.coordinates(index);
+                assert_eq!(indexed.index(x, y), index, "{:?}", indexed);
+
+                let side = indexed.side();
+                let (x, y) = (rng.gen_range(0..side), rng.gen_range(0..side));
+                let (x, y) = (x as u32, y as u32);
+                assert_eq!(indexed.coordinates(indexed.index(x, y)), (x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_consecutive_indices_are_adjacent() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for curve in [SpaceFillingCurve::Hilbert, SpaceFillingCurve::Moore] {
+            for order in 1..6 {
+                let indexed = IndexedCurve::new(curve, order);
+                for index in 1..indexed.cells() {
+                    assert!(adjacent(
+                        indexed.coordinates(index - 1),
+                        indexed.coordinates(index)
+                    ));
+                }
+            }
+            for _ in 0..1000 {
+                let indexed = IndexedCurve::new(curve, rng.gen_range(1..=MAX_ORDER));
+                let index = rng.gen_range(1..indexed.cells());
+                assert!(
+                    adjacent(indexed.coordinates(index - 1), indexed.coordinates(index)),
+                    "{:?} at {}",
+                    indexed,
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_moore_is_closed() {
+        for order in 1..=MAX_ORDER {
+            let moore = IndexedCurve::new(SpaceFillingCurve::Moore, order);
+            assert!(adjacent(
+                moore.coordinates(0),
+                moore.coordinates(moore.cells() - 1)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_turtle_program() {
+        let hilbert = IndexedCurve::new(SpaceFillingCurve::Hilbert, 3);
+        let stats = TurtleStats::of_program(&hilbert);
+        assert_eq!(stats.segments, 63);
+        assert_point_eq!(stats.start.unwrap(), Point { x: 0.0, y: 0.0 }, 0.000000001);
+        assert_point_eq!(stats.end.unwrap(), Point { x: 1.0, y: 0.0 }, 0.000000001);
+        assert_approx_eq!(stats.length, 9.0, 0.000000001);
+        assert_eq!(stats.intersections, 0);
+
+        let single = TurtleStats::of_program(&IndexedCurve::new(SpaceFillingCurve::ZOrder, 0));
+        assert_eq!(single.segments, 0);
+    }
+}
@@ -90,11 +90,13 @@ impl LindenmayerSystemDrawingParameters<LSA> for TerdragonFractal {
         PI / 6.0 * -(self.iterations as f64)
     }
 
-    fn interpret_symbol(&self, symbol: LSA) -> TurtleStep {
+    fn interpret_symbol(&self, symbol: LSA) -> Option<TurtleStep> {
         match symbol {
-            LSA::F => TurtleStep::Forward(1.0 / (self.lines_between_endpoints() as f64)),
-            LSA::L => TurtleStep::TurnRad(deg2rad(120.0)),
-            LSA::R => TurtleStep::TurnRad(deg2rad(-120.0)),
+            LSA::F => Some(TurtleStep::Forward(
+                1.0 / (self.lines_between_endpoints() as f64),
+            )),
+            LSA::L => Some(TurtleStep::TurnRad(deg2rad(120.0))),
+            LSA::R => Some(TurtleStep::TurnRad(deg2rad(-120.0))),
         }
     }
 }
@@ -177,6 +179,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_segment_info() {
+        use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+        use crate::turtle::TurtleProgram;
+        let depths = |iteration| {
+            LindenmayerSystemTurtleProgram::new(TerdragonFractal::new(iteration))
+                .segment_info()
+                .unwrap()
+                .map(|info| info.depth)
+                .collect::<Vec<u64>>()
+        };
+        assert_eq!(depths(0), [0]);
+        assert_eq!(depths(1), [0, 1, 1]);
+        assert_eq!(depths(2), [0, 2, 2, 1, 2, 2, 1, 2, 2]);
+    }
+
     #[test]
     fn test_initial_pos() {
         assert_point_eq!(
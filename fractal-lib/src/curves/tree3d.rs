@@ -0,0 +1,129 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computations and abstractions needed for rendering a 3D tree, based upon the ternary branching
+//! tree from *The Algorithmic Beauty of Plants* (without its leaves).
+
+use crate::geometry::Point3;
+use crate::lindenmayer::{LindenmayerSystem, LindenmayerSystem3dDrawingParameters};
+use crate::turtle3d::{Frame, Turtle3dStep};
+
+/// The angle used for every pitch and roll, in degrees.
+const ANGLE: f64 = 22.5;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tree3d {
+    iterations: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LSA {
+    A,         // grows three new branches
+    S,         // grows into a new segment of a branch, but does not draw anything
+    F,         // move forward
+    PitchDown, // pitch down 22.5 degrees (&)
+    RollRight, // roll clockwise 22.5 degrees (/)
+    Push,      // start a branch
+    Pop,       // end a branch
+}
+
+impl Tree3d {
+    pub fn new(iterations: u64) -> Tree3d {
+        Tree3d { iterations }
+    }
+
+    /// Each segment of a branch grows into the segment drawn by `S` and the segment after it, so
+    /// the trunk grows along the Fibonacci sequence. Shrinking the segments to match keeps the
+    /// trunk's height at 1.
+    fn distance_forward(self) -> f64 {
+        let (mut previous, mut trunk) = (1.0, 1.0);
+        for _ in 1..self.iterations {
+            (previous, trunk) = (trunk, previous + trunk);
+        }
+        1.0 / trunk
+    }
+}
+
+impl LindenmayerSystem<LSA> for Tree3d {
+    fn initial(&self) -> Vec<LSA> {
+        vec![LSA::F, LSA::A]
+    }
+
+    fn apply_rule(&self, lstr: LSA) -> Vec<LSA> {
+        let roll = |times| vec![LSA::RollRight; times];
+        let branch = || vec![LSA::Push, LSA::PitchDown, LSA::F, LSA::A, LSA::Pop];
+        match lstr {
+            LSA::A => [branch(), roll(5), branch(), roll(7), branch()].concat(),
+            LSA::F => [vec![LSA::S], roll(5), vec![LSA::F]].concat(),
+            LSA::S => vec![LSA::F],
+            x => vec![x],
+        }
+    }
+}
+
+impl LindenmayerSystem3dDrawingParameters<LSA> for Tree3d {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The tree grows up the Z axis.
+    fn initial_frame(&self) -> Frame {
+        Frame::from_heading_left(Point3::new(0.0, 0.0, 1.0), Point3::new(0.0, 1.0, 0.0))
+    }
+
+    fn interpret_symbol(&self, symbol: LSA) -> Option<Turtle3dStep> {
+        match symbol {
+            LSA::A | LSA::S => None,
+            LSA::F => Some(Turtle3dStep::Forward(self.distance_forward())),
+            LSA::PitchDown => Some(Turtle3dStep::pitch_deg(-ANGLE)),
+            LSA::RollRight => Some(Turtle3dStep::roll_deg(ANGLE)),
+            LSA::Push => Some(Turtle3dStep::Push),
+            LSA::Pop => Some(Turtle3dStep::Pop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::LindenmayerSystemTurtle3dProgram;
+    use crate::turtle3d::{record, Turtle3dProgram};
+
+    #[test]
+    fn test_branches() {
+        // each A grows three branches, each ending in another A
+        let pushes: Vec<usize> = (0..4)
+            .map(|i| {
+                LindenmayerSystemTurtle3dProgram::new(Tree3d::new(i))
+                    .turtle_program_iter()
+                    .filter(|step| *step == Turtle3dStep::Push)
+                    .count()
+            })
+            .collect();
+        assert_eq!(pushes, vec![0, 3, 12, 39]);
+    }
+
+    #[test]
+    fn test_trunk_height() {
+        for iteration in 0..6 {
+            let program = LindenmayerSystemTurtle3dProgram::new(Tree3d::new(iteration));
+            let trunk_top = record(&program)[0]
+                .iter()
+                .filter(|point| point.x.abs() < 1e-9 && point.y.abs() < 1e-9)
+                .map(|point| point.z)
+                .fold(0.0, f64::max);
+            assert!((trunk_top - 1.0).abs() < 1e-9, "{}", trunk_top);
+        }
+    }
+}
@@ -63,6 +63,10 @@ impl EscapeTime for BurningShip {
         [Complex64::new(-2.5, 2.0), Complex64::new(1.5, -1.0)]
     }
 
+    fn power(&self) -> u64 {
+        self.power
+    }
+
     fn iterate(&self, c: Complex64, z: Complex64) -> Complex64 {
         let absz = Complex64::new(z.re.abs(), -z.im.abs());
         geometry::cpow(absz, self.power) + c
@@ -117,6 +121,10 @@ impl EscapeTime for BurningMandel {
         [Complex64::new(-2.5, 1.0), Complex64::new(1.5, -1.0)]
     }
 
+    fn power(&self) -> u64 {
+        self.power
+    }
+
     fn iterate(&self, c: Complex64, z: Complex64) -> Complex64 {
         let absz = Complex64::new(z.re.abs(), -z.im);
         geometry::cpow(absz, self.power) + c
@@ -171,6 +179,10 @@ impl EscapeTime for RoadRunner {
         [Complex64::new(-2.5, 1.5), Complex64::new(1.5, -1.5)]
     }
 
+    fn power(&self) -> u64 {
+        self.power
+    }
+
     fn iterate(&self, c: Complex64, z: Complex64) -> Complex64 {
         let absz = Complex64::new(z.re, -z.im.abs());
         geometry::cpow(absz, self.power) + c
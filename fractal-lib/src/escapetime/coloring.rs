@@ -0,0 +1,206 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maps the escape times of a rendered frame onto positions along a palette.
+//!
+//! Renderers compute an escape value for every pixel with `escape_value()`, build a
+//! `PaletteMapping` from the values of the whole frame, and then look up each pixel's color with
+//! `PaletteMapping::position()` and `Palette::color_at()`.
+
+use super::{Complex64, EscapeTime};
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The number of palette bands that `ColoringMode::Linear` spreads escape times over. Escape
+/// times past the last band all use the end of the palette.
+pub const LINEAR_BANDS: u64 = 50;
+
+/// How escape times are mapped onto a palette.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColoringMode {
+    /// Each iteration moves a fixed distance along the palette.
+    Linear,
+    /// Escape times are spread out according to how common they are in the frame, so that every
+    /// part of the palette covers roughly the same number of pixels.
+    Histogram,
+}
+
+/// Tests a point and returns its escape value, or `None` if the point does not escape.
+///
+/// If `smooth` is true, the escape value is continuous (see `EscapeTime::test_point_smooth()`),
+/// otherwise it is a whole number of iterations.
+pub fn escape_value(etsystem: &dyn EscapeTime, point: Complex64, smooth: bool) -> Option<f64> {
+    let (attracted, time) = if smooth {
+        etsystem.test_point_smooth(point)
+    } else {
+        let (attracted, time) = etsystem.test_point(point);
+        (attracted, time as f64)
+    };
+    if attracted {
+        None
+    } else {
+        Some(time)
+    }
+}
+
+/// Converts escape values to palette positions between 0.0 and 1.0.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteMapping {
+    /// Divides escape values by a fixed number of bands.
+    Linear { bands: f64 },
+    /// Uses the cumulative distribution of the escape values in a frame.
+    Histogram(HistogramEqualizer),
+}
+
+impl PaletteMapping {
+    /// Constructs the mapping for a frame. `escape_values` is only needed for
+    /// `ColoringMode::Histogram`, and should contain the escape value of every pixel in the frame
+    /// that escaped.
+    pub fn new<I>(mode: ColoringMode, max_iterations: u64, escape_values: I) -> PaletteMapping
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        match mode {
+            ColoringMode::Linear => PaletteMapping::linear(max_iterations),
+            ColoringMode::Histogram => {
+                PaletteMapping::Histogram(HistogramEqualizer::new(escape_values))
+            }
+        }
+    }
+
+    /// The linear mapping, which does not depend on the rest of the frame.
+    pub fn linear(max_iterations: u64) -> PaletteMapping {
+        let bands = max_iterations.clamp(2, LINEAR_BANDS) - 1;
+        PaletteMapping::Linear {
+            bands: bands as f64,
+        }
+    }
+
+    /// The position along the palette for an escape value.
+    pub fn position(&self, escape_value: f64) -> f64 {
+        match self {
+            PaletteMapping::Linear { bands } => (escape_value / bands).clamp(0.0, 1.0),
+            PaletteMapping::Histogram(equalizer) => equalizer.cdf(escape_value),
+        }
+    }
+}
+
+/// Builds the cumulative distribution of the escape values in a frame.
+///
+/// Whole-number escape times form a histogram with one bucket per iteration, and each bucket is
+/// mapped to the middle of its share of the palette. Smooth escape times are effectively
+/// buckets of one, which makes the mapping continuous.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramEqualizer {
+    sorted: Vec<f64>,
+}
+
+impl HistogramEqualizer {
+    /// Builds the distribution from the escape values of a frame. NaN values are ignored.
+    pub fn new<I>(escape_values: I) -> HistogramEqualizer
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let mut sorted: Vec<f64> = escape_values.into_iter().filter(|v| !v.is_nan()).collect();
+        sorted.sort_unstable_by(f64::total_cmp);
+        HistogramEqualizer { sorted }
+    }
+
+    /// The fraction of the frame's escape values that are below `escape_value`, counting values
+    /// equal to it as half below. The result is between 0.0 and 1.0.
+    pub fn cdf(&self, escape_value: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return 0.0;
+        }
+        let below = self.sorted.partition_point(|v| *v < escape_value);
+        let at_or_below = self.sorted.partition_point(|v| *v <= escape_value);
+        (below + at_or_below) as f64 / (2 * self.sorted.len()) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::mandelbrot::Mandelbrot;
+    use super::*;
+
+    #[test]
+    fn test_linear() {
+        let mapping = PaletteMapping::linear(100);
+        assert_eq!(mapping.position(0.0), 0.0);
+        assert_eq!(mapping.position(49.0), 1.0);
+        assert_eq!(mapping.position(80.0), 1.0);
+        let mapping = PaletteMapping::linear(11);
+        assert_eq!(mapping.position(5.0), 0.5);
+        // degenerate limits should not divide by zero
+        assert_eq!(PaletteMapping::linear(1).position(0.0), 0.0);
+        assert_eq!(PaletteMapping::linear(0).position(3.0), 1.0);
+    }
+
+    #[test]
+    fn test_histogram_integer_values() {
+        // Most of the frame escapes after 3 iterations, so that band gets most of the palette.
+        let values = [1.0, 3.0, 3.0, 3.0, 3.0, 3.0, 3.0, 90.0];
+        let mapping = PaletteMapping::new(ColoringMode::Histogram, 100, values.iter().cloned());
+        assert_eq!(mapping.position(1.0), 0.0625);
+        assert_eq!(mapping.position(3.0), 0.5);
+        assert_eq!(mapping.position(90.0), 0.9375);
+        // values between and outside of the frame's values still map sensibly
+        assert_eq!(mapping.position(2.0), 0.125);
+        assert_eq!(mapping.position(0.0), 0.0);
+        assert_eq!(mapping.position(100.0), 1.0);
+    }
+
+    #[test]
+    fn test_histogram_smooth_values() {
+        let values: Vec<f64> = (0..1000).map(|i| f64::from(i) / 100.0).collect();
+        let mapping = PaletteMapping::new(ColoringMode::Histogram, 100, values);
+        assert_eq!(mapping.position(0.0), 0.0005);
+        assert_eq!(mapping.position(5.0), 0.5005);
+        assert_eq!(mapping.position(5.005), 0.501);
+        assert!(mapping.position(9.99) < 1.0);
+    }
+
+    #[test]
+    fn test_histogram_without_values() {
+        let mapping = PaletteMapping::new(ColoringMode::Histogram, 100, vec![f64::NAN]);
+        assert_eq!(mapping.position(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_escape_value() {
+        let mb = Mandelbrot::new(100, 2);
+        assert_eq!(escape_value(&mb, Complex64::new(0.0, 0.0), false), None);
+        assert_eq!(escape_value(&mb, Complex64::new(0.0, 0.0), true), None);
+
+        let point = Complex64::new(-0.8, 0.35);
+        let whole = escape_value(&mb, point, false).unwrap();
+        let smooth = escape_value(&mb, point, true).unwrap();
+        assert_eq!(whole, mb.test_point(point).1 as f64);
+        assert!(whole <= smooth && smooth <= whole + 1.0);
+    }
+
+    #[test]
+    fn test_smooth_values_are_continuous() {
+        // Neighboring points should have similar smooth escape times, even when they cross a
+        // boundary between whole-number escape times.
+        let mb = Mandelbrot::new(100, 2);
+        let mut previous = escape_value(&mb, Complex64::new(-2.0, 0.5), true).unwrap();
+        for i in 1..=1000 {
+            let point = Complex64::new(-2.0 + f64::from(i) / 1000.0, 0.5);
+            let smooth = escape_value(&mb, point, true).unwrap();
+            assert!((smooth - previous).abs() < 0.2, "jump at {}", point);
+            previous = smooth;
+        }
+    }
+}
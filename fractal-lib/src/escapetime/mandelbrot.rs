@@ -55,6 +55,10 @@ impl EscapeTime for Mandelbrot {
         [Complex64::new(-2.0, 1.0), Complex64::new(1.0, -1.0)]
     }
 
+    fn power(&self) -> u64 {
+        self.power
+    }
+
     fn iterate(&self, c: Complex64, z: Complex64) -> Complex64 {
         geometry::cpow(z, self.power) + c
     }
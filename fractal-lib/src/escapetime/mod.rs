@@ -13,10 +13,21 @@
 // limitations under the License.
 
 pub mod burningship;
+pub mod coloring;
 pub mod mandelbrot;
 
 pub use num::complex::Complex64;
 
+/// Once the iterated value is this far from the origin, it is assumed to diverge.
+pub const ESCAPE_RADIUS: f64 = 3.0;
+
+/// The number of extra iterations `EscapeTime::test_point_smooth` may perform past the escape
+/// radius while waiting for the iterated value to grow large enough to smooth accurately.
+const SMOOTHING_ITERATIONS: u64 = 8;
+
+/// How large the iterated value needs to grow before its smooth escape time is accurate.
+const SMOOTHING_RADIUS: f64 = 256.0;
+
 pub trait EscapeTime {
     /// The maximum number of iterations to perform before accepting that the value being
     /// iterated will not diverge.
@@ -30,6 +41,12 @@ pub trait EscapeTime {
     /// A single iteration of the function that defines this particular fractal.
     fn iterate(&self, c: Complex64, z: Complex64) -> Complex64;
 
+    /// The exponent used by `iterate`. It is used to compute smooth escape times, and defaults to
+    /// 2.
+    fn power(&self) -> u64 {
+        2
+    }
+
     /// Tests whether a given complex number is in the fractal's set or if it diverges.
     ///
     /// The default implementation implements the mandelbrot test, which uses the complex number
@@ -41,10 +58,43 @@ pub trait EscapeTime {
         let mut zp = Complex64::new(0.0, 0.0);
         for i in 0..self.max_iterations() {
             zp = self.iterate(point, zp);
-            if zp.norm() >= 3.0 {
+            if zp.norm() >= ESCAPE_RADIUS {
                 return (false, i);
             }
         }
         (true, 0)
     }
+
+    /// Like `test_point`, but a point that escapes is given a continuous escape time instead of a
+    /// whole number of iterations. This avoids visible bands between points that escape after
+    /// different numbers of iterations.
+    ///
+    /// The smooth escape time for a point is between the escape time from `test_point` and the
+    /// next whole number, based on how far past the escape radius the iterated value landed. This
+    /// relies on `EscapeTime::power()`, and falls back to whole numbers for powers below 2.
+    fn test_point_smooth(&self, point: Complex64) -> (bool, f64) {
+        let mut zp = Complex64::new(0.0, 0.0);
+        for i in 0..self.max_iterations() {
+            zp = self.iterate(point, zp);
+            if zp.norm() >= ESCAPE_RADIUS {
+                let power = self.power();
+                if power < 2 {
+                    return (false, i as f64);
+                }
+                // |z| grows by roughly a power of `power` every iteration once it is large, so
+                // iterating a little further makes the estimate much more accurate.
+                let mut n = i;
+                while zp.norm() < SMOOTHING_RADIUS && n - i < SMOOTHING_ITERATIONS {
+                    zp = self.iterate(point, zp);
+                    n += 1;
+                }
+                let overshoot = (zp.norm().ln() / ESCAPE_RADIUS.ln()).ln() / (power as f64).ln();
+                return (
+                    false,
+                    (n as f64 + 1.0 - overshoot).clamp(i as f64, i as f64 + 1.0),
+                );
+            }
+        }
+        (true, 0.0)
+    }
 }
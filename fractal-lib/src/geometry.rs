@@ -12,12 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Various types and functions the work within a 2-D cartesian coordinate
-//! system.
+//! Various types and functions the work within a 2-D (and, for `Point3`, 3-D) cartesian
+//! coordinate system.
 
 use num::complex::Complex64;
 use std::f64::consts::PI;
 use std::fmt;
+use std::ops;
 
 pub type Vec2d = [f64; 2];
 
@@ -101,6 +102,81 @@ impl Vector {
     }
 }
 
+/// Represents a point, or a vector from the origin, in a 3-D cartesian coordinate system.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Point3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Point3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    pub fn dot(self, other: Point3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Point3) -> Point3 {
+        Point3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// The distance from the origin.
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn distance_to(self, other: Point3) -> f64 {
+        (self - other).length()
+    }
+
+    /// Scales the vector to a length of 1. The zero vector stays the zero vector.
+    pub fn normalized(self) -> Point3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+}
+
+impl ops::Add for Point3 {
+    type Output = Point3;
+
+    fn add(self, other: Point3) -> Point3 {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl ops::Sub for Point3 {
+    type Output = Point3;
+
+    fn sub(self, other: Point3) -> Point3 {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl ops::Mul<f64> for Point3 {
+    type Output = Point3;
+
+    fn mul(self, scale: f64) -> Point3 {
+        Point3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl fmt::Display for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
 /// Converts degrees into radians.
 pub fn deg2rad(degrees: f64) -> f64 {
     degrees / 360.0 * 2.0 * PI
@@ -141,6 +217,26 @@ impl AffineTransform<Point> for CartesianAffineTransform {
     }
 }
 
+/// Builds the affine transform that rotates points by `radians` (counter-clockwise) about
+/// `center`.
+pub fn rotation_about(center: Point, radians: f64) -> CartesianAffineTransform {
+    let (sin, cos) = radians.sin_cos();
+    [
+        [cos, -sin, center.x - cos * center.x + sin * center.y],
+        [sin, cos, center.y - sin * center.x - cos * center.y],
+    ]
+}
+
+/// Builds the affine transform that mirrors points across the line that passes through `through`
+/// at an angle of `radians` from the X axis.
+pub fn reflection_across(through: Point, radians: f64) -> CartesianAffineTransform {
+    let (sin, cos) = (2.0 * radians).sin_cos();
+    [
+        [cos, sin, through.x - cos * through.x - sin * through.y],
+        [sin, -cos, through.y - sin * through.x + cos * through.y],
+    ]
+}
+
 /// Ensures that the cartesian area specified by `top_left` and `bot_right` fit into the
 /// `view_area` of a window/viewport without distortion.
 ///
@@ -317,6 +413,41 @@ mod test {
         assert_eq!(Point::from(vec2d).y, 456.5);
     }
 
+    #[test]
+    fn test_point3() {
+        let x = Point3::new(1.0, 0.0, 0.0);
+        let y = Point3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.cross(y), Point3::new(0.0, 0.0, 1.0));
+        assert_eq!(y.cross(x), Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(x.dot(y), 0.0);
+        assert_eq!(x + y * 2.0 - x, Point3::new(0.0, 2.0, 0.0));
+        assert_approx_eq!(Point3::new(1.0, 2.0, 2.0).length(), 3.0, 0.000000001);
+        assert_approx_eq!(
+            Point3::new(0.0, 3.0, 4.0).normalized().length(),
+            1.0,
+            0.000000001
+        );
+        assert_eq!(Point3::default().normalized(), Point3::default());
+        assert_approx_eq!(x.distance_to(y), SQRT_2, 0.000000001);
+    }
+
+    #[test]
+    fn test_rotation_and_reflection() {
+        let assert_point = |actual: Point, x: f64, y: f64| {
+            assert_approx_eq!(actual.x, x, 0.000000001);
+            assert_approx_eq!(actual.y, y, 0.000000001);
+        };
+        let center = Point { x: 1.0, y: 1.0 };
+        let quarter_turn = rotation_about(center, PI / 2.0);
+        assert_point(quarter_turn.transform(Point { x: 2.0, y: 1.0 }), 1.0, 2.0);
+        assert_point(quarter_turn.transform(center), 1.0, 1.0);
+
+        let mirror = reflection_across(Point { x: 0.0, y: 1.0 }, 0.0);
+        assert_point(mirror.transform(Point { x: 3.0, y: 3.0 }), 3.0, -1.0);
+        let diagonal = reflection_across(Point { x: 0.0, y: 0.0 }, PI / 4.0);
+        assert_point(diagonal.transform(Point { x: 2.0, y: 0.0 }), 0.0, 2.0);
+    }
+
     #[test]
     fn test_vector_delta_x() {
         assert_approx_eq!(
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::lindenmayer::presets::{self, LSystemPreset};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
 
@@ -26,6 +27,7 @@ pub mod escapetime;
 pub mod geometry;
 pub mod lindenmayer;
 pub mod turtle;
+pub mod turtle3d;
 
 /// Mainly used to categorize the fractals in a UI or menu.
 #[derive(Copy, Clone, EnumIter, PartialEq)]
@@ -33,6 +35,7 @@ pub enum FractalCategory {
     ChaosGames,
     EscapeTimeFractals,
     TurtleCurves,
+    LSystemPresets,
 }
 
 impl FractalCategory {
@@ -41,6 +44,7 @@ impl FractalCategory {
             FractalCategory::ChaosGames => "Chaos Games",
             FractalCategory::EscapeTimeFractals => "Escape-time Fractals",
             FractalCategory::TurtleCurves => "Turtle Curves",
+            FractalCategory::LSystemPresets => "L-System Presets",
         }
     }
 }
@@ -57,8 +61,8 @@ impl FractalCategory {
 /// use strum::IntoEnumIterator;
 ///
 /// SelectedFractal::iter().for_each(|fractal| println!("{:?}", fractal));
-/// // => SelectedFractal::BarnsleyGern
-/// // => SelectedFractal::BurningManel
+/// // => SelectedFractal::AntiSnowflake
+/// // => SelectedFractal::BarnsleyFern
 /// // => ...
 /// ```
 ///
@@ -92,18 +96,51 @@ impl FractalCategory {
 #[derive(Copy, Clone, Debug, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
 #[strum(serialize_all = "lowercase")]
 pub enum SelectedFractal {
+    AntiSnowflake,
     BarnsleyFern,
+    Board,
     BurningMandel,
     BurningShip,
+    Bush,
+    Bush2,
+    Bush3,
     Cesaro,
     CesaroTri,
+    Crystal,
     Dragon,
+    FractalPlant,
+    Gosper,
+    HexSnowflake,
+    Hilbert,
+    Hilbert3d,
+    Islands,
     KochCurve,
+    KochIsland,
     LevyCCurve,
     Mandelbrot,
+    Moore,
+    PaperFolding,
+    ParametricTree,
+    Peano,
+    Pentaplexity,
+    PlantA,
+    PlantB,
+    PlantC,
+    PlantD,
+    PlantE,
+    PlantF,
+    QuadraticKoch,
+    Rings,
     RoadRunner,
     Sierpinski,
+    SierpinskiArrowhead,
+    SierpinskiCurve,
+    StochasticPlant,
+    TallPlant,
     TerDragon,
+    Tiles,
+    Tree3d,
+    Weed,
 }
 
 impl SelectedFractal {
@@ -125,36 +162,102 @@ impl SelectedFractal {
     /// you turn the Enum `into()` a `&'static str`.
     pub fn name(self) -> &'static str {
         match self {
+            SelectedFractal::AntiSnowflake => "Koch Anti-snowflake",
             SelectedFractal::BarnsleyFern => "Barnsley Fern",
+            SelectedFractal::Board => "Board",
             SelectedFractal::BurningMandel => "Burning Mandel",
             SelectedFractal::BurningShip => "Burning Ship",
+            SelectedFractal::Bush => "Bush",
+            SelectedFractal::Bush2 => "Bush 2",
+            SelectedFractal::Bush3 => "Bush 3",
             SelectedFractal::Cesaro => "Cesàro",
             SelectedFractal::CesaroTri => "Cesàro Triangle",
+            SelectedFractal::Crystal => "Crystal",
             SelectedFractal::Dragon => "Dragon",
+            SelectedFractal::FractalPlant => "Fractal Plant",
+            SelectedFractal::Gosper => "Gosper Curve",
+            SelectedFractal::HexSnowflake => "Hexagonal Snowflake",
+            SelectedFractal::Hilbert => "Hilbert Curve",
+            SelectedFractal::Hilbert3d => "3D Hilbert Curve",
+            SelectedFractal::Islands => "Islands and Lakes",
             SelectedFractal::KochCurve => "Koch Curve",
+            SelectedFractal::KochIsland => "Quadratic Koch Island",
             SelectedFractal::LevyCCurve => "Lévy C Curve",
             SelectedFractal::Mandelbrot => "Mandelbrot",
+            SelectedFractal::Moore => "Moore Curve",
+            SelectedFractal::PaperFolding => "Paperfolding Curve",
+            SelectedFractal::ParametricTree => "Parametric Tree",
+            SelectedFractal::Peano => "Peano Curve",
+            SelectedFractal::Pentaplexity => "Pentaplexity",
+            SelectedFractal::PlantA => "Plant A",
+            SelectedFractal::PlantB => "Plant B",
+            SelectedFractal::PlantC => "Plant C",
+            SelectedFractal::PlantD => "Plant D",
+            SelectedFractal::PlantE => "Plant E",
+            SelectedFractal::PlantF => "Plant F",
+            SelectedFractal::QuadraticKoch => "Quadratic Koch Curve",
+            SelectedFractal::Rings => "Rings",
             SelectedFractal::RoadRunner => "Roadrunner",
             SelectedFractal::Sierpinski => "Sierpiński Triangle",
+            SelectedFractal::SierpinskiArrowhead => "Sierpiński Arrowhead Curve",
+            SelectedFractal::SierpinskiCurve => "Sierpiński Curve",
+            SelectedFractal::StochasticPlant => "Stochastic Plant",
+            SelectedFractal::TallPlant => "Tall Plant",
             SelectedFractal::TerDragon => "Terdragon",
+            SelectedFractal::Tiles => "Tiles",
+            SelectedFractal::Tree3d => "3D Tree",
+            SelectedFractal::Weed => "Weed",
         }
     }
 
     /// A short description of each fractal variant.
     pub fn description(self) -> &'static str {
         match self {
+            SelectedFractal::AntiSnowflake => "Draws a Koch snowflake whose bumps point inwards",
             SelectedFractal::BarnsleyFern => "Draws the Barnsley Fern fractal using a chaos game with affine transforms.",
+            SelectedFractal::Board => "Draws a board of squares within squares",
             SelectedFractal::BurningMandel => "Draws a variation of the burning ship fractal",
             SelectedFractal::BurningShip => "Draws the burning ship fractal",
+            SelectedFractal::Bush => "Draws a bush whose branches end in tufts",
+            SelectedFractal::Bush2 => "Draws a dense bush of forking branches",
+            SelectedFractal::Bush3 => "Draws a bush of feathery fronds",
             SelectedFractal::Cesaro => "Draws a square Cesàro fractal",
             SelectedFractal::CesaroTri => "Draws a triangle Cesàro fractal",
+            SelectedFractal::Crystal => "Draws a crystal that grows inwards from the sides of a square",
             SelectedFractal::Dragon => "Draws a dragon curve fractal",
+            SelectedFractal::FractalPlant => "Draws a branching fractal plant",
+            SelectedFractal::Gosper => "Draws a Gosper curve (flowsnake) that fills a hexagonal region",
+            SelectedFractal::HexSnowflake => "Draws a Koch snowflake around a hexagon",
+            SelectedFractal::Hilbert => "Draws a Hilbert curve that fills a square",
+            SelectedFractal::Hilbert3d => "Draws a 3D Hilbert curve that fills a cube",
+            SelectedFractal::Islands => "Draws islands and lakes, from The Algorithmic Beauty of Plants",
             SelectedFractal::KochCurve => "Draws a Koch snowflake curve",
+            SelectedFractal::KochIsland => "Draws a quadratic Koch island, from The Algorithmic Beauty of Plants",
             SelectedFractal::LevyCCurve => "Draws a Lévy C Curve",
             SelectedFractal::Mandelbrot => "Draws the mandelbrot fractal",
+            SelectedFractal::Moore => "Draws a Moore curve, a closed variant of the Hilbert curve",
+            SelectedFractal::PaperFolding => "Draws the curve made by folding a strip of paper in a pattern of directions",
+            SelectedFractal::ParametricTree => "Draws a tree whose branches shrink and narrow as they grow, using a parametric L-system",
+            SelectedFractal::Peano => "Draws a Peano curve that fills a square",
+            SelectedFractal::Pentaplexity => "Draws a snowflake of pentagons like a Penrose tiling",
+            SelectedFractal::PlantA => "Draws plant (a) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::PlantB => "Draws plant (b) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::PlantC => "Draws plant (c) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::PlantD => "Draws plant (d) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::PlantE => "Draws plant (e) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::PlantF => "Draws plant (f) of figure 1.24 in The Algorithmic Beauty of Plants",
+            SelectedFractal::QuadraticKoch => "Draws a quadratic Koch curve made of square bumps",
+            SelectedFractal::Rings => "Draws a ring of square crystals",
             SelectedFractal::RoadRunner => "Draws a variation of the burning ship fractal",
             SelectedFractal::Sierpinski => "Draws a Sierpiński triangle using a chaos game and 3 randomly chosen points on the screen",
+            SelectedFractal::SierpinskiArrowhead => "Draws a Sierpiński arrowhead curve that approximates the Sierpiński triangle",
+            SelectedFractal::SierpinskiCurve => "Draws a closed Sierpiński curve that fills a square",
+            SelectedFractal::StochasticPlant => "Draws a plant whose branches grow at random, from The Algorithmic Beauty of Plants",
+            SelectedFractal::TallPlant => "Draws a tall plant with leaning side branches",
             SelectedFractal::TerDragon => "Draws a terdragon curve",
+            SelectedFractal::Tiles => "Draws a tiling of squares and rectangles",
+            SelectedFractal::Tree3d => "Draws a 3D tree that forks into three branches",
+            SelectedFractal::Weed => "Draws a weed with a long, thin stem",
         }
     }
 
@@ -163,18 +266,96 @@ impl SelectedFractal {
     /// The categories relate to the kind of configuration that the given fractal needs.
     pub fn category(self) -> FractalCategory {
         match self {
+            SelectedFractal::AntiSnowflake => FractalCategory::LSystemPresets,
             SelectedFractal::BarnsleyFern => FractalCategory::ChaosGames,
+            SelectedFractal::Board => FractalCategory::LSystemPresets,
             SelectedFractal::BurningMandel => FractalCategory::EscapeTimeFractals,
             SelectedFractal::BurningShip => FractalCategory::EscapeTimeFractals,
+            SelectedFractal::Bush => FractalCategory::LSystemPresets,
+            SelectedFractal::Bush2 => FractalCategory::LSystemPresets,
+            SelectedFractal::Bush3 => FractalCategory::LSystemPresets,
             SelectedFractal::Cesaro => FractalCategory::TurtleCurves,
             SelectedFractal::CesaroTri => FractalCategory::TurtleCurves,
+            SelectedFractal::Crystal => FractalCategory::LSystemPresets,
             SelectedFractal::Dragon => FractalCategory::TurtleCurves,
+            SelectedFractal::FractalPlant => FractalCategory::TurtleCurves,
+            SelectedFractal::Gosper => FractalCategory::TurtleCurves,
+            SelectedFractal::HexSnowflake => FractalCategory::LSystemPresets,
+            SelectedFractal::Hilbert => FractalCategory::TurtleCurves,
+            SelectedFractal::Hilbert3d => FractalCategory::TurtleCurves,
+            SelectedFractal::Islands => FractalCategory::LSystemPresets,
             SelectedFractal::KochCurve => FractalCategory::TurtleCurves,
+            SelectedFractal::KochIsland => FractalCategory::LSystemPresets,
             SelectedFractal::LevyCCurve => FractalCategory::TurtleCurves,
             SelectedFractal::Mandelbrot => FractalCategory::EscapeTimeFractals,
+            SelectedFractal::Moore => FractalCategory::TurtleCurves,
+            SelectedFractal::PaperFolding => FractalCategory::TurtleCurves,
+            SelectedFractal::ParametricTree => FractalCategory::TurtleCurves,
+            SelectedFractal::Peano => FractalCategory::TurtleCurves,
+            SelectedFractal::Pentaplexity => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantA => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantB => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantC => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantD => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantE => FractalCategory::LSystemPresets,
+            SelectedFractal::PlantF => FractalCategory::LSystemPresets,
+            SelectedFractal::QuadraticKoch => FractalCategory::TurtleCurves,
+            SelectedFractal::Rings => FractalCategory::LSystemPresets,
             SelectedFractal::RoadRunner => FractalCategory::EscapeTimeFractals,
             SelectedFractal::Sierpinski => FractalCategory::ChaosGames,
+            SelectedFractal::SierpinskiArrowhead => FractalCategory::TurtleCurves,
+            SelectedFractal::SierpinskiCurve => FractalCategory::TurtleCurves,
+            SelectedFractal::StochasticPlant => FractalCategory::LSystemPresets,
+            SelectedFractal::TallPlant => FractalCategory::LSystemPresets,
             SelectedFractal::TerDragon => FractalCategory::TurtleCurves,
+            SelectedFractal::Tiles => FractalCategory::LSystemPresets,
+            SelectedFractal::Tree3d => FractalCategory::TurtleCurves,
+            SelectedFractal::Weed => FractalCategory::LSystemPresets,
         }
     }
+
+    /// Whether the variant is drawn by a turtle, either as one of the turtle curves or as an
+    /// L-system preset.
+    pub fn is_turtle(self) -> bool {
+        matches!(
+            self.category(),
+            FractalCategory::TurtleCurves | FractalCategory::LSystemPresets
+        )
+    }
+
+    /// The grammar and recommended drawing parameters of an L-system preset, or `None` if the
+    /// variant is not one.
+    pub fn preset(self) -> Option<LSystemPreset> {
+        let preset = match self {
+            SelectedFractal::AntiSnowflake => presets::ANTI_SNOWFLAKE,
+            SelectedFractal::Board => presets::BOARD,
+            SelectedFractal::Bush => presets::BUSH,
+            SelectedFractal::Bush2 => presets::BUSH_2,
+            SelectedFractal::Bush3 => presets::BUSH_3,
+            SelectedFractal::Crystal => presets::CRYSTAL,
+            SelectedFractal::HexSnowflake => presets::HEX_SNOWFLAKE,
+            SelectedFractal::Islands => presets::ISLANDS,
+            SelectedFractal::KochIsland => presets::KOCH_ISLAND,
+            SelectedFractal::Pentaplexity => presets::PENTAPLEXITY,
+            SelectedFractal::PlantA => presets::PLANT_A,
+            SelectedFractal::PlantB => presets::PLANT_B,
+            SelectedFractal::PlantC => presets::PLANT_C,
+            SelectedFractal::PlantD => presets::PLANT_D,
+            SelectedFractal::PlantE => presets::PLANT_E,
+            SelectedFractal::PlantF => presets::PLANT_F,
+            SelectedFractal::Rings => presets::RINGS,
+            SelectedFractal::StochasticPlant => presets::STOCHASTIC_PLANT,
+            SelectedFractal::TallPlant => presets::TALL_PLANT,
+            SelectedFractal::Tiles => presets::TILES,
+            SelectedFractal::Weed => presets::WEED,
+            _ => return None,
+        };
+        Some(preset)
+    }
+
+    /// Whether the variant is a turtle curve drawn in three dimensions, which has to be projected
+    /// (see `turtle3d::projection`) before it can be displayed.
+    pub fn is_3d(self) -> bool {
+        matches!(self, SelectedFractal::Hilbert3d | SelectedFractal::Tree3d)
+    }
 }
@@ -0,0 +1,369 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Context-sensitive L-systems, whose rules can depend on the symbols on either side of the one
+//! being replaced.
+//!
+//! A 1L system's rules see one neighbour, and a 2L system's rules see both, as in the rule
+//! `A < B > C -> X`, which replaces `B` with `X` only when it comes after an `A` and before a `C`.
+//! Rules like these can pass signals along a plant, such as a hormone moving up from its roots.
+//!
+//! The neighbours a symbol sees follow the structure of its branches:
+//!
+//! * Its left neighbour is the symbol before it on the way back to the start of the sequence. Whole
+//!   branches are skipped, so in `A[B]C` the left neighbour of `C` is `A`, and the first symbol
+//!   of a branch sees the symbol the branch grows from, so the left neighbour of `B` is also `A`.
+//! * Its right neighbour is the next symbol on the same branch, skipping whole branches, so in
+//!   `A[B]C` the right neighbour of `A` is `C`. The last symbol of a branch has no right neighbour.
+//!
+//! Symbols that only matter for drawing, such as turns, can be ignored so that they are never
+//! anyone's neighbour.
+
+use super::{iteration_rng, LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::geometry::Point;
+use crate::turtle::TurtleStep;
+use rand::RngCore;
+use std::marker::PhantomData;
+
+/// Whether a symbol starts or ends a branch.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bracket {
+    Open,
+    Close,
+}
+
+/// An L-system whose rules see each symbol's neighbours. Use a
+/// `LindenmayerSystemContextAdapter` to generate or draw it like any other `LindenmayerSystem`.
+pub trait ContextSensitiveLindenmayerSystem<Alphabet: Clone> {
+    /// Should return the initial Lindenmayer system string (iteration 0).
+    fn initial(&self) -> Vec<Alphabet>;
+
+    /// Apply Lindenmayer system rules to `curr_symbol`, given its neighbours (see the module
+    /// documentation), which are `None` at the ends of the sequence or its branches. Stochastic
+    /// systems can use `rng` to choose between successors.
+    fn apply_rule(
+        &self,
+        left: Option<&Alphabet>,
+        curr_symbol: Alphabet,
+        right: Option<&Alphabet>,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Alphabet>;
+
+    /// Whether `symbol` starts or ends a branch. Defaults to neither, for systems without
+    /// branches.
+    fn bracket(&self, _symbol: &Alphabet) -> Option<Bracket> {
+        None
+    }
+
+    /// Whether `symbol` is skipped when finding the neighbours of other symbols. Defaults to
+    /// false.
+    fn ignore_in_context(&self, _symbol: &Alphabet) -> bool {
+        false
+    }
+
+    /// The seed that chooses between successors. Defaults to 0.
+    fn seed(&self) -> u64 {
+        0
+    }
+}
+
+/// Finds the indices of the left and right neighbours of every symbol in `sequence`.
+///
+/// Brackets and ignored symbols have neighbours, but are never neighbours themselves.
+pub fn neighbours<L, A>(system: &L, sequence: &[A]) -> Vec<(Option<usize>, Option<usize>)>
+where
+    L: ContextSensitiveLindenmayerSystem<A>,
+    A: Clone,
+{
+    let is_neighbour =
+        |symbol: &A| system.bracket(symbol).is_none() && !system.ignore_in_context(symbol);
+    let mut found = vec![(None, None); sequence.len()];
+
+    // the last symbol seen on the current branch, saved while its side branches are visited
+    let mut last = None;
+    let mut saved = vec![];
+    for (index, symbol) in sequence.iter().enumerate() {
+        found[index].0 = last;
+        match system.bracket(symbol) {
+            Some(Bracket::Open) => saved.push(last),
+            Some(Bracket::Close) => last = saved.pop().unwrap_or(None),
+            None if is_neighbour(symbol) => last = Some(index),
+            None => {}
+        }
+    }
+
+    // the same, backwards, except that the end of a branch has nothing after it
+    let mut next = None;
+    let mut saved = vec![];
+    for (index, symbol) in sequence.iter().enumerate().rev() {
+        found[index].1 = next;
+        match system.bracket(symbol) {
+            Some(Bracket::Close) => {
+                saved.push(next);
+                next = None;
+            }
+            Some(Bracket::Open) => next = saved.pop().unwrap_or(None),
+            None if is_neighbour(symbol) => next = Some(index),
+            None => {}
+        }
+    }
+    found
+}
+
+/// Generates and draws a `ContextSensitiveLindenmayerSystem` as a `LindenmayerSystem`, so that
+/// it can be cached by a `LindenmayerSystemCachingDecorator` and drawn by a
+/// `LindenmayerSystemTurtleProgram`.
+#[derive(Clone, Debug)]
+pub struct LindenmayerSystemContextAdapter<L, A>
+where
+    L: ContextSensitiveLindenmayerSystem<A>,
+    A: Clone,
+{
+    alphabet: PhantomData<A>,
+    pub system: L,
+}
+
+impl<L, A> LindenmayerSystemContextAdapter<L, A>
+where
+    L: ContextSensitiveLindenmayerSystem<A>,
+    A: Clone,
+{
+    pub fn new(system: L) -> LindenmayerSystemContextAdapter<L, A> {
+        LindenmayerSystemContextAdapter {
+            alphabet: PhantomData,
+            system,
+        }
+    }
+}
+
+impl<L, A> LindenmayerSystem<A> for LindenmayerSystemContextAdapter<L, A>
+where
+    L: ContextSensitiveLindenmayerSystem<A>,
+    A: Clone,
+{
+    fn initial(&self) -> Vec<A> {
+        self.system.initial()
+    }
+
+    /// Applies the rules as if `curr_symbol` had no neighbours.
+    fn apply_rule(&self, curr_symbol: A) -> Vec<A> {
+        self.apply_rule_with_rng(curr_symbol, &mut iteration_rng(self.seed(), 0))
+    }
+
+    /// Applies the rules as if `curr_symbol` had no neighbours.
+    fn apply_rule_with_rng(&self, curr_symbol: A, rng: &mut dyn RngCore) -> Vec<A> {
+        self.system.apply_rule(None, curr_symbol, None, rng)
+    }
+
+    fn seed(&self) -> u64 {
+        self.system.seed()
+    }
+
+    fn is_context_free(&self) -> bool {
+        false
+    }
+
+    fn successors(&self, last_iteration: &[A], rng: &mut dyn RngCore) -> Vec<Vec<A>> {
+        neighbours(&self.system, last_iteration)
+            .into_iter()
+            .zip(last_iteration.iter().cloned())
+            .map(|((left, right), symbol)| {
+                self.system.apply_rule(
+                    left.map(|i| &last_iteration[i]),
+                    symbol,
+                    right.map(|i| &last_iteration[i]),
+                    rng,
+                )
+            })
+            .collect()
+    }
+}
+
+impl<L, A> LindenmayerSystemDrawingParameters<A> for LindenmayerSystemContextAdapter<L, A>
+where
+    L: ContextSensitiveLindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+    A: Clone,
+{
+    fn iteration(&self) -> u64 {
+        self.system.iteration()
+    }
+
+    fn initial_pos(&self) -> Point {
+        self.system.initial_pos()
+    }
+
+    fn initial_rad(&self) -> f64 {
+        self.system.initial_rad()
+    }
+
+    fn interpret_symbol(&self, symbol: A) -> Option<TurtleStep> {
+        self.system.interpret_symbol(symbol)
+    }
+
+    fn symbol_kind(&self, symbol: &A) -> u64 {
+        self.system.symbol_kind(symbol)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::{
+        ExpansionStrategy, LindenmayerSystemCachingDecorator, LindenmayerSystemTurtleProgram,
+    };
+    use crate::turtle::TurtleProgram;
+
+    /// A signal `S` that moves up a plant, one segment `F` per iteration, into every branch.
+    #[derive(Clone)]
+    struct Signal;
+
+    impl ContextSensitiveLindenmayerSystem<char> for Signal {
+        fn initial(&self) -> Vec<char> {
+            "SF+F[-F+F[F]F]F[F]F".chars().collect()
+        }
+
+        fn apply_rule(
+            &self,
+            left: Option<&char>,
+            curr_symbol: char,
+            _right: Option<&char>,
+            _rng: &mut dyn RngCore,
+        ) -> Vec<char> {
+            match (left, curr_symbol) {
+                (Some('S'), 'F') => vec!['S'],
+                (_, 'S') => vec!['F'],
+                (_, symbol) => vec![symbol],
+            }
+        }
+
+        fn bracket(&self, symbol: &char) -> Option<Bracket> {
+            match symbol {
+                '[' => Some(Bracket::Open),
+                ']' => Some(Bracket::Close),
+                _ => None,
+            }
+        }
+
+        fn ignore_in_context(&self, symbol: &char) -> bool {
+            matches!(symbol, '+' | '-')
+        }
+    }
+
+    impl LindenmayerSystemDrawingParameters<char> for Signal {
+        fn iteration(&self) -> u64 {
+            3
+        }
+
+        fn interpret_symbol(&self, symbol: char) -> Option<TurtleStep> {
+            match symbol {
+                'F' | 'S' => Some(TurtleStep::Forward(0.1)),
+                '+' => Some(TurtleStep::TurnRad(0.5)),
+                '-' => Some(TurtleStep::TurnRad(-0.5)),
+                '[' => Some(TurtleStep::Push),
+                ']' => Some(TurtleStep::Pop),
+                _ => None,
+            }
+        }
+    }
+
+    /// Replaces `B` with `X` only between an `A` and a `C`.
+    #[derive(Clone)]
+    struct TwoSided;
+
+    impl ContextSensitiveLindenmayerSystem<char> for TwoSided {
+        fn initial(&self) -> Vec<char> {
+            "ABC AB BC ABBC".chars().collect()
+        }
+
+        fn apply_rule(
+            &self,
+            left: Option<&char>,
+            curr_symbol: char,
+            right: Option<&char>,
+            _rng: &mut dyn RngCore,
+        ) -> Vec<char> {
+            match (left, curr_symbol, right) {
+                (Some('A'), 'B', Some('C')) => vec!['X'],
+                (_, symbol, _) => vec![symbol],
+            }
+        }
+    }
+
+    fn generate<L: LindenmayerSystem<char>>(system: &L, iteration: u64) -> String {
+        system.generate(iteration).into_iter().collect()
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let sequence: Vec<char> = "A+[B]C[D[E]]F".chars().collect();
+        let names = |(left, right): (Option<usize>, Option<usize>)| {
+            (left.map(|i| sequence[i]), right.map(|i| sequence[i]))
+        };
+        let found: Vec<_> = neighbours(&Signal, &sequence)
+            .into_iter()
+            .map(names)
+            .collect();
+        assert_eq!(found[0], (None, Some('C')), "A skips the turn and branch");
+        assert_eq!(found[3], (Some('A'), None), "B starts a branch from A");
+        assert_eq!(found[5], (Some('A'), Some('F')), "C skips two branches");
+        assert_eq!(found[7], (Some('C'), None), "D grows from C");
+        assert_eq!(found[9], (Some('D'), None), "E grows from D");
+        assert_eq!(found[12], (Some('C'), None), "F is last");
+    }
+
+    #[test]
+    fn test_two_sided() {
+        let system = LindenmayerSystemContextAdapter::new(TwoSided);
+        assert_eq!(generate(&system, 1), "AXC AB BC ABBC");
+    }
+
+    #[test]
+    fn test_signal() {
+        let system = LindenmayerSystemContextAdapter::new(Signal);
+        assert_eq!(generate(&system, 0), "SF+F[-F+F[F]F]F[F]F");
+        assert_eq!(generate(&system, 1), "FS+F[-F+F[F]F]F[F]F");
+        assert_eq!(generate(&system, 2), "FF+S[-F+F[F]F]F[F]F");
+        assert_eq!(generate(&system, 3), "FF+F[-S+F[F]F]S[F]F");
+        assert_eq!(generate(&system, 4), "FF+F[-F+S[F]F]F[S]S");
+
+        let cached = LindenmayerSystemCachingDecorator::new(system.clone());
+        for iteration in [4, 2, 6, 5] {
+            assert_eq!(generate(&cached, iteration), generate(&system, iteration));
+        }
+    }
+
+    #[test]
+    fn test_segment_info() {
+        let program =
+            LindenmayerSystemTurtleProgram::new(LindenmayerSystemContextAdapter::new(Signal));
+        let forwards = program
+            .turtle_program_iter()
+            .filter(|step| matches!(step, TurtleStep::Forward(_)))
+            .count();
+        assert_eq!(program.segment_info().unwrap().count(), forwards);
+    }
+
+    #[test]
+    fn test_streaming_falls_back() {
+        let adapter = LindenmayerSystemContextAdapter::new(Signal);
+        assert!(!adapter.is_context_free());
+        let cached = LindenmayerSystemTurtleProgram::new(adapter.clone());
+        let streaming = LindenmayerSystemTurtleProgram::new(adapter)
+            .with_expansion(ExpansionStrategy::Streaming);
+        assert_eq!(
+            streaming.turtle_program_iter().collect::<Vec<_>>(),
+            cached.turtle_program_iter().collect::<Vec<_>>()
+        );
+    }
+}
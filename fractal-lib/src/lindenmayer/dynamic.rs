@@ -0,0 +1,621 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! L-systems that are defined at runtime by a grammar file, instead of at compile time by an enum.
+//!
+//! Grammars are written in TOML. Every symbol is a single character:
+//!
+//! ```toml
+//! name = "Koch snowflake"  # optional
+//! axiom = "F++F++F"
+//! angle = 60               # how far + and - turn, in degrees
+//! length = 0.5             # optional, how far F moves at iteration 0, defaults to 1
+//! scale = 0.3333333333     # optional, multiplies the length with each iteration, defaults to 1
+//! start = [0.0, 0.0]       # optional, where the turtle starts, defaults to the origin
+//! heading = 0              # optional, the turtle's initial direction in degrees, defaults to 0
+//!
+//! [rules]                  # symbols without a rule are left as they are
+//! F = "F-F++F-F"
+//!
+//! [actions]                # optional, overrides what symbols do
+//! X = "nothing"
+//! ```
+//!
+//! A rule can also list several successors, each with a weight (defaulting to 1). Every time the
+//! symbol is replaced, one of them is chosen at random, with a probability proportional to its
+//! weight. The choices only depend on the grammar's `seed` (defaulting to 0), so a grammar always
+//! grows the same way until its seed is changed:
+//!
+//! ```toml
+//! seed = 42
+//!
+//! [rules]
+//! F = [
+//!     { successor = "F[+F]F[-F]F", weight = 1 },
+//!     { successor = "F[+F]F", weight = 2 },
+//!     { successor = "F[-F]F" },
+//! ]
+//! ```
+//!
+//! By default, `F` and `G` move forward, `+` turns left, `-` turns right, `|` turns around, `[`
+//! starts a branch, `]` ends it, `!` resets the pen width, and every other symbol does nothing.
+//! The `[actions]` table can give any symbol one of the actions in `SymbolAction`.
+
+use super::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::geometry::{deg2rad, Point};
+use crate::turtle::{TurtleStep, DEFAULT_PEN_WIDTH};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::f64::consts::PI;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// What the turtle does when it reaches a symbol.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SymbolAction {
+    /// Move forward, drawing a line.
+    Forward,
+    /// Turn counter-clockwise by the grammar's angle.
+    Left,
+    /// Turn clockwise by the grammar's angle.
+    Right,
+    /// Turn by 180 degrees.
+    TurnAround,
+    /// Start a branch.
+    Push,
+    /// End a branch, returning to where it started.
+    Pop,
+    /// Set the width of the pen. Without a parameter (see `parametric`), this resets it.
+    PenWidth,
+    /// Lift the pen, so that moving forward does not draw until the pen is put down again.
+    PenUp,
+    /// Put the pen back down.
+    PenDown,
+    /// Only controls how the system grows.
+    Nothing,
+}
+
+impl SymbolAction {
+    /// What a symbol does when the grammar does not say otherwise.
+    pub(super) fn default_for(symbol: char) -> SymbolAction {
+        match symbol {
+            'F' | 'G' => SymbolAction::Forward,
+            '+' => SymbolAction::Left,
+            '-' => SymbolAction::Right,
+            '|' => SymbolAction::TurnAround,
+            '[' => SymbolAction::Push,
+            ']' => SymbolAction::Pop,
+            '!' => SymbolAction::PenWidth,
+            _ => SymbolAction::Nothing,
+        }
+    }
+}
+
+/// The successors that a symbol can be replaced with.
+#[derive(Clone, Debug)]
+struct Rule {
+    successors: Vec<Vec<char>>,
+    /// Chooses between successors, if there is more than one.
+    weights: Option<WeightedIndex<f64>>,
+}
+
+/// An L-system whose symbols, rules, and drawing parameters are read from a grammar.
+#[derive(Clone, Debug)]
+pub struct DynamicLSystem {
+    iterations: u64,
+    seed: u64,
+    name: String,
+    axiom: Vec<char>,
+    rules: HashMap<char, Rule>,
+    actions: HashMap<char, SymbolAction>,
+    /// In radians.
+    angle: f64,
+    length: f64,
+    scale: f64,
+    start: Point,
+    /// In radians.
+    heading: f64,
+}
+
+impl DynamicLSystem {
+    /// Parses a grammar (see the module documentation), to be drawn at `iterations`.
+    pub fn parse(text: &str, iterations: u64) -> Result<DynamicLSystem, DynamicLSystemError> {
+        let grammar: TomlGrammar = parse_toml(text)?;
+        let error = |message: String| DynamicLSystemError::Parse {
+            line: None,
+            message,
+        };
+
+        let axiom: Vec<char> = grammar.axiom.chars().collect();
+        if axiom.is_empty() {
+            return Err(error("the axiom is empty".to_string()));
+        }
+        let mut rules = HashMap::new();
+        for (symbol, rule) in grammar.rules {
+            let successors = match rule {
+                TomlRule::Successor(successor) => vec![TomlSuccessor {
+                    successor,
+                    weight: 1.0,
+                }],
+                TomlRule::Stochastic(successors) => successors,
+            };
+            let weights = match successors.len() {
+                0 => {
+                    return Err(error(format!(
+                        "the rule for {:?} has no successors",
+                        symbol
+                    )))
+                }
+                1 => None,
+                _ => Some(
+                    WeightedIndex::new(successors.iter().map(|s| s.weight)).map_err(|e| {
+                        error(format!(
+                            "the rule for {:?} has invalid weights: {}",
+                            symbol, e
+                        ))
+                    })?,
+                ),
+            };
+            let rule = Rule {
+                successors: successors
+                    .into_iter()
+                    .map(|s| s.successor.chars().collect())
+                    .collect(),
+                weights,
+            };
+            rules.insert(single_symbol(&symbol)?, rule);
+        }
+        let actions = parse_actions(grammar.actions)?;
+        check_finite(&[
+            ("angle", grammar.angle),
+            ("length", grammar.length),
+            ("scale", grammar.scale),
+            ("heading", grammar.heading),
+            ("start", grammar.start[0]),
+            ("start", grammar.start[1]),
+        ])?;
+
+        Ok(DynamicLSystem {
+            iterations,
+            seed: grammar.seed,
+            name: grammar.name,
+            axiom,
+            rules,
+            actions,
+            angle: deg2rad(grammar.angle),
+            length: grammar.length,
+            scale: grammar.scale,
+            start: Point {
+                x: grammar.start[0],
+                y: grammar.start[1],
+            },
+            heading: deg2rad(grammar.heading),
+        })
+    }
+
+    /// Loads a grammar file, to be drawn at `iterations`.
+    pub fn load(path: &Path, iterations: u64) -> Result<DynamicLSystem, DynamicLSystemError> {
+        let text = fs::read_to_string(path).map_err(|source| DynamicLSystemError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        DynamicLSystem::parse(&text, iterations)
+    }
+
+    /// Chooses between successors with `seed` instead of the grammar's seed.
+    pub fn with_seed(self, seed: u64) -> DynamicLSystem {
+        DynamicLSystem { seed, ..self }
+    }
+
+    /// The grammar's name, which is empty if it does not have one.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How far `+` and `-` turn, in degrees.
+    pub fn angle(&self) -> f64 {
+        self.angle.to_degrees()
+    }
+
+    /// Where the turtle starts.
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    /// The turtle's initial direction, in degrees.
+    pub fn heading(&self) -> f64 {
+        self.heading.to_degrees()
+    }
+
+    /// What the turtle does when it reaches `symbol`.
+    pub fn action(&self, symbol: char) -> SymbolAction {
+        match self.actions.get(&symbol) {
+            Some(action) => *action,
+            None => SymbolAction::default_for(symbol),
+        }
+    }
+
+    /// The length scaled once for each iteration.
+    fn distance_forward(&self) -> f64 {
+        self.length * self.scale.powf(self.iterations as f64)
+    }
+}
+
+/// Deserializes a grammar, reporting which line any syntax error is on.
+pub(super) fn parse_toml<T: DeserializeOwned>(text: &str) -> Result<T, DynamicLSystemError> {
+    toml::from_str(text).map_err(|e| DynamicLSystemError::Parse {
+        line: e
+            .span()
+            .map(|span| text[..span.start].matches('\n').count() + 1),
+        message: e.message().to_string(),
+    })
+}
+
+/// Reads the `[actions]` table.
+pub(super) fn parse_actions(
+    actions: BTreeMap<String, String>,
+) -> Result<HashMap<char, SymbolAction>, DynamicLSystemError> {
+    let mut parsed = HashMap::new();
+    for (symbol, action) in actions {
+        let action = SymbolAction::from_str(&action).map_err(|_| DynamicLSystemError::Parse {
+            line: None,
+            message: format!(
+                "unknown action {:?} for {:?}, expected one of: {}",
+                action,
+                symbol,
+                <SymbolAction as strum::VariantNames>::VARIANTS.join(", ")
+            ),
+        })?;
+        parsed.insert(single_symbol(&symbol)?, action);
+    }
+    Ok(parsed)
+}
+
+/// Rejects infinite and NaN drawing parameters, which are named by their keys.
+pub(super) fn check_finite(values: &[(&str, f64)]) -> Result<(), DynamicLSystemError> {
+    match values.iter().find(|(_, value)| !value.is_finite()) {
+        Some((key, _)) => Err(DynamicLSystemError::Parse {
+            line: None,
+            message: format!("{} must be a finite number", key),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Rule and action keys each name one symbol.
+pub(super) fn single_symbol(key: &str) -> Result<char, DynamicLSystemError> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(symbol), None) => Ok(symbol),
+        _ => Err(DynamicLSystemError::Parse {
+            line: None,
+            message: format!("{:?} is not a single symbol", key),
+        }),
+    }
+}
+
+impl LindenmayerSystem<char> for DynamicLSystem {
+    fn initial(&self) -> Vec<char> {
+        self.axiom.clone()
+    }
+
+    /// Rules with several successors are replaced with the first of them.
+    fn apply_rule(&self, symbol: char) -> Vec<char> {
+        match self.rules.get(&symbol) {
+            Some(rule) => rule.successors[0].clone(),
+            None => vec![symbol],
+        }
+    }
+
+    fn apply_rule_with_rng(&self, symbol: char, rng: &mut dyn RngCore) -> Vec<char> {
+        match self.rules.get(&symbol) {
+            Some(Rule {
+                successors,
+                weights: Some(weights),
+            }) => successors[weights.sample(rng)].clone(),
+            _ => self.apply_rule(symbol),
+        }
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<char> for DynamicLSystem {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn initial_pos(&self) -> Point {
+        self.start
+    }
+
+    fn initial_rad(&self) -> f64 {
+        self.heading
+    }
+
+    fn interpret_symbol(&self, symbol: char) -> Option<TurtleStep> {
+        match self.action(symbol) {
+            SymbolAction::Forward => Some(TurtleStep::Forward(self.distance_forward())),
+            SymbolAction::Left => Some(TurtleStep::TurnRad(self.angle)),
+            SymbolAction::Right => Some(TurtleStep::TurnRad(-self.angle)),
+            SymbolAction::TurnAround => Some(TurtleStep::TurnRad(PI)),
+            SymbolAction::Push => Some(TurtleStep::Push),
+            SymbolAction::Pop => Some(TurtleStep::Pop),
+            SymbolAction::PenWidth => Some(TurtleStep::SetPenWidth(DEFAULT_PEN_WIDTH)),
+            SymbolAction::PenUp => Some(TurtleStep::Up),
+            SymbolAction::PenDown => Some(TurtleStep::Down),
+            SymbolAction::Nothing => None,
+        }
+    }
+
+    fn symbol_kind(&self, symbol: &char) -> u64 {
+        u64::from(*symbol)
+    }
+}
+
+/// Everything that can go wrong while loading a grammar.
+#[derive(Debug)]
+pub enum DynamicLSystemError {
+    /// The file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The grammar is malformed. `line` starts at 1.
+    Parse {
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl fmt::Display for DynamicLSystemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DynamicLSystemError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            DynamicLSystemError::Parse {
+                line: Some(line),
+                message,
+            } => write!(f, "line {}: {}", line, message),
+            DynamicLSystemError::Parse {
+                line: None,
+                message,
+            } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for DynamicLSystemError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DynamicLSystemError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlGrammar {
+    #[serde(default)]
+    name: String,
+    axiom: String,
+    angle: f64,
+    #[serde(default = "one")]
+    length: f64,
+    #[serde(default = "one")]
+    scale: f64,
+    #[serde(default)]
+    start: [f64; 2],
+    #[serde(default)]
+    heading: f64,
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    rules: BTreeMap<String, TomlRule>,
+    #[serde(default)]
+    actions: BTreeMap<String, String>,
+}
+
+/// Either a single successor, or several to choose between.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TomlRule {
+    Successor(String),
+    Stochastic(Vec<TomlSuccessor>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlSuccessor {
+    successor: String,
+    #[serde(default = "one")]
+    weight: f64,
+}
+
+pub(super) fn one() -> f64 {
+    1.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::kochcurve::KochCurve;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::{polyline, TurtleProgram};
+
+    const KOCH: &str = r#"
+        name = "Koch snowflake"
+        axiom = "F++F++F"
+        angle = 60
+        length = 0.5
+        scale = 0.3333333333333333
+
+        [rules]
+        F = "F-F++F-F"
+    "#;
+
+    #[test]
+    fn test_koch() {
+        for iteration in 0..4 {
+            let dynamic = DynamicLSystem::parse(KOCH, iteration).unwrap();
+            assert_eq!(dynamic.name(), "Koch snowflake");
+            let expected = polyline::record(&LindenmayerSystemTurtleProgram::new(KochCurve::new(
+                iteration,
+            )));
+            let actual = polyline::record(&LindenmayerSystemTurtleProgram::new(dynamic));
+            assert_eq!(expected.len(), actual.len());
+            for (expected, actual) in expected.concat().iter().zip(actual.concat()) {
+                assert_point_eq!(*expected, actual, 0.000000001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_actions() {
+        let plant = DynamicLSystem::parse(
+            r#"
+            axiom = "X"
+            angle = 25
+            start = [-0.1, -0.72]
+            heading = 65
+            rules = { X = "F+[[X]-X]-F[-FX]+X", F = "FF" }
+            actions = { X = "push", F = "nothing" }
+            "#,
+            1,
+        )
+        .unwrap();
+        assert_eq!(plant.generate(1).len(), 18);
+        assert_eq!(plant.generate(2)[..2], ['F', 'F']);
+        assert_eq!(plant.action('X'), SymbolAction::Push);
+        assert_eq!(plant.action('F'), SymbolAction::Nothing);
+        assert_eq!(plant.action('G'), SymbolAction::Forward);
+        assert_eq!(plant.action('['), SymbolAction::Push);
+        assert_eq!(plant.action('|'), SymbolAction::TurnAround);
+        assert_eq!(plant.action('Y'), SymbolAction::Nothing);
+        assert_point_eq!(
+            plant.initial_pos(),
+            Point { x: -0.1, y: -0.72 },
+            0.000000001
+        );
+        assert_approx_eq!(plant.initial_rad(), deg2rad(65.0), 0.000000001);
+        assert_eq!(
+            plant.interpret_symbol('+'),
+            Some(TurtleStep::TurnRad(deg2rad(25.0)))
+        );
+        assert_eq!(plant.interpret_symbol('Y'), None);
+    }
+
+    const BUSH: &str = r#"
+        axiom = "F"
+        angle = 25
+        heading = 90
+        seed = 3
+
+        [rules]
+        F = [
+            { successor = "F[+F]F[-F]F", weight = 1 },
+            { successor = "F[+F]F", weight = 2 },
+            { successor = "F[-F]F" },
+        ]
+    "#;
+
+    #[test]
+    fn test_stochastic() {
+        let bush = DynamicLSystem::parse(BUSH, 4).unwrap();
+        assert_eq!(bush.seed(), 3);
+        assert_eq!(
+            bush.apply_rule('F'),
+            "F[+F]F[-F]F".chars().collect::<Vec<_>>()
+        );
+        assert_eq!(bush.generate(4), bush.clone().generate(4));
+        let reseeded = bush.clone().with_seed(4);
+        assert_ne!(bush.generate(4), reseeded.generate(4));
+        assert_eq!(
+            bush.generate(4),
+            reseeded.generate_with_seed(4, 3),
+            "the seed is all that changes"
+        );
+
+        let program = LindenmayerSystemTurtleProgram::new(bush);
+        let forwards = program
+            .turtle_program_iter()
+            .filter(|step| matches!(step, TurtleStep::Forward(_)))
+            .count();
+        assert_eq!(program.segment_info().unwrap().count(), forwards);
+    }
+
+    #[test]
+    fn test_segment_symbols() {
+        let system = DynamicLSystem::parse(
+            r#"
+                axiom = "F"
+                angle = 90
+
+                [rules]
+                F = "F+G"
+                G = "F-G"
+            "#,
+            2,
+        )
+        .unwrap();
+        // Iteration 2 is F+G+F-G.
+        let program = LindenmayerSystemTurtleProgram::new(system);
+        let symbols = program
+            .segment_info()
+            .unwrap()
+            .map(|info| info.symbol)
+            .collect::<Vec<usize>>();
+        assert_eq!(symbols, [0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| DynamicLSystem::parse(text, 1).unwrap_err().to_string();
+        assert_eq!(error("axiom = \"\"\nangle = 90"), "the axiom is empty");
+        assert!(error("axiom = \"F\"").contains("missing field `angle`"));
+        assert!(error("axiom = \"F\"\nangle = 90\ncolour = 1").starts_with("line 3: "));
+        assert_eq!(
+            error("axiom = \"F\"\nangle = 90\nrules = { FF = \"F\" }"),
+            "\"FF\" is not a single symbol"
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nangle = 90\nactions = { F = \"jump\" }"),
+            "unknown action \"jump\" for \"F\", expected one of: forward, left, right, \
+             turn-around, push, pop, pen-width, pen-up, pen-down, nothing"
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nangle = nan"),
+            "angle must be a finite number"
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nangle = 90\nrules = { F = [] }"),
+            "the rule for \"F\" has no successors"
+        );
+        assert!(error(
+            "axiom = \"F\"\nangle = 90\nrules = { F = [{ successor = \"F\", weight = -1 }, \
+             { successor = \"FF\" }] }"
+        )
+        .starts_with("the rule for \"F\" has invalid weights"));
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Predicts how an L-system grows, without generating its sequences.
+//!
+//! Replacing every symbol of a context free L-system turns how many of each symbol one iteration
+//! has into how many the next iteration has, by multiplying them with the system's production
+//! matrix. Counting symbols this way takes time that depends on the number of iterations and the
+//! size of the alphabet, instead of on the length of the sequence, which grows exponentially.
+
+use super::{
+    LindenmayerSystem, LindenmayerSystem3dDrawingParameters, LindenmayerSystemDrawingParameters,
+};
+use crate::turtle::TurtleStep;
+use crate::turtle3d::Turtle3dStep;
+use std::error;
+use std::fmt;
+
+/// The most symbols that `ProductionMatrix::new()` discovers before giving up, since parametric
+/// alphabets can be endless.
+pub const MAX_SYMBOLS: usize = 1024;
+
+/// How many times the sequence has grown that `ProductionMatrix::growth_rate()` waits for before
+/// measuring the growth rate, and then how many iterations it measures it over. The window is
+/// divisible by every period up to 6, so that systems whose growth cycles do not skew the rate.
+const GROWTH_RATE_WARMUP: usize = 1000;
+const GROWTH_RATE_WINDOW: usize = 60;
+
+/// How many iterations `ProductionMatrix::counts()` steps through one at a time, before it
+/// switches to repeated squaring.
+const STEPPED_ITERATIONS: u64 = 1024;
+
+/// How many of each symbol an L-system's sequence has, from one iteration to the next.
+///
+/// Stochastic systems are counted as if each symbol were always replaced by its first successor
+/// (see `LindenmayerSystem::apply_rule()`), so their counts are only estimates.
+#[derive(Clone, Debug)]
+pub struct ProductionMatrix<A> {
+    /// Every symbol that the sequence can contain, in the order they were discovered.
+    symbols: Vec<A>,
+    /// For each symbol, the index and number of each symbol that it is replaced with.
+    productions: Vec<Vec<(usize, u128)>>,
+    /// How many of each symbol the initial sequence has.
+    initial: Vec<u128>,
+    /// Which symbols the turtle performs a step for.
+    steps: Vec<bool>,
+    /// Which symbols move the turtle forward.
+    forwards: Vec<bool>,
+}
+
+/// How long an iteration's sequence is. Counts saturate at `u128::MAX`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SequenceSize {
+    /// How many symbols the sequence has.
+    pub length: u128,
+    /// How many of them the turtle performs a step for.
+    pub steps: u128,
+    /// How many of them move the turtle forward, drawing a line segment.
+    pub forwards: u128,
+}
+
+impl<A> ProductionMatrix<A>
+where
+    A: Clone + PartialEq,
+{
+    /// Builds the production matrix of every symbol that can be reached from `system`'s initial
+    /// sequence.
+    pub fn new<L>(system: &L) -> Result<ProductionMatrix<A>, GrowthError>
+    where
+        L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+    {
+        ProductionMatrix::with_steps(system, |symbol| match system.interpret_symbol(symbol) {
+            Some(step) => (true, matches!(step, TurtleStep::Forward(_))),
+            None => (false, false),
+        })
+    }
+
+    /// Builds the production matrix of a 3D L-system, like `new()`.
+    pub fn new_3d<L>(system: &L) -> Result<ProductionMatrix<A>, GrowthError>
+    where
+        L: LindenmayerSystem<A> + LindenmayerSystem3dDrawingParameters<A>,
+    {
+        ProductionMatrix::with_steps(system, |symbol| match system.interpret_symbol(symbol) {
+            Some(step) => (true, matches!(step, Turtle3dStep::Forward(_))),
+            None => (false, false),
+        })
+    }
+
+    /// Builds the production matrix, where `interpret` says whether the turtle performs a step
+    /// for a symbol, and whether that step moves it forward.
+    fn with_steps<L, F>(system: &L, interpret: F) -> Result<ProductionMatrix<A>, GrowthError>
+    where
+        L: LindenmayerSystem<A>,
+        F: Fn(A) -> (bool, bool),
+    {
+        if !system.is_context_free() {
+            return Err(GrowthError::ContextSensitive);
+        }
+        let mut symbols = vec![];
+        let mut initial = vec![];
+        for symbol in system.initial() {
+            let index = index_of(&mut symbols, symbol)?;
+            initial.resize(symbols.len(), 0);
+            initial[index] += 1;
+        }
+        // replacing each symbol may discover more symbols, until every symbol has a row
+        let mut productions: Vec<Vec<(usize, u128)>> = vec![];
+        while productions.len() < symbols.len() {
+            let symbol = symbols[productions.len()].clone();
+            let mut production: Vec<(usize, u128)> = vec![];
+            for successor in system.apply_rule(symbol) {
+                let index = index_of(&mut symbols, successor)?;
+                match production.iter_mut().find(|(i, _)| *i == index) {
+                    Some((_, count)) => *count += 1,
+                    None => production.push((index, 1)),
+                }
+            }
+            productions.push(production);
+        }
+        initial.resize(symbols.len(), 0);
+
+        let (steps, forwards) = symbols
+            .iter()
+            .map(|symbol| interpret(symbol.clone()))
+            .unzip();
+        Ok(ProductionMatrix {
+            symbols,
+            productions,
+            initial,
+            steps,
+            forwards,
+        })
+    }
+}
+
+impl<A> ProductionMatrix<A> {
+    /// Every symbol that the sequence can contain.
+    pub fn symbols(&self) -> &[A] {
+        &self.symbols
+    }
+
+    /// How many of each symbol (in the order of `symbols()`) the sequence has at `iteration`.
+    ///
+    /// Up to `STEPPED_ITERATIONS`, the counts are replaced one iteration at a time. Later
+    /// iterations raise the production matrix to the power of `iteration` by repeated squaring
+    /// instead, which takes time proportional to the number of bits in `iteration`.
+    pub fn counts(&self, iteration: u64) -> Vec<u128> {
+        if iteration > STEPPED_ITERATIONS {
+            return self.counts_by_squaring(iteration);
+        }
+        let mut counts = self.initial.clone();
+        for _ in 0..iteration {
+            let next = self.next_counts(&counts);
+            if next == counts {
+                // nothing will change any more, including once every count has saturated
+                break;
+            }
+            counts = next;
+        }
+        counts
+    }
+
+    fn counts_by_squaring(&self, mut iteration: u64) -> Vec<u128> {
+        let mut power = self.dense_matrix();
+        let mut counts = self.initial.clone();
+        while iteration > 0 {
+            if iteration & 1 == 1 {
+                counts = (0..counts.len())
+                    .map(|to| {
+                        counts.iter().zip(&power).fold(0u128, |sum, (count, row)| {
+                            sum.saturating_add(count.saturating_mul(row[to]))
+                        })
+                    })
+                    .collect();
+            }
+            iteration >>= 1;
+            if iteration > 0 {
+                power = multiply(&power, &power);
+            }
+        }
+        counts
+    }
+
+    /// The production matrix with a row and a column for every symbol.
+    fn dense_matrix(&self) -> Vec<Vec<u128>> {
+        self.productions
+            .iter()
+            .map(|production| {
+                let mut row = vec![0; self.symbols.len()];
+                for (index, successors) in production {
+                    row[*index] = *successors;
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// How long the sequence is at `iteration`.
+    pub fn size(&self, iteration: u64) -> SequenceSize {
+        let counts = self.counts(iteration);
+        let sum = |only: Option<&[bool]>| {
+            counts
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| only.is_none_or(|only| only[*index]))
+                .fold(0u128, |sum, (_, count)| sum.saturating_add(*count))
+        };
+        SequenceSize {
+            length: sum(None),
+            steps: sum(Some(&self.steps)),
+            forwards: sum(Some(&self.forwards)),
+        }
+    }
+
+    /// Roughly how many times longer the sequence gets with each iteration, in the long run: the
+    /// largest eigenvalue of the production matrix among the symbols that the sequence contains.
+    /// This is 1 for sequences that grow slower than exponentially, and 0 for ones that vanish.
+    ///
+    /// The rate is measured by counting (with floating point numbers) how much the sequence grows
+    /// over several iterations, after it has grown for a while.
+    pub fn growth_rate(&self) -> f64 {
+        let mut counts: Vec<f64> = self.initial.iter().map(|count| *count as f64).collect();
+        let mut log_growth = 0.0;
+        let mut log_growth_at_warmup = 0.0;
+        for iteration in 0..GROWTH_RATE_WARMUP + GROWTH_RATE_WINDOW {
+            if iteration == GROWTH_RATE_WARMUP {
+                log_growth_at_warmup = log_growth;
+            }
+            let mut next = vec![0.0; counts.len()];
+            for (production, count) in self.productions.iter().zip(&counts) {
+                for (index, successors) in production {
+                    next[*index] += count * *successors as f64;
+                }
+            }
+            let (length, next_length) = (counts.iter().sum::<f64>(), next.iter().sum::<f64>());
+            if next_length == 0.0 {
+                return 0.0;
+            }
+            log_growth += (next_length / length).ln();
+            // keep the counts from overflowing, since only their proportions matter
+            counts = next.into_iter().map(|count| count / next_length).collect();
+        }
+        ((log_growth - log_growth_at_warmup) / GROWTH_RATE_WINDOW as f64).exp()
+    }
+
+    fn next_counts(&self, counts: &[u128]) -> Vec<u128> {
+        let mut next = vec![0u128; counts.len()];
+        for (production, count) in self.productions.iter().zip(counts) {
+            for (index, successors) in production {
+                next[*index] = next[*index].saturating_add(count.saturating_mul(*successors));
+            }
+        }
+        next
+    }
+}
+
+/// Multiplies two square matrices, saturating at `u128::MAX`.
+fn multiply(a: &[Vec<u128>], b: &[Vec<u128>]) -> Vec<Vec<u128>> {
+    a.iter()
+        .map(|row| {
+            let mut product = vec![0u128; b.len()];
+            for (k, a_k) in row.iter().enumerate().filter(|(_, a_k)| **a_k != 0) {
+                for (to, b_k) in b[k].iter().enumerate().filter(|(_, b_k)| **b_k != 0) {
+                    product[to] = product[to].saturating_add(a_k.saturating_mul(*b_k));
+                }
+            }
+            product
+        })
+        .collect()
+}
+
+/// Returns the index of `symbol` in `symbols`, adding it if it is not there yet.
+fn index_of<A: PartialEq>(symbols: &mut Vec<A>, symbol: A) -> Result<usize, GrowthError> {
+    if let Some(index) = symbols.iter().position(|s| *s == symbol) {
+        return Ok(index);
+    }
+    if symbols.len() == MAX_SYMBOLS {
+        return Err(GrowthError::TooManySymbols);
+    }
+    symbols.push(symbol);
+    Ok(symbols.len() - 1)
+}
+
+/// Why an L-system's growth cannot be predicted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GrowthError {
+    /// Its symbols are not replaced independently of each other (see
+    /// `LindenmayerSystem::is_context_free()`).
+    ContextSensitive,
+    /// Its sequences can contain more than `MAX_SYMBOLS` different symbols.
+    TooManySymbols,
+}
+
+impl fmt::Display for GrowthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrowthError::ContextSensitive => {
+                write!(f, "context-sensitive L-systems have no production matrix")
+            }
+            GrowthError::TooManySymbols => write!(
+                f,
+                "the L-system has more than {} different symbols",
+                MAX_SYMBOLS
+            ),
+        }
+    }
+}
+
+impl error::Error for GrowthError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::cesarotri::CesaroTriFractal;
+    use crate::curves::fractalplant::FractalPlant;
+    use crate::curves::kochcurve::KochCurve;
+    use crate::curves::terdragon::TerdragonFractal;
+    use crate::lindenmayer::context::{
+        ContextSensitiveLindenmayerSystem, LindenmayerSystemContextAdapter,
+    };
+    use crate::lindenmayer::dynamic::DynamicLSystem;
+    use crate::lindenmayer::parametric::ParametricLSystem;
+
+    /// Checks the predicted sizes against the generated sequences.
+    fn assert_predicts<L, A>(system: &L, iterations: u64)
+    where
+        L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+        A: Clone + PartialEq,
+    {
+        let matrix = ProductionMatrix::new(system).unwrap();
+        for iteration in 0..iterations {
+            let sequence = system.generate(iteration);
+            let steps: Vec<TurtleStep> = sequence
+                .iter()
+                .filter_map(|symbol| system.interpret_symbol(symbol.clone()))
+                .collect();
+            let forwards = steps
+                .iter()
+                .filter(|step| matches!(step, TurtleStep::Forward(_)))
+                .count();
+            assert_eq!(
+                matrix.size(iteration),
+                SequenceSize {
+                    length: sequence.len() as u128,
+                    steps: steps.len() as u128,
+                    forwards: forwards as u128,
+                },
+                "iteration {}",
+                iteration
+            );
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_predicts(&KochCurve::new(0), 6);
+        assert_predicts(&TerdragonFractal::new(0), 6);
+        assert_predicts(&CesaroTriFractal::new(0), 6);
+        assert_predicts(&FractalPlant::new(0), 5);
+        let koch = "axiom = \"F++F++F\"\nangle = 60\nrules = { F = \"F-F++F-F\" }";
+        assert_predicts(&DynamicLSystem::parse(koch, 0).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_large_iterations() {
+        let matrix = ProductionMatrix::new(&TerdragonFractal::new(0)).unwrap();
+        assert_eq!(matrix.size(20).forwards, 3u128.pow(20));
+        assert_eq!(matrix.size(80).forwards, 3u128.pow(80));
+        assert_eq!(matrix.size(81).forwards, u128::MAX, "3^81 saturates");
+        assert_eq!(matrix.size(u64::MAX).forwards, u128::MAX);
+
+        // grows by one symbol each iteration, so it never saturates or stops changing
+        let grammar = "axiom = \"A\"\nangle = 90\nrules = { A = \"AF\" }";
+        let matrix = ProductionMatrix::new(&DynamicLSystem::parse(grammar, 0).unwrap()).unwrap();
+        assert_eq!(matrix.size(STEPPED_ITERATIONS).forwards, 1024);
+        assert_eq!(matrix.size(STEPPED_ITERATIONS + 1).forwards, 1025);
+        assert_eq!(matrix.size(u64::MAX).forwards, u64::MAX as u128);
+        assert_eq!(matrix.size(u64::MAX).length, u64::MAX as u128 + 1);
+    }
+
+    fn dynamic_rate(rules: &str) -> f64 {
+        let grammar = format!("axiom = \"A\"\nangle = 90\nrules = {{ {} }}", rules);
+        ProductionMatrix::new(&DynamicLSystem::parse(&grammar, 0).unwrap())
+            .unwrap()
+            .growth_rate()
+    }
+
+    #[test]
+    fn test_growth_rate() {
+        let koch = ProductionMatrix::new(&KochCurve::new(0)).unwrap();
+        assert_approx_eq!(koch.growth_rate(), 4.0, 0.000001);
+        let terdragon = ProductionMatrix::new(&TerdragonFractal::new(0)).unwrap();
+        assert_approx_eq!(terdragon.growth_rate(), 3.0, 0.000001);
+        // grows by one symbol each iteration
+        assert_approx_eq!(dynamic_rate("A = \"AB\""), 1.0, 0.001);
+        // doubles every two iterations
+        assert_approx_eq!(dynamic_rate("A = \"B\", B = \"AA\""), 2f64.sqrt(), 0.000001);
+        assert_eq!(dynamic_rate("A = \"\""), 0.0);
+    }
+
+    #[derive(Clone)]
+    struct Neighbourly;
+
+    impl ContextSensitiveLindenmayerSystem<char> for Neighbourly {
+        fn initial(&self) -> Vec<char> {
+            vec!['A']
+        }
+
+        fn apply_rule(
+            &self,
+            _left: Option<&char>,
+            symbol: char,
+            _right: Option<&char>,
+            _rng: &mut dyn rand::RngCore,
+        ) -> Vec<char> {
+            vec![symbol]
+        }
+    }
+
+    impl LindenmayerSystemDrawingParameters<char> for Neighbourly {
+        fn iteration(&self) -> u64 {
+            0
+        }
+
+        fn interpret_symbol(&self, _symbol: char) -> Option<TurtleStep> {
+            Some(TurtleStep::Forward(1.0))
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            ProductionMatrix::new(&LindenmayerSystemContextAdapter::new(Neighbourly)).unwrap_err(),
+            GrowthError::ContextSensitive
+        );
+        let endless =
+            "parametric = true\naxiom = \"F(1)\"\nangle = 90\nrules = [\"F(x) -> F(x+1)\"]";
+        assert_eq!(
+            ProductionMatrix::new(&ParametricLSystem::parse(endless, 0).unwrap()).unwrap_err(),
+            GrowthError::TooManySymbols
+        );
+        let finite = "parametric = true\naxiom = \"F(1)\"\nangle = 90\n\
+                      rules = [\"F(x) : x < 5 -> F(x+1)F(x+1)\"]";
+        assert_predicts(&ParametricLSystem::parse(finite, 0).unwrap(), 8);
+    }
+}
@@ -0,0 +1,1363 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lindenmayer systems are a methodology for representing various kinds of iterated curves or
+//! other constructions. A given system has an alphabet of symbols, an initial string, and one or
+//! more rules for transforming that string into the next iteration/generation.
+//!
+//! The resulting string at a given iteration or generation can represent a sequence of commands
+//! that some representation process, such as a turtle drawing program, can then use to draw a
+//! curve/fractal/plant (which is what this implementation provides).
+//!
+//! Most systems are defined in Rust, with an enum as their alphabet (see `curves`), but `dynamic`
+//! reads them from a grammar file at runtime instead, and `parametric` reads grammars whose symbols
+//! carry numeric parameters. Systems whose rules depend on the symbols around the one being
+//! replaced implement `context::ContextSensitiveLindenmayerSystem`. `presets` is a library of
+//! ready-made grammars.
+
+pub mod context;
+pub mod dynamic;
+pub mod growth;
+pub mod parametric;
+pub mod presets;
+
+use crate::geometry::{Point, Point3};
+use crate::turtle::*;
+use crate::turtle3d::{Frame, Turtle3dProgram, Turtle3dStep};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Represents a particular Lindenmayer system. It requires an alphabet (represented as an enum),
+/// an initial sequence ("string"), and one or more rules that transform the sequence with each
+/// iteration/generation.
+///
+/// Under many circumstances, these methods will not actually use &self. However, having a &self
+/// allows for more flexibility, such as creating caching objects or creating L-systems that can be
+/// defined at runtime instead of at compile time.
+pub trait LindenmayerSystem<Alphabet: Clone> {
+    /// Should return the initial Lindenmayer system string (iteration 0).
+    fn initial(&self) -> Vec<Alphabet>;
+
+    /// Apply Lindenmayer system rules to a given character.
+    ///
+    /// A common implementation approach would be to use `match` with the Alphabet. For example:
+    ///
+    /// ```
+    /// use fractal_lib::lindenmayer::LindenmayerSystem;
+    ///
+    /// /// Our Alphbet
+    /// #[derive(Clone)]
+    /// enum SomeAlphabet { A, B, C, Foo }
+    ///
+    /// struct SomeLSystem;
+    ///
+    /// impl LindenmayerSystem<SomeAlphabet> for SomeLSystem {
+    ///     fn initial(&self) -> Vec<SomeAlphabet> {
+    ///         vec![SomeAlphabet::A, SomeAlphabet::B, SomeAlphabet::C]
+    ///     }
+    ///
+    ///     fn apply_rule(&self, l: SomeAlphabet) -> Vec<SomeAlphabet> {
+    ///         match l {
+    ///             SomeAlphabet::A => vec![SomeAlphabet::A, SomeAlphabet::Foo,
+    ///             SomeAlphabet::B, SomeAlphabet::C],
+    ///             SomeAlphabet::B => vec![SomeAlphabet::Foo],
+    ///             SomeAlphabet::C => vec![SomeAlphabet::Foo, SomeAlphabet::B],
+    ///             SomeAlphabet::Foo => vec![SomeAlphabet::Foo],
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn apply_rule(&self, curr_symbol: Alphabet) -> Vec<Alphabet>;
+
+    /// Apply Lindenmayer system rules to a given character, using `rng` to choose between
+    /// successors when a symbol has more than one (a stochastic L-system).
+    ///
+    /// Defaults to `apply_rule()`, which is all that deterministic L-systems need. Stochastic
+    /// L-systems should override this, and have `apply_rule()` return the first successor.
+    fn apply_rule_with_rng(&self, curr_symbol: Alphabet, _rng: &mut dyn RngCore) -> Vec<Alphabet> {
+        self.apply_rule(curr_symbol)
+    }
+
+    /// The seed that `generate()` chooses between successors with. Defaults to 0, which makes no
+    /// difference to deterministic L-systems.
+    fn seed(&self) -> u64 {
+        0
+    }
+
+    /// Applies the rules to every symbol of `last_iteration`, in order, using `rng` to choose
+    /// between successors, and returns what each symbol is replaced with.
+    ///
+    /// Defaults to `apply_rule_with_rng()` on each symbol alone. Systems whose rules depend on
+    /// more than one symbol (see `context`) override this instead.
+    fn successors(&self, last_iteration: &[Alphabet], rng: &mut dyn RngCore) -> Vec<Vec<Alphabet>> {
+        last_iteration
+            .iter()
+            .cloned()
+            .map(|l| self.apply_rule_with_rng(l, rng))
+            .collect()
+    }
+
+    /// Whether every symbol is replaced independently of the symbols around it, so that a
+    /// sequence can be expanded one symbol at a time (see `LindenmayerSystemStream`). Defaults to
+    /// true. Systems that override `successors()` to look at more than one symbol must return
+    /// false.
+    fn is_context_free(&self) -> bool {
+        true
+    }
+
+    /// Applies the rules to every symbol of `last_iteration` (see `successors()`), and joins
+    /// together what they are replaced with.
+    fn generate_next_iteration(
+        &self,
+        last_iteration: &[Alphabet],
+        rng: &mut dyn RngCore,
+    ) -> Vec<Alphabet> {
+        self.successors(last_iteration, rng).concat()
+    }
+
+    /// Generates a Lindenmayer system string for `iteration`.
+    ///
+    /// This is done by starting with the initial sequence, and repeatedly applying the rules to
+    /// the sequence `iteration` times. The result is a new vector that contains the sequence
+    /// for the specified iteration.
+    fn generate(&self, iteration: u64) -> Vec<Alphabet> {
+        self.generate_with_seed(iteration, self.seed())
+    }
+
+    /// Like `generate()`, but chooses between successors with `seed` instead of `self.seed()`.
+    ///
+    /// Each iteration is generated from the one before it with `iteration_rng(seed, ...)`, so the
+    /// same seed always generates the same sequences, whichever iteration they are generated from.
+    fn generate_with_seed(&self, iteration: u64, seed: u64) -> Vec<Alphabet> {
+        let mut last: Vec<Alphabet> = self.initial();
+        for i in 0..iteration {
+            last = self.generate_next_iteration(&last, &mut iteration_rng(seed, i));
+        }
+        last
+    }
+}
+
+/// The random number generator that chooses between successors when generating iteration
+/// `iteration + 1` from iteration `iteration`.
+pub fn iteration_rng(seed: u64, iteration: u64) -> StdRng {
+    let mut key = [0u8; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    key[8..16].copy_from_slice(&iteration.to_le_bytes());
+    StdRng::from_seed(key)
+}
+
+/// In order to draw a fractal using a Lindenmayer System, we need to translate the output from the
+/// L-System into turtle commands. To do this, we need to initial the turtle, and we need a way to
+/// convert the L-System's symbols into actions. This trait provides the methods needed to
+/// configure the `LindenmayerSystemTurtleProgram`, which is the glue that issues arbitrary Turtle
+/// commands.
+pub trait LindenmayerSystemDrawingParameters<Alphabet> {
+    /// Returns the iteration that should be drawn.
+    fn iteration(&self) -> u64;
+
+    /// Specifies the turtle's initial position. Defaults to (0.0, 0.0).
+    fn initial_pos(&self) -> Point {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    /// Specifies the turtle's initial direction. Defaults to 0.0.
+    fn initial_rad(&self) -> f64 {
+        0.0
+    }
+
+    /// Convert symbol into a turtle command, or `None` for symbols that only control how the
+    /// system grows, which the turtle skips.
+    ///
+    /// Usually, when moving the turtle forwards, there is some formula that will ensure that
+    /// the turtle always ends at a given point, such as at (1.0, 0.0).
+    fn interpret_symbol(&self, symbol: Alphabet) -> Option<TurtleStep>;
+
+    /// Identifies the kind of `symbol`, so that segments drawn by the same kind of symbol can be
+    /// told apart from the rest. Defaults to the symbol's enum variant, which only works for enum
+    /// alphabets; systems with other alphabets should override it.
+    fn symbol_kind(&self, symbol: &Alphabet) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        mem::discriminant(symbol).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// How many bytes of sequences a `LindenmayerSystemCachingDecorator` keeps by default.
+pub const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+/// In order to improve the performance of using a Lindenmayer System under some circumstances, it
+/// is beneficial to cache each iteration. This allows O(1) lookups for every iteration that is
+/// still cached, and generating any other iteration only has to start from the largest cached
+/// iteration below it.
+///
+/// The cache holds at most `budget` bytes of sequences (counting `size_of::<A>()` for each
+/// symbol, but not anything a symbol owns on the heap), and evicts the least recently used
+/// iterations to stay within it. Iterations that are larger than the whole budget are not cached.
+///
+/// Clones share the same cache, which can be used from several threads at once, so the decorator
+/// is `Sync` whenever the system and its alphabet are. Sequences are generated without holding
+/// the cache's lock, so threads only wait for each other to look up and store sequences.
+///
+/// Stochastic L-systems generate different sequences for each seed, so iterations are cached
+/// separately for each seed.
+#[derive(Clone)]
+pub struct LindenmayerSystemCachingDecorator<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone + 'static,
+{
+    alphabet: PhantomData<A>,
+    pub system: L,
+    iteration_cache: Arc<Mutex<IterationCache<A>>>,
+}
+
+/// The iterations cached by a `LindenmayerSystemCachingDecorator`.
+struct IterationCache<A> {
+    /// The most bytes that `sequences` can hold.
+    budget: usize,
+    /// How many bytes `sequences` holds.
+    bytes: usize,
+    /// Sequences keyed by seed and iteration.
+    sequences: HashMap<(u64, u64), Arc<Vec<A>>>,
+    /// The keys of `sequences`, from the least to the most recently used.
+    recency: VecDeque<(u64, u64)>,
+}
+
+impl<A> IterationCache<A> {
+    fn sequence_bytes(sequence: &[A]) -> usize {
+        mem::size_of_val(sequence)
+    }
+
+    fn get(&mut self, key: (u64, u64)) -> Option<Arc<Vec<A>>> {
+        let sequence = self.sequences.get(&key)?.clone();
+        self.touch(key);
+        Some(sequence)
+    }
+
+    /// Marks `key` as the most recently used.
+    fn touch(&mut self, key: (u64, u64)) {
+        if let Some(position) = self.recency.iter().position(|k| *k == key) {
+            self.recency.remove(position);
+        }
+        self.recency.push_back(key);
+    }
+
+    /// Caches `sequence`, evicting the least recently used sequences until it fits.
+    fn insert(&mut self, key: (u64, u64), sequence: Arc<Vec<A>>) {
+        let bytes = IterationCache::sequence_bytes(&sequence);
+        if bytes > self.budget || self.sequences.contains_key(&key) {
+            return;
+        }
+        while self.bytes + bytes > self.budget {
+            let evicted = self.recency.pop_front().unwrap();
+            let evicted = self.sequences.remove(&evicted).unwrap();
+            self.bytes -= IterationCache::sequence_bytes(&evicted);
+        }
+        self.bytes += bytes;
+        self.sequences.insert(key, sequence);
+        self.recency.push_back(key);
+    }
+}
+
+impl<'a, L, A> LindenmayerSystemCachingDecorator<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone + 'static,
+{
+    /// Return a new LindenmayerSystemCachingDecorator that wraps the input
+    /// `system` and caches its iterations, within `DEFAULT_CACHE_BUDGET` bytes.
+    pub fn new(system: L) -> LindenmayerSystemCachingDecorator<L, A> {
+        LindenmayerSystemCachingDecorator::with_budget(system, DEFAULT_CACHE_BUDGET)
+    }
+
+    /// Like `new()`, but caches at most `budget` bytes of sequences.
+    pub fn with_budget(system: L, budget: usize) -> LindenmayerSystemCachingDecorator<L, A> {
+        LindenmayerSystemCachingDecorator {
+            alphabet: PhantomData,
+            system,
+            iteration_cache: Arc::new(Mutex::new(IterationCache {
+                budget,
+                bytes: 0,
+                sequences: HashMap::new(),
+                recency: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// How many bytes of sequences are cached.
+    pub fn cached_bytes(&self) -> usize {
+        self.cache().bytes
+    }
+
+    fn cache(&self) -> MutexGuard<'_, IterationCache<A>> {
+        // the cache is consistent between every change, even if another thread panicked
+        self.iteration_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<L, A> LindenmayerSystem<A> for LindenmayerSystemCachingDecorator<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone + 'static,
+{
+    /// Delegate to system
+    fn initial(&self) -> Vec<A> {
+        self.system.initial()
+    }
+
+    /// Delegate to system
+    fn apply_rule(&self, curr_symbol: A) -> Vec<A> {
+        self.system.apply_rule(curr_symbol)
+    }
+
+    /// Delegate to system
+    fn apply_rule_with_rng(&self, curr_symbol: A, rng: &mut dyn RngCore) -> Vec<A> {
+        self.system.apply_rule_with_rng(curr_symbol, rng)
+    }
+
+    /// Delegate to system
+    fn seed(&self) -> u64 {
+        self.system.seed()
+    }
+
+    /// Delegate to system
+    fn successors(&self, last_iteration: &[A], rng: &mut dyn RngCore) -> Vec<Vec<A>> {
+        self.system.successors(last_iteration, rng)
+    }
+
+    /// Delegate to system
+    fn is_context_free(&self) -> bool {
+        self.system.is_context_free()
+    }
+
+    /// Delegate to system
+    fn generate_next_iteration(&self, last_iteration: &[A], rng: &mut dyn RngCore) -> Vec<A> {
+        self.system.generate_next_iteration(last_iteration, rng)
+    }
+
+    /// Reimplement to use caching. Starts from the largest iteration up to `iteration` that is
+    /// cached for `seed`, and generates and caches each iteration after it in turn.
+    fn generate_with_seed(&self, iteration: u64, seed: u64) -> Vec<A> {
+        let (mut last, mut sequence) = {
+            let mut cache = self.cache();
+            (0..=iteration)
+                .rev()
+                .find_map(|i| cache.get((seed, i)).map(|sequence| (i, sequence)))
+                .unwrap_or_else(|| (0, Arc::new(self.system.initial())))
+        };
+        while last < iteration {
+            log::debug!("generating {} with seed {}", last + 1, seed);
+            sequence = Arc::new(
+                self.system
+                    .generate_next_iteration(&sequence, &mut iteration_rng(seed, last)),
+            );
+            last += 1;
+            self.cache().insert((seed, last), sequence.clone());
+        }
+        log::debug!("found {}", iteration);
+        Arc::try_unwrap(sequence).unwrap_or_else(|sequence| (*sequence).clone())
+    }
+}
+
+/// Expands the sequence of one iteration of an L-system lazily, one symbol at a time, instead of
+/// generating every iteration before it like `LindenmayerSystem::generate()`.
+///
+/// Symbols are expanded depth-first with an explicit stack, which only holds the successors that
+/// have not been visited yet of each symbol between the initial sequence and the current one. This
+/// uses O(iterations × the longest successor) memory, rather than memory proportional to the
+/// length of the sequence. Rules are applied in the same order and with the same random number
+/// generators as `generate()`, so stochastic systems expand to the same sequence.
+///
+/// Only context free systems (see `LindenmayerSystem::is_context_free()`) can be expanded this way.
+pub struct LindenmayerSystemStream<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone,
+{
+    alphabet: PhantomData<A>,
+    system: L,
+    iteration: u64,
+    /// The unvisited successors at each depth, where depth `d` holds symbols of iteration `d`.
+    stack: Vec<StreamFrame<A>>,
+    /// The random number generator for the rules applied to each depth's symbols.
+    rngs: Vec<StdRng>,
+    /// At each depth, the split that symbols replaced by nothing pass on to the next symbol.
+    carried_splits: Vec<u64>,
+}
+
+struct StreamFrame<A> {
+    successors: std::vec::IntoIter<A>,
+    /// The split of the first successor. The rest split off at this frame's depth.
+    first_split: Option<u64>,
+}
+
+impl<L, A> LindenmayerSystemStream<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone,
+{
+    /// Expands `system` to `iteration`, choosing between successors with `system.seed()`.
+    pub fn new(system: L, iteration: u64) -> LindenmayerSystemStream<L, A> {
+        let seed = system.seed();
+        let initial = system.initial();
+        LindenmayerSystemStream {
+            alphabet: PhantomData,
+            system,
+            iteration,
+            stack: vec![StreamFrame {
+                successors: initial.into_iter(),
+                first_split: None,
+            }],
+            rngs: (0..iteration).map(|i| iteration_rng(seed, i)).collect(),
+            carried_splits: vec![u64::MAX; iteration as usize + 1],
+        }
+    }
+
+    /// Returns the next symbol, along with the iteration at which it was split from the symbol
+    /// before it (see `TurtleProgram::segment_info()` for `LindenmayerSystemTurtleProgram`).
+    pub fn next_with_split(&mut self) -> Option<(A, u64)> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            let frame = self.stack.last_mut().unwrap();
+            let symbol = match frame.successors.next() {
+                Some(symbol) => symbol,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let split = frame.first_split.take().unwrap_or(depth as u64);
+            if depth as u64 == self.iteration {
+                return Some((symbol, split));
+            }
+
+            let successors = self
+                .system
+                .apply_rule_with_rng(symbol, &mut self.rngs[depth]);
+            let carried = &mut self.carried_splits[depth + 1];
+            if successors.is_empty() {
+                *carried = (*carried).min(split);
+            } else {
+                self.stack.push(StreamFrame {
+                    successors: successors.into_iter(),
+                    first_split: Some(split.min(*carried)),
+                });
+                *carried = u64::MAX;
+            }
+        }
+    }
+}
+
+impl<L, A> Iterator for LindenmayerSystemStream<L, A>
+where
+    L: LindenmayerSystem<A>,
+    A: Clone,
+{
+    type Item = A;
+
+    fn next(&mut self) -> Option<A> {
+        self.next_with_split().map(|(symbol, _)| symbol)
+    }
+}
+
+/// How `LindenmayerSystemTurtleProgram` expands the sequence that it draws.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ExpansionStrategy {
+    /// Generates and caches every iteration up to the one being drawn (see
+    /// `LindenmayerSystemCachingDecorator`). This uses memory proportional to the length of the
+    /// sequence, but later calls reuse it, and seeking skips to the nearest checkpoint.
+    #[default]
+    Cached,
+    /// Expands the sequence lazily every time it is drawn (see `LindenmayerSystemStream`), so that
+    /// memory only grows with the number of iterations. Seeking replays the sequence from the
+    /// start. Systems that are not context free are still cached.
+    Streaming,
+}
+
+/// How many symbols apart `LindenmayerSystemTurtleProgram` saves the turtle's state, so that
+/// seeking only has to replay the symbols after the nearest saved state.
+const SEEK_INTERVAL: usize = 1024;
+
+/// The turtle's state before a symbol of an L-system's sequence.
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    /// The index of the symbol in the sequence.
+    symbol: usize,
+    /// How many line segments the symbols before it draw.
+    moves: u64,
+    state: TurtleState,
+}
+
+#[derive(Clone)]
+pub struct LindenmayerSystemTurtleProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+    A: Clone + 'static,
+{
+    alphabet: PhantomData<A>,
+    cacheable_system: LindenmayerSystemCachingDecorator<L, A>, // system: L,
+    expansion: ExpansionStrategy,
+    /// A checkpoint every `SEEK_INTERVAL` symbols, built by the first `seek()`.
+    checkpoints: RefCell<Option<Rc<Vec<Checkpoint>>>>,
+}
+
+impl<L, A> LindenmayerSystemTurtleProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+    A: Clone,
+{
+    pub fn new(system: L) -> LindenmayerSystemTurtleProgram<L, A> {
+        LindenmayerSystemTurtleProgram {
+            alphabet: PhantomData,
+            cacheable_system: LindenmayerSystemCachingDecorator::new(system), // system: system,
+            expansion: ExpansionStrategy::default(),
+            checkpoints: RefCell::new(None),
+        }
+    }
+
+    /// Expands the sequence with `expansion` instead of caching it.
+    pub fn with_expansion(
+        self,
+        expansion: ExpansionStrategy,
+    ) -> LindenmayerSystemTurtleProgram<L, A> {
+        LindenmayerSystemTurtleProgram { expansion, ..self }
+    }
+
+    pub fn build<C>(ctor: C) -> Box<dyn Fn(u64) -> LindenmayerSystemTurtleProgram<L, A>>
+    where
+        C: Fn(u64) -> L + 'static,
+    {
+        Box::new(move |iteration| LindenmayerSystemTurtleProgram::new(ctor(iteration)))
+    }
+}
+
+impl<L, A> LindenmayerSystemTurtleProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A>,
+    A: Clone,
+{
+    /// Returns the cached checkpoints for `sequence`, the sequence of the iteration being drawn,
+    /// building them if needed.
+    fn checkpoints(&self, sequence: &[A]) -> Rc<Vec<Checkpoint>> {
+        if let Some(checkpoints) = self.checkpoints.borrow().as_ref() {
+            return checkpoints.clone();
+        }
+        let system = &self.cacheable_system.system;
+        let mut state = TurtleState::new();
+        for step in self.init_steps() {
+            state.perform(step);
+        }
+        let mut checkpoints = vec![];
+        let mut moves = 0;
+        for (index, symbol) in sequence.iter().enumerate() {
+            if index % SEEK_INTERVAL == 0 {
+                checkpoints.push(Checkpoint {
+                    symbol: index,
+                    moves,
+                    state: state.clone(),
+                });
+            }
+            if let Some(step) = system.interpret_symbol(symbol.clone()) {
+                if let TurtleStep::Forward(_) = step {
+                    moves += 1;
+                }
+                state.perform(step);
+            }
+        }
+        if checkpoints.is_empty() {
+            checkpoints.push(Checkpoint {
+                symbol: 0,
+                moves: 0,
+                state,
+            });
+        }
+        let checkpoints = Rc::new(checkpoints);
+        *self.checkpoints.borrow_mut() = Some(checkpoints.clone());
+        checkpoints
+    }
+
+    /// Whether the sequence is expanded lazily, instead of being cached.
+    fn is_streaming(&self) -> bool {
+        self.expansion == ExpansionStrategy::Streaming
+            && self.cacheable_system.system.is_context_free()
+    }
+
+    fn init_steps(&self) -> Vec<TurtleStep> {
+        vec![
+            TurtleStep::SetPos(self.cacheable_system.system.initial_pos()),
+            TurtleStep::SetRad(self.cacheable_system.system.initial_rad()),
+            TurtleStep::Down,
+        ]
+    }
+}
+
+impl<L, A> TurtleProgram for LindenmayerSystemTurtleProgram<L, A>
+where
+    LindenmayerSystemTurtleProgram<L, A>: Clone,
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A> + Clone + 'static,
+    A: Clone + PartialEq + 'static,
+{
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        self.init_steps()
+    }
+
+    // The lifetimes are needed here to satisfy TurtleProgramIterator's type signature.
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        if self.is_streaming() {
+            let system = self.cacheable_system.system.clone();
+            let stream = LindenmayerSystemStream::new(system.clone(), system.iteration());
+            return TurtleProgramIterator::new(Box::new(
+                stream.filter_map(move |symbol| system.interpret_symbol(symbol)),
+            ));
+        }
+        let sequence = self
+            .cacheable_system
+            .generate(self.cacheable_system.system.iteration());
+        log::debug!("Done");
+
+        TurtleProgramIterator::new(Box::new(LindenmayerSystemTurtleProgramIterator {
+            alphabet: PhantomData,
+            program: (*self).clone(),
+            sequence,
+            curr_step: 0,
+        }))
+    }
+
+    /// Each symbol's segment depth is the iteration at which it was split from the symbol before
+    /// it (the first symbol produced by a rule stays with its parent, while the rest split off).
+    /// A segment's depth is the shallowest split since the previous segment.
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        let system = self.cacheable_system.system.clone();
+        if self.is_streaming() {
+            let mut stream = LindenmayerSystemStream::new(system.clone(), system.iteration());
+            return Some(segment_infos(
+                system,
+                std::iter::from_fn(move || stream.next_with_split()),
+            ));
+        }
+
+        let mut sequence = system.initial();
+        let mut splits = vec![0; sequence.len()];
+        for iteration in 1..=system.iteration() {
+            // choose the same successors as `generate()`
+            let mut rng = iteration_rng(system.seed(), iteration - 1);
+            let mut next_sequence = Vec::with_capacity(sequence.len());
+            let mut next_splits = Vec::with_capacity(splits.len());
+            // Symbols that are replaced by nothing pass their split on to the next symbol.
+            let mut carried_split = u64::MAX;
+            let successors = system.successors(&sequence, &mut rng);
+            for (children, split) in successors.into_iter().zip(splits) {
+                let mut first = true;
+                for child in children {
+                    next_sequence.push(child);
+                    next_splits.push(if first {
+                        split.min(carried_split)
+                    } else {
+                        iteration
+                    });
+                    first = false;
+                    carried_split = u64::MAX;
+                }
+                if first {
+                    carried_split = carried_split.min(split);
+                }
+            }
+            sequence = next_sequence;
+            splits = next_splits;
+        }
+        Some(segment_infos(system, sequence.into_iter().zip(splits)))
+    }
+
+    /// Starts from the last checkpoint before the `moves`th segment, and only replays the symbols
+    /// after it. The first seek replays the whole sequence to build the checkpoints. Streaming
+    /// programs replay the sequence from the start instead.
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        if self.is_streaming() {
+            return seek_by_replay(self, moves);
+        }
+        let system = &self.cacheable_system.system;
+        let sequence = self.cacheable_system.generate(system.iteration());
+        let checkpoints = self.checkpoints(&sequence);
+        // a checkpoint that has already drawn `moves` segments may also be past the steps that
+        // follow the last of them
+        let nearest = checkpoints.partition_point(|checkpoint| checkpoint.moves < moves);
+        let checkpoint = &checkpoints[nearest.max(1) - 1];
+
+        let mut state = checkpoint.state.clone();
+        let mut index = checkpoint.symbol;
+        let mut skipped = checkpoint.moves;
+        while skipped < moves && index < sequence.len() {
+            if let Some(step) = system.interpret_symbol(sequence[index].clone()) {
+                if let TurtleStep::Forward(_) = step {
+                    skipped += 1;
+                }
+                state.perform(step);
+            }
+            index += 1;
+        }
+
+        TurtleSeek {
+            state,
+            rest: TurtleProgramIterator::new(Box::new(LindenmayerSystemTurtleProgramIterator {
+                alphabet: PhantomData,
+                program: (*self).clone(),
+                sequence,
+                curr_step: index,
+            })),
+        }
+    }
+
+    /// Counts symbols with the system's production matrix (see `growth::ProductionMatrix`), which
+    /// is only available for context free systems with a small enough alphabet.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let system = &self.cacheable_system.system;
+        let matrix = growth::ProductionMatrix::new(system).ok()?;
+        let size = matrix.size(system.iteration());
+        Some(ProgramSize {
+            steps: size.steps,
+            moves: size.forwards,
+            growth_rate: Some(matrix.growth_rate()),
+        })
+    }
+}
+
+pub struct LindenmayerSystemTurtleProgramIterator<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A> + 'static,
+    A: Clone + 'static,
+{
+    alphabet: PhantomData<A>,
+    program: LindenmayerSystemTurtleProgram<L, A>,
+    sequence: Vec<A>,
+    curr_step: usize,
+}
+
+impl<'a, L, A> Iterator for LindenmayerSystemTurtleProgramIterator<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystemDrawingParameters<A> + 'static,
+    A: Clone + 'static,
+{
+    type Item = TurtleStep;
+
+    /// Skips symbols that the system does not draw.
+    fn next(&mut self) -> Option<TurtleStep> {
+        while self.curr_step < self.sequence.len() {
+            let symbol = self.sequence[self.curr_step].clone();
+            self.curr_step += 1;
+            let step = self
+                .program
+                .cacheable_system
+                .system
+                .interpret_symbol(symbol);
+            if step.is_some() {
+                return step;
+            }
+        }
+        None
+    }
+}
+
+/// Describes the segments that `symbols` draw, given the split of each symbol. A segment's depth
+/// is the shallowest split since the previous segment, and its symbol is the index of the first
+/// segment drawn by the same kind of symbol, as given by `symbol_kind()`.
+fn segment_infos<L, A>(
+    system: L,
+    symbols: impl Iterator<Item = (A, u64)> + 'static,
+) -> Box<dyn Iterator<Item = SegmentInfo>>
+where
+    L: LindenmayerSystemDrawingParameters<A> + 'static,
+    A: 'static,
+{
+    let mut kinds = vec![];
+    let mut depth = u64::MAX;
+    let mut first = true;
+    Box::new(symbols.filter_map(move |(symbol, split)| {
+        depth = depth.min(split);
+        let kind = system.symbol_kind(&symbol);
+        match system.interpret_symbol(symbol) {
+            Some(TurtleStep::Forward(_)) => {
+                let symbol = kinds.iter().position(|k| *k == kind).unwrap_or_else(|| {
+                    kinds.push(kind);
+                    kinds.len() - 1
+                });
+                let info = SegmentInfo {
+                    symbol,
+                    depth: if first { 0 } else { depth },
+                };
+                first = false;
+                depth = u64::MAX;
+                Some(info)
+            }
+            _ => None,
+        }
+    }))
+}
+
+/// The 3D counterpart to `LindenmayerSystemDrawingParameters`, for L-systems that are drawn with
+/// a `Turtle3d` by a `LindenmayerSystemTurtle3dProgram`.
+pub trait LindenmayerSystem3dDrawingParameters<Alphabet> {
+    /// Returns the iteration that should be drawn.
+    fn iteration(&self) -> u64;
+
+    /// Specifies the turtle's initial position. Defaults to the origin.
+    fn initial_pos(&self) -> Point3 {
+        Point3::default()
+    }
+
+    /// Specifies the turtle's initial orientation. Defaults to `Frame::new()`.
+    fn initial_frame(&self) -> Frame {
+        Frame::new()
+    }
+
+    /// Convert symbol into a 3D turtle command, or `None` for symbols that only control how the
+    /// system grows.
+    fn interpret_symbol(&self, symbol: Alphabet) -> Option<Turtle3dStep>;
+}
+
+/// Draws a 3D L-system, caching its iterations like `LindenmayerSystemTurtleProgram`.
+#[derive(Clone)]
+pub struct LindenmayerSystemTurtle3dProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystem3dDrawingParameters<A>,
+    A: Clone + 'static,
+{
+    alphabet: PhantomData<A>,
+    cacheable_system: LindenmayerSystemCachingDecorator<L, A>,
+}
+
+impl<L, A> LindenmayerSystemTurtle3dProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystem3dDrawingParameters<A>,
+    A: Clone,
+{
+    pub fn new(system: L) -> LindenmayerSystemTurtle3dProgram<L, A> {
+        LindenmayerSystemTurtle3dProgram {
+            alphabet: PhantomData,
+            cacheable_system: LindenmayerSystemCachingDecorator::new(system),
+        }
+    }
+}
+
+impl<L, A> Turtle3dProgram for LindenmayerSystemTurtle3dProgram<L, A>
+where
+    L: LindenmayerSystem<A> + LindenmayerSystem3dDrawingParameters<A> + Clone + 'static,
+    A: Clone + PartialEq + 'static,
+{
+    fn init_turtle(&self) -> Vec<Turtle3dStep> {
+        let system = &self.cacheable_system.system;
+        vec![
+            Turtle3dStep::SetPos(system.initial_pos()),
+            Turtle3dStep::SetFrame(system.initial_frame()),
+            Turtle3dStep::Down,
+        ]
+    }
+
+    fn turtle_program_iter(&self) -> Box<dyn Iterator<Item = Turtle3dStep>> {
+        let system = self.cacheable_system.system.clone();
+        let sequence = self.cacheable_system.generate(system.iteration());
+        Box::new(
+            sequence
+                .into_iter()
+                .filter_map(move |symbol| system.interpret_symbol(symbol)),
+        )
+    }
+
+    /// Counts symbols like `LindenmayerSystemTurtleProgram::predicted_size()`.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let system = &self.cacheable_system.system;
+        let matrix = growth::ProductionMatrix::new_3d(system).ok()?;
+        let size = matrix.size(system.iteration());
+        Some(ProgramSize {
+            steps: size.steps,
+            moves: size.forwards,
+            growth_rate: Some(matrix.growth_rate()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::fractalplant::FractalPlant;
+    use crate::curves::kochcurve::KochCurve;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    enum TestABC {
+        A,
+        B,
+        C,
+        Foo,
+    }
+
+    #[derive(Clone)]
+    struct TestLS;
+
+    impl LindenmayerSystem<TestABC> for TestLS {
+        fn initial(&self) -> Vec<TestABC> {
+            vec![TestABC::A, TestABC::B, TestABC::C]
+        }
+
+        fn apply_rule(&self, l: TestABC) -> Vec<TestABC> {
+            match l {
+                TestABC::A => vec![TestABC::A, TestABC::Foo, TestABC::B, TestABC::C],
+                TestABC::B => vec![TestABC::Foo],
+                TestABC::C => vec![TestABC::Foo, TestABC::B],
+                TestABC::Foo => vec![TestABC::Foo],
+            }
+        }
+    }
+
+    /// Test a sample TestLS system
+    #[test]
+    fn test_lsystem() {
+        assert_eq!(TestLS.generate(0), [TestABC::A, TestABC::B, TestABC::C]);
+        assert_eq!(
+            TestLS.generate(1),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B
+            ]
+        );
+        assert_eq!(
+            TestLS.generate(2),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo
+            ]
+        );
+        assert_eq!(
+            TestLS.generate(3),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo
+            ]
+        );
+    }
+
+    impl LindenmayerSystemDrawingParameters<TestABC> for TestLS {
+        fn iteration(&self) -> u64 {
+            2
+        }
+
+        fn interpret_symbol(&self, symbol: TestABC) -> Option<TurtleStep> {
+            match symbol {
+                TestABC::A | TestABC::Foo => Some(TurtleStep::Forward(1.0)),
+                TestABC::B | TestABC::C => Some(TurtleStep::TurnRad(1.0)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_info() {
+        // Iteration 2 is: A Foo B C Foo Foo Foo B Foo Foo Foo, where A and Foo move forward.
+        let program = LindenmayerSystemTurtleProgram::new(TestLS);
+        let infos = program
+            .segment_info()
+            .unwrap()
+            .collect::<Vec<SegmentInfo>>();
+        assert_eq!(
+            infos.iter().map(|info| info.symbol).collect::<Vec<usize>>(),
+            [0, 1, 1, 1, 1, 1, 1, 1]
+        );
+        assert_eq!(
+            infos.iter().map(|info| info.depth).collect::<Vec<u64>>(),
+            [0, 2, 1, 1, 1, 0, 0, 1]
+        );
+        assert_eq!(
+            infos.len(),
+            program
+                .turtle_program_iter()
+                .filter(|step| matches!(step, TurtleStep::Forward(_)))
+                .count()
+        );
+    }
+
+    /// Test the caching decorator
+    #[test]
+    fn test_caching() {
+        // All this can really test is verify that multiple calls for the same
+        // generation come out the same.
+        let orig_system = TestLS;
+        let system = LindenmayerSystemCachingDecorator::new(orig_system);
+        // baseline
+        assert_eq!(system.generate(0), [TestABC::A, TestABC::B, TestABC::C]);
+        // again
+        assert_eq!(system.generate(0), [TestABC::A, TestABC::B, TestABC::C]);
+        // something that will require caching 1 and 2 and use 0's cache
+        assert_eq!(
+            system.generate(3),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo
+            ]
+        );
+        // use cache
+        assert_eq!(
+            system.generate(3),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::Foo
+            ]
+        );
+        // use cache
+        assert_eq!(
+            system.generate(1),
+            [
+                TestABC::A,
+                TestABC::Foo,
+                TestABC::B,
+                TestABC::C,
+                TestABC::Foo,
+                TestABC::Foo,
+                TestABC::B
+            ]
+        );
+    }
+
+    /// Grows each `A` into either `A B` or `B A`.
+    #[derive(Clone)]
+    struct TestStochastic(u64);
+
+    impl LindenmayerSystem<TestABC> for TestStochastic {
+        fn initial(&self) -> Vec<TestABC> {
+            vec![TestABC::A]
+        }
+
+        fn apply_rule(&self, l: TestABC) -> Vec<TestABC> {
+            match l {
+                TestABC::A => vec![TestABC::A, TestABC::B],
+                x => vec![x],
+            }
+        }
+
+        fn apply_rule_with_rng(&self, l: TestABC, rng: &mut dyn RngCore) -> Vec<TestABC> {
+            match l {
+                TestABC::A if rng.next_u32() % 2 == 1 => vec![TestABC::B, TestABC::A],
+                x => self.apply_rule(x),
+            }
+        }
+
+        fn seed(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_stochastic() {
+        let a_position = |sequence: Vec<TestABC>| {
+            assert_eq!(sequence.len(), 21);
+            sequence.iter().position(|l| *l == TestABC::A).unwrap()
+        };
+        assert_eq!(
+            TestStochastic(7).generate(20),
+            TestStochastic(7).generate(20)
+        );
+        let positions: Vec<usize> = (0..10)
+            .map(|seed| a_position(TestStochastic(seed).generate(20)))
+            .collect();
+        assert!(
+            positions.iter().any(|p| *p != positions[0]),
+            "every seed grew the same way: {:?}",
+            positions
+        );
+        assert_eq!(
+            TestStochastic(0).generate_with_seed(20, 3),
+            TestStochastic(3).generate(20)
+        );
+    }
+
+    #[test]
+    fn test_stochastic_caching() {
+        let system = LindenmayerSystemCachingDecorator::new(TestStochastic(5));
+        // cache iterations out of order, and for several seeds at once
+        for (iteration, seed) in [(10, 5), (4, 1), (12, 1), (3, 5), (20, 5), (20, 2)] {
+            assert_eq!(
+                system.generate_with_seed(iteration, seed),
+                TestStochastic(seed).generate(iteration),
+                "iteration {} seed {}",
+                iteration,
+                seed
+            );
+        }
+        assert_eq!(system.generate(20), TestStochastic(5).generate(20));
+    }
+
+    #[test]
+    fn test_cache_budget() {
+        // TestLS's iterations are 3, 7, 11, 15, 19... one-byte symbols long
+        assert_eq!(mem::size_of::<TestABC>(), 1);
+        let system = LindenmayerSystemCachingDecorator::with_budget(TestLS, 30);
+        assert_eq!(system.generate(3), TestLS.generate(3));
+        // iteration 1 was evicted to make room for 3
+        assert_eq!(system.cached_bytes(), 11 + 15);
+        assert_eq!(system.generate(2), TestLS.generate(2));
+        // generating 4 starts from 3, and then both 2 and 3 are evicted to make room for it
+        assert_eq!(system.generate(4), TestLS.generate(4));
+        assert_eq!(system.cached_bytes(), 19);
+
+        let tiny = LindenmayerSystemCachingDecorator::with_budget(TestLS, 10);
+        assert_eq!(tiny.generate(3), TestLS.generate(3));
+        assert_eq!(tiny.cached_bytes(), 7, "only iteration 1 fits");
+
+        let nothing = LindenmayerSystemCachingDecorator::with_budget(TestLS, 0);
+        assert_eq!(nothing.generate(3), TestLS.generate(3));
+        assert_eq!(nothing.cached_bytes(), 0);
+    }
+
+    #[test]
+    fn test_cache_threads() {
+        fn assert_sync<T: Send + Sync>(_: &T) {}
+        let system = LindenmayerSystemCachingDecorator::with_budget(TestStochastic(0), 200);
+        assert_sync(&system);
+        std::thread::scope(|scope| {
+            for seed in 0..4 {
+                let system = &system;
+                scope.spawn(move || {
+                    for iteration in [10, 5, 30, 20, 30] {
+                        assert_eq!(
+                            system.generate_with_seed(iteration, seed),
+                            TestStochastic(seed).generate(iteration)
+                        );
+                    }
+                });
+            }
+        });
+        assert!(system.cached_bytes() <= 200);
+
+        let clone = system.clone();
+        assert_eq!(clone.generate(30), TestStochastic(0).generate(30));
+        assert_eq!(
+            clone.cached_bytes(),
+            system.cached_bytes(),
+            "clones share a cache"
+        );
+    }
+
+    fn assert_seeks_like_replay<P: TurtleProgram>(program: &P, moves: u64) {
+        let seek = program.seek(moves);
+        let replay = seek_by_replay(program, moves);
+        assert_point_eq!(seek.state.position, replay.state.position, 0.000000001);
+        assert_approx_eq!(seek.state.angle, replay.state.angle, 0.000000001);
+        assert_eq!(seek.state.stack.len(), replay.state.stack.len());
+        assert_eq!(
+            seek.rest.collect::<Vec<TurtleStep>>(),
+            replay.rest.collect::<Vec<TurtleStep>>(),
+            "after {} moves",
+            moves
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        // long enough to need several checkpoints
+        let koch = LindenmayerSystemTurtleProgram::new(KochCurve::new(5));
+        let segments = 3 * 4u64.pow(5);
+        assert!(koch.cacheable_system.generate(5).len() > 3 * SEEK_INTERVAL);
+        for moves in [
+            0,
+            1,
+            2,
+            255,
+            256,
+            257,
+            1000,
+            segments - 1,
+            segments,
+            segments + 1,
+        ] {
+            assert_seeks_like_replay(&koch, moves);
+        }
+
+        let plant = LindenmayerSystemTurtleProgram::new(FractalPlant::new(5));
+        for moves in (0..600).step_by(37) {
+            assert_seeks_like_replay(&plant, moves);
+        }
+    }
+
+    /// Replaces `B` with nothing, so that it passes its split on to the next symbol.
+    #[derive(Clone)]
+    struct TestVanishing(u64);
+
+    impl LindenmayerSystem<TestABC> for TestVanishing {
+        fn initial(&self) -> Vec<TestABC> {
+            vec![TestABC::A, TestABC::B, TestABC::C]
+        }
+
+        fn apply_rule(&self, l: TestABC) -> Vec<TestABC> {
+            match l {
+                TestABC::A => vec![TestABC::B, TestABC::A, TestABC::Foo],
+                TestABC::B => vec![],
+                TestABC::C => vec![TestABC::C, TestABC::Foo],
+                TestABC::Foo => vec![TestABC::Foo],
+            }
+        }
+    }
+
+    impl LindenmayerSystemDrawingParameters<TestABC> for TestVanishing {
+        fn iteration(&self) -> u64 {
+            self.0
+        }
+
+        fn interpret_symbol(&self, symbol: TestABC) -> Option<TurtleStep> {
+            match symbol {
+                TestABC::A | TestABC::Foo => Some(TurtleStep::Forward(1.0)),
+                TestABC::B | TestABC::C => Some(TurtleStep::TurnRad(1.0)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        for iteration in 0..5 {
+            assert_eq!(
+                LindenmayerSystemStream::new(TestLS, iteration).collect::<Vec<_>>(),
+                TestLS.generate(iteration)
+            );
+            assert_eq!(
+                LindenmayerSystemStream::new(TestVanishing(0), iteration).collect::<Vec<_>>(),
+                TestVanishing(0).generate(iteration)
+            );
+        }
+        // stochastic rules choose the same successors
+        for seed in 0..5 {
+            assert_eq!(
+                LindenmayerSystemStream::new(TestStochastic(seed), 20).collect::<Vec<_>>(),
+                TestStochastic(seed).generate(20)
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_memory() {
+        let mut stream = LindenmayerSystemStream::new(KochCurve::new(6), 6);
+        let mut symbols = 0;
+        let mut deepest = 0;
+        while stream.next().is_some() {
+            symbols += 1;
+            deepest = deepest.max(stream.stack.len());
+        }
+        assert_eq!(symbols, KochCurve::new(6).generate(6).len());
+        // the initial sequence, and one frame of successors for each iteration
+        assert_eq!(deepest, 7);
+    }
+
+    #[test]
+    fn test_streaming_program() {
+        fn assert_streams_like_cache<L>(system: L)
+        where
+            L: LindenmayerSystem<TestABC>
+                + LindenmayerSystemDrawingParameters<TestABC>
+                + Clone
+                + 'static,
+        {
+            let cached = LindenmayerSystemTurtleProgram::new(system.clone());
+            let streaming = LindenmayerSystemTurtleProgram::new(system)
+                .with_expansion(ExpansionStrategy::Streaming);
+            assert_eq!(
+                streaming.turtle_program_iter().collect::<Vec<_>>(),
+                cached.turtle_program_iter().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                streaming.segment_info().unwrap().collect::<Vec<_>>(),
+                cached.segment_info().unwrap().collect::<Vec<_>>()
+            );
+        }
+        assert_streams_like_cache(TestLS);
+        for iteration in 0..5 {
+            assert_streams_like_cache(TestVanishing(iteration));
+        }
+
+        let koch = LindenmayerSystemTurtleProgram::new(KochCurve::new(5))
+            .with_expansion(ExpansionStrategy::Streaming);
+        for moves in [0, 1, 1000, 3 * 4u64.pow(5) + 1] {
+            assert_seeks_like_replay(&koch, moves);
+        }
+        assert_eq!(
+            koch.cacheable_system.cached_bytes(),
+            0,
+            "nothing was cached"
+        );
+
+        let plant = FractalPlant::new(4);
+        let cached = LindenmayerSystemTurtleProgram::new(plant);
+        let streaming = cached.clone().with_expansion(ExpansionStrategy::Streaming);
+        assert_eq!(
+            streaming.segment_info().unwrap().collect::<Vec<_>>(),
+            cached.segment_info().unwrap().collect::<Vec<_>>()
+        );
+    }
+}
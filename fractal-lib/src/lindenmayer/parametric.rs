@@ -0,0 +1,743 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parametric L-systems, whose symbols carry numeric parameters that their rules compute and
+//! test, and that the turtle uses to draw them.
+//!
+//! Grammars are written in TOML, like those in `dynamic`, and say that they are parametric:
+//!
+//! ```toml
+//! parametric = true
+//! name = "Binary tree"     # optional
+//! axiom = "!(6)A(0.5)"
+//! angle = 45               # how far + and - turn without a parameter, in degrees
+//! length = 1               # optional, how far F moves without a parameter, defaults to 1
+//! start = [0.0, -1.0]      # optional, where the turtle starts, defaults to the origin
+//! heading = 90             # optional, the turtle's initial direction in degrees, defaults to 0
+//! rules = [
+//!     "A(l) : l >= 0.05 -> F(l)[+(30)!(l*10)A(l*0.7)][-(25)!(l*10)A(l*0.6)]",
+//!     "A(l) -> F(l)",
+//! ]
+//!
+//! [actions]                # optional, overrides what symbols do, as in `dynamic`
+//! X = "forward"
+//! ```
+//!
+//! A symbol's parameters follow it in parentheses, separated by commas. A rule names the
+//! parameters of the symbol it replaces, may have a condition after a `:`, and computes the
+//! parameters of its successor from them. Expressions can use numbers, parameters, `+ - * / ^`,
+//! comparisons (`< <= > >= == !=`), `&&`, `||`, and parentheses. Every symbol is replaced by the
+//! first rule with the same symbol and number of parameters whose condition holds, or left as it
+//! is if there is none. Whitespace is ignored.
+//!
+//! The turtle reads the first parameter, if there is one: `F` and `G` move forward by it, `+` and
+//! `-` turn by it in degrees, and `!` sets the pen width to it in pixels.
+
+use super::dynamic::{
+    check_finite, one, parse_actions, parse_toml, DynamicLSystemError, SymbolAction,
+};
+use super::{LindenmayerSystem, LindenmayerSystemDrawingParameters};
+use crate::geometry::{deg2rad, Point};
+use crate::turtle::{TurtleStep, DEFAULT_PEN_WIDTH};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::PI;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A symbol and its parameters, which is the alphabet of a `ParametricLSystem`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    pub symbol: char,
+    pub params: Vec<f64>,
+}
+
+impl Module {
+    pub fn new(symbol: char, params: Vec<f64>) -> Module {
+        Module { symbol, params }
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol)?;
+        if !self.params.is_empty() {
+            let params: Vec<String> = self.params.iter().map(f64::to_string).collect();
+            write!(f, "({})", params.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Arithmetic on a rule's parameters. Comparisons and logic are 1 when true and 0 when false.
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Number(f64),
+    /// The value of the parameter at this index.
+    Parameter(usize),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+impl Expression {
+    fn evaluate(&self, params: &[f64]) -> f64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Parameter(index) => params[*index],
+            Expression::Negate(expression) => -expression.evaluate(params),
+            Expression::Binary(operator, left, right) => {
+                let (a, b) = (left.evaluate(params), right.evaluate(params));
+                let truth = |holds: bool| if holds { 1.0 } else { 0.0 };
+                match operator {
+                    Operator::Add => a + b,
+                    Operator::Subtract => a - b,
+                    Operator::Multiply => a * b,
+                    Operator::Divide => a / b,
+                    Operator::Power => a.powf(b),
+                    Operator::Less => truth(a < b),
+                    Operator::LessOrEqual => truth(a <= b),
+                    Operator::Greater => truth(a > b),
+                    Operator::GreaterOrEqual => truth(a >= b),
+                    Operator::Equal => truth(a == b),
+                    Operator::NotEqual => truth(a != b),
+                    Operator::And => truth(a != 0.0 && b != 0.0),
+                    Operator::Or => truth(a != 0.0 || b != 0.0),
+                }
+            }
+        }
+    }
+}
+
+/// Replaces a symbol that has `arity` parameters, if `condition` holds for them.
+#[derive(Clone, Debug)]
+struct Rule {
+    symbol: char,
+    arity: usize,
+    condition: Option<Expression>,
+    successor: Vec<(char, Vec<Expression>)>,
+}
+
+impl Rule {
+    /// Parses `predecessor : condition -> successor`, where the condition is optional.
+    fn parse(text: &str) -> Result<Rule, String> {
+        let (left, successor) = text
+            .split_once("->")
+            .ok_or_else(|| "expected \"->\"".to_string())?;
+        let (predecessor, condition) = match left.split_once(':') {
+            Some((predecessor, condition)) => (predecessor, Some(condition)),
+            None => (left, None),
+        };
+
+        let mut parser = Parser::new(predecessor, &[]);
+        let symbol = parser.symbol()?.ok_or("the predecessor is empty")?;
+        let mut names = vec![];
+        if parser.eat("(") && !parser.eat(")") {
+            loop {
+                let name = parser.name().ok_or("expected a parameter name")?;
+                if names.contains(&name) {
+                    return Err(format!("the parameter {:?} is named twice", name));
+                }
+                names.push(name);
+                if !parser.eat(",") {
+                    parser.expect(")")?;
+                    break;
+                }
+            }
+        }
+        parser.expect_end()?;
+
+        let condition = match condition {
+            Some(condition) => {
+                let mut parser = Parser::new(condition, &names);
+                let condition = parser.expression()?;
+                parser.expect_end()?;
+                Some(condition)
+            }
+            None => None,
+        };
+        Ok(Rule {
+            symbol,
+            arity: names.len(),
+            condition,
+            successor: Parser::new(successor, &names).modules()?,
+        })
+    }
+
+    fn matches(&self, module: &Module) -> bool {
+        self.symbol == module.symbol
+            && self.arity == module.params.len()
+            && self
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.evaluate(&module.params) != 0.0)
+    }
+}
+
+/// Evaluates the parameters of every module in `modules`.
+fn evaluate_modules(modules: &[(char, Vec<Expression>)], params: &[f64]) -> Vec<Module> {
+    modules
+        .iter()
+        .map(|(symbol, expressions)| Module {
+            symbol: *symbol,
+            params: expressions.iter().map(|e| e.evaluate(params)).collect(),
+        })
+        .collect()
+}
+
+/// A recursive descent parser for modules and the expressions in their parameters.
+struct Parser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    /// The names of the parameters that expressions can use.
+    names: &'a [String],
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &str, names: &'a [String]) -> Parser<'a> {
+        Parser {
+            chars: text.chars().filter(|c| !c.is_whitespace()).collect(),
+            position: 0,
+            names,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consumes `token` if it is next.
+    fn eat(&mut self, token: &str) -> bool {
+        let length = token.chars().count();
+        let matches = self.position + length <= self.chars.len()
+            && self.chars[self.position..self.position + length]
+                .iter()
+                .copied()
+                .eq(token.chars());
+        if matches {
+            self.position += length;
+        }
+        matches
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("{:?}", token)))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), String> {
+        match self.peek() {
+            Some(_) => Err(self.unexpected("the end")),
+            None => Ok(()),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(c) => format!("expected {}, found {:?}", expected, c),
+            None => format!("expected {}, found the end", expected),
+        }
+    }
+
+    /// A symbol, which is any character except the ones that surround parameters.
+    fn symbol(&mut self) -> Result<Option<char>, String> {
+        match self.peek() {
+            Some('(' | ')' | ',') => Err(self.unexpected("a symbol")),
+            Some(c) => {
+                self.position += 1;
+                Ok(Some(c))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Symbols, each optionally followed by its parameters, until the end of the text.
+    fn modules(&mut self) -> Result<Vec<(char, Vec<Expression>)>, String> {
+        let mut modules = vec![];
+        while let Some(symbol) = self.symbol()? {
+            let mut params = vec![];
+            if self.eat("(") && !self.eat(")") {
+                loop {
+                    params.push(self.expression()?);
+                    if !self.eat(",") {
+                        self.expect(")")?;
+                        break;
+                    }
+                }
+            }
+            modules.push((symbol, params));
+        }
+        Ok(modules)
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c.is_alphabetic() || c == '_' || (self.position > start && c.is_ascii_digit()) {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        if self.position > start {
+            Some(self.chars[start..self.position].iter().collect())
+        } else {
+            None
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        self.binary(0)
+    }
+
+    /// Parses operators that bind at least as tightly as `OPERATORS[level]`, from left to right.
+    fn binary(&mut self, level: usize) -> Result<Expression, String> {
+        /// Each level's operators, from the loosest to the tightest binding. Longer tokens come
+        /// first, so that `<=` is not read as `<`.
+        const OPERATORS: &[&[(&str, Operator)]] = &[
+            &[("||", Operator::Or)],
+            &[("&&", Operator::And)],
+            &[
+                ("<=", Operator::LessOrEqual),
+                (">=", Operator::GreaterOrEqual),
+                ("==", Operator::Equal),
+                ("!=", Operator::NotEqual),
+                ("<", Operator::Less),
+                (">", Operator::Greater),
+            ],
+            &[("+", Operator::Add), ("-", Operator::Subtract)],
+            &[("*", Operator::Multiply), ("/", Operator::Divide)],
+        ];
+        if level == OPERATORS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        'operators: loop {
+            for (token, operator) in OPERATORS[level] {
+                if self.eat(token) {
+                    let right = self.binary(level + 1)?;
+                    left = Expression::Binary(*operator, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat("-") {
+            return Ok(Expression::Negate(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat("^") {
+            // right associative, and tighter than negation only on its left
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        if self.eat("(") {
+            let expression = self.expression()?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+        if let Some(name) = self.name() {
+            return match self.names.iter().position(|n| *n == name) {
+                Some(index) => Ok(Expression::Parameter(index)),
+                None => Err(format!("unknown parameter {:?}", name)),
+            };
+        }
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '.') {
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.unexpected("a number or parameter"));
+        }
+        let number: String = self.chars[start..self.position].iter().collect();
+        number
+            .parse()
+            .map(Expression::Number)
+            .map_err(|_| format!("{:?} is not a number", number))
+    }
+}
+
+/// Whether `text` is a grammar for a `ParametricLSystem`, rather than a `DynamicLSystem`.
+pub fn is_parametric(text: &str) -> bool {
+    text.parse::<toml::Table>()
+        .is_ok_and(|table| table.get("parametric") == Some(&toml::Value::Boolean(true)))
+}
+
+/// An L-system whose symbols carry parameters, read from a grammar.
+#[derive(Clone, Debug)]
+pub struct ParametricLSystem {
+    iterations: u64,
+    name: String,
+    axiom: Vec<Module>,
+    rules: Vec<Rule>,
+    actions: HashMap<char, SymbolAction>,
+    /// In radians.
+    angle: f64,
+    length: f64,
+    start: Point,
+    /// In radians.
+    heading: f64,
+}
+
+impl ParametricLSystem {
+    /// Parses a grammar (see the module documentation), to be drawn at `iterations`.
+    pub fn parse(text: &str, iterations: u64) -> Result<ParametricLSystem, DynamicLSystemError> {
+        let grammar: TomlParametricGrammar = parse_toml(text)?;
+        let error = |message: String| DynamicLSystemError::Parse {
+            line: None,
+            message,
+        };
+
+        if !grammar.parametric {
+            return Err(error("the grammar is not parametric".to_string()));
+        }
+        let axiom = Parser::new(&grammar.axiom, &[])
+            .modules()
+            .map_err(|e| error(format!("axiom {:?}: {}", grammar.axiom, e)))?;
+        if axiom.is_empty() {
+            return Err(error("the axiom is empty".to_string()));
+        }
+        let rules = grammar
+            .rules
+            .iter()
+            .map(|rule| Rule::parse(rule).map_err(|e| error(format!("rule {:?}: {}", rule, e))))
+            .collect::<Result<_, _>>()?;
+        let actions = parse_actions(grammar.actions)?;
+        check_finite(&[
+            ("angle", grammar.angle),
+            ("length", grammar.length),
+            ("heading", grammar.heading),
+            ("start", grammar.start[0]),
+            ("start", grammar.start[1]),
+        ])?;
+
+        Ok(ParametricLSystem {
+            iterations,
+            name: grammar.name,
+            axiom: evaluate_modules(&axiom, &[]),
+            rules,
+            actions,
+            angle: deg2rad(grammar.angle),
+            length: grammar.length,
+            start: Point {
+                x: grammar.start[0],
+                y: grammar.start[1],
+            },
+            heading: deg2rad(grammar.heading),
+        })
+    }
+
+    /// Loads a grammar file, to be drawn at `iterations`.
+    pub fn load(path: &Path, iterations: u64) -> Result<ParametricLSystem, DynamicLSystemError> {
+        let text = fs::read_to_string(path).map_err(|source| DynamicLSystemError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        ParametricLSystem::parse(&text, iterations)
+    }
+
+    /// The grammar's name, which is empty if it does not have one.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What the turtle does when it reaches `symbol`.
+    pub fn action(&self, symbol: char) -> SymbolAction {
+        match self.actions.get(&symbol) {
+            Some(action) => *action,
+            None => SymbolAction::default_for(symbol),
+        }
+    }
+}
+
+impl LindenmayerSystem<Module> for ParametricLSystem {
+    fn initial(&self) -> Vec<Module> {
+        self.axiom.clone()
+    }
+
+    fn apply_rule(&self, module: Module) -> Vec<Module> {
+        match self.rules.iter().find(|rule| rule.matches(&module)) {
+            Some(rule) => evaluate_modules(&rule.successor, &module.params),
+            None => vec![module],
+        }
+    }
+}
+
+impl LindenmayerSystemDrawingParameters<Module> for ParametricLSystem {
+    fn iteration(&self) -> u64 {
+        self.iterations
+    }
+
+    fn initial_pos(&self) -> Point {
+        self.start
+    }
+
+    fn initial_rad(&self) -> f64 {
+        self.heading
+    }
+
+    fn interpret_symbol(&self, module: Module) -> Option<TurtleStep> {
+        let param = module.params.first().copied();
+        match self.action(module.symbol) {
+            SymbolAction::Forward => Some(TurtleStep::Forward(param.unwrap_or(self.length))),
+            SymbolAction::Left => Some(TurtleStep::TurnRad(param.map_or(self.angle, deg2rad))),
+            SymbolAction::Right => Some(TurtleStep::TurnRad(-param.map_or(self.angle, deg2rad))),
+            SymbolAction::TurnAround => Some(TurtleStep::TurnRad(PI)),
+            SymbolAction::Push => Some(TurtleStep::Push),
+            SymbolAction::Pop => Some(TurtleStep::Pop),
+            SymbolAction::PenWidth => {
+                Some(TurtleStep::SetPenWidth(param.unwrap_or(DEFAULT_PEN_WIDTH)))
+            }
+            SymbolAction::PenUp => Some(TurtleStep::Up),
+            SymbolAction::PenDown => Some(TurtleStep::Down),
+            SymbolAction::Nothing => None,
+        }
+    }
+
+    fn symbol_kind(&self, module: &Module) -> u64 {
+        u64::from(module.symbol)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlParametricGrammar {
+    #[serde(default)]
+    parametric: bool,
+    #[serde(default)]
+    name: String,
+    axiom: String,
+    angle: f64,
+    #[serde(default = "one")]
+    length: f64,
+    #[serde(default)]
+    start: [f64; 2],
+    #[serde(default)]
+    heading: f64,
+    #[serde(default)]
+    rules: Vec<String>,
+    #[serde(default)]
+    actions: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::TurtleProgram;
+
+    const TREE: &str = r#"
+        parametric = true
+        name = "Binary tree"
+        axiom = "!(6)A(0.5)"
+        angle = 45
+        start = [0.0, -1.0]
+        heading = 90
+        rules = [
+            "A(l) : l >= 0.3 -> F(l)[+(30)!(l*10)A(l*0.7)][-(25)!(l*10)A(l*0.6)]",
+            "A(l) -> F(l)",
+        ]
+    "#;
+
+    fn modules(text: &str) -> Vec<Module> {
+        evaluate_modules(&Parser::new(text, &[]).modules().unwrap(), &[])
+    }
+
+    fn evaluate(expression: &str, params: &[f64]) -> f64 {
+        let names: Vec<String> = ["a", "b"].iter().map(|n| n.to_string()).collect();
+        let mut parser = Parser::new(expression, &names[..params.len()]);
+        let expression = parser.expression().unwrap();
+        parser.expect_end().unwrap();
+        expression.evaluate(params)
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_eq!(evaluate("1 + 2 * 3", &[]), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3", &[]), 9.0);
+        assert_eq!(evaluate("10 - 4 - 3", &[]), 3.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2", &[]), 512.0);
+        assert_eq!(evaluate("-2 ^ 2", &[]), -4.0);
+        assert_eq!(evaluate("a * 0.5 - b", &[3.0, 1.0]), 0.5);
+        assert_eq!(evaluate("a >= 1 && b < 1", &[1.0, 0.5]), 1.0);
+        assert_eq!(evaluate("a != 1 || b == 2", &[1.0, 0.5]), 0.0);
+        assert_eq!(evaluate("a < b + 1", &[1.0, 0.5]), 1.0);
+    }
+
+    #[test]
+    fn test_modules() {
+        assert_eq!(
+            modules("F(1, 2.5) + [G()]"),
+            vec![
+                Module::new('F', vec![1.0, 2.5]),
+                Module::new('+', vec![]),
+                Module::new('[', vec![]),
+                Module::new('G', vec![]),
+                Module::new(']', vec![]),
+            ]
+        );
+        assert_eq!(
+            modules("A(2*3)-(45)")
+                .iter()
+                .map(Module::to_string)
+                .collect::<Vec<_>>(),
+            vec!["A(6)", "-(45)"]
+        );
+    }
+
+    #[test]
+    fn test_rules() {
+        let tree = ParametricLSystem::parse(TREE, 2).unwrap();
+        assert_eq!(tree.name(), "Binary tree");
+        assert_eq!(
+            tree.apply_rule(Module::new('A', vec![0.5])),
+            modules("F(0.5)[+(30)!(5)A(0.35)][-(25)!(5)A(0.3)]")
+        );
+        // the condition no longer holds, so the second rule applies
+        assert_eq!(
+            tree.apply_rule(Module::new('A', vec![0.25])),
+            modules("F(0.25)")
+        );
+        // neither rule has two parameters
+        assert_eq!(
+            tree.apply_rule(Module::new('A', vec![0.5, 1.0])),
+            vec![Module::new('A', vec![0.5, 1.0])]
+        );
+        assert_eq!(tree.generate(3), tree.generate(4), "the tree stops growing");
+    }
+
+    #[test]
+    fn test_drawing() {
+        let program =
+            LindenmayerSystemTurtleProgram::new(ParametricLSystem::parse(TREE, 1).unwrap());
+        let steps: Vec<TurtleStep> = program.turtle_program_iter().collect();
+        assert_eq!(
+            steps,
+            vec![
+                TurtleStep::SetPenWidth(6.0),
+                TurtleStep::Forward(0.5),
+                TurtleStep::Push,
+                TurtleStep::TurnRad(deg2rad(30.0)),
+                TurtleStep::SetPenWidth(5.0),
+                TurtleStep::Pop,
+                TurtleStep::Push,
+                TurtleStep::TurnRad(deg2rad(-25.0)),
+                TurtleStep::SetPenWidth(5.0),
+                TurtleStep::Pop,
+            ]
+        );
+
+        let defaults = ParametricLSystem::parse(
+            "parametric = true\naxiom = \"F+-!\"\nangle = 90\nlength = 2",
+            0,
+        )
+        .unwrap();
+        let steps: Vec<TurtleStep> = "F+-!"
+            .chars()
+            .filter_map(|symbol| defaults.interpret_symbol(Module::new(symbol, vec![])))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                TurtleStep::Forward(2.0),
+                TurtleStep::TurnRad(PI / 2.0),
+                TurtleStep::TurnRad(-PI / 2.0),
+                TurtleStep::SetPenWidth(DEFAULT_PEN_WIDTH),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_parametric() {
+        assert!(is_parametric(TREE));
+        assert!(!is_parametric("axiom = \"F\"\nangle = 90"));
+        assert!(!is_parametric("parametric = \"yes\""));
+        assert!(!is_parametric("not toml"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| {
+            ParametricLSystem::parse(&format!("parametric = true\nangle = 90\n{}", text), 1)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            ParametricLSystem::parse("axiom = \"F\"\nangle = 90", 1)
+                .unwrap_err()
+                .to_string(),
+            "the grammar is not parametric"
+        );
+        assert_eq!(error("axiom = \"\""), "the axiom is empty");
+        assert_eq!(
+            error("axiom = \"F(1\""),
+            "axiom \"F(1\": expected \")\", found the end"
+        );
+        assert_eq!(
+            error("axiom = \"F(l)\""),
+            "axiom \"F(l)\": unknown parameter \"l\""
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nrules = [\"F(l) F(l)\"]"),
+            "rule \"F(l) F(l)\": expected \"->\""
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nrules = [\"F(l, l) -> F\"]"),
+            "rule \"F(l, l) -> F\": the parameter \"l\" is named twice"
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nrules = [\"F(l) : l > -> F\"]"),
+            "rule \"F(l) : l > -> F\": expected a number or parameter, found the end"
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nrules = [\"F(l) -> F(x)\"]"),
+            "rule \"F(l) -> F(x)\": unknown parameter \"x\""
+        );
+        assert_eq!(
+            error("axiom = \"F\"\nlength = inf"),
+            "length must be a finite number"
+        );
+    }
+}
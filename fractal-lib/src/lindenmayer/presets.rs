@@ -0,0 +1,142 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A library of ready-made grammars (see `dynamic`), for plants, bushes, weeds, islands,
+//! snowflakes, tiles, and crystals, along with classics from Prusinkiewicz and Lindenmayer's *The
+//! Algorithmic Beauty of Plants*.
+//!
+//! Each preset is a grammar file in the `presets` directory, so they also serve as examples of
+//! the grammar format. `SelectedFractal::preset()` finds the preset for a fractal.
+
+use super::dynamic::DynamicLSystem;
+use crate::geometry::Point;
+
+/// A grammar from the library, along with how it is best drawn.
+#[derive(Copy, Clone, Debug)]
+pub struct LSystemPreset {
+    /// The iteration that shows off the preset, before it gets too dense or slow to draw.
+    pub iteration: u64,
+    /// The grammar, which `DynamicLSystem::parse()` reads.
+    pub grammar: &'static str,
+}
+
+/// Where a preset's turtle starts and which way it faces, which decides how the preset sits in
+/// the view. Plants face up, so that they grow from the ground.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PresetView {
+    pub start: Point,
+    /// In degrees.
+    pub heading: f64,
+}
+
+impl LSystemPreset {
+    /// Builds the preset's L-system, to be drawn at `iteration`.
+    pub fn system(&self, iteration: u64) -> DynamicLSystem {
+        DynamicLSystem::parse(self.grammar, iteration).expect("presets are valid grammars")
+    }
+
+    /// How far the preset turns, in degrees.
+    pub fn angle(&self) -> f64 {
+        self.system(0).angle()
+    }
+
+    /// Where the preset starts and which way it faces.
+    pub fn view(&self) -> PresetView {
+        let system = self.system(0);
+        PresetView {
+            start: system.start(),
+            heading: system.heading(),
+        }
+    }
+}
+
+macro_rules! preset {
+    ($iteration:expr, $file:expr) => {
+        LSystemPreset {
+            iteration: $iteration,
+            grammar: include_str!(concat!("presets/", $file)),
+        }
+    };
+}
+
+// Plants, bushes, and weeds
+pub const STOCHASTIC_PLANT: LSystemPreset = preset!(5, "stochasticplant.toml");
+pub const TALL_PLANT: LSystemPreset = preset!(6, "tallplant.toml");
+pub const BUSH: LSystemPreset = preset!(5, "bush.toml");
+pub const BUSH_2: LSystemPreset = preset!(4, "bush2.toml");
+pub const BUSH_3: LSystemPreset = preset!(8, "bush3.toml");
+pub const WEED: LSystemPreset = preset!(6, "weed.toml");
+
+// Islands and snowflakes
+pub const ISLANDS: LSystemPreset = preset!(2, "islands.toml");
+pub const KOCH_ISLAND: LSystemPreset = preset!(2, "kochisland.toml");
+pub const ANTI_SNOWFLAKE: LSystemPreset = preset!(4, "antisnowflake.toml");
+pub const HEX_SNOWFLAKE: LSystemPreset = preset!(4, "hexsnowflake.toml");
+
+// Tiles and crystals
+pub const TILES: LSystemPreset = preset!(3, "tiles.toml");
+pub const BOARD: LSystemPreset = preset!(3, "board.toml");
+pub const PENTAPLEXITY: LSystemPreset = preset!(3, "pentaplexity.toml");
+pub const CRYSTAL: LSystemPreset = preset!(4, "crystal.toml");
+pub const RINGS: LSystemPreset = preset!(3, "rings.toml");
+
+// The branching plants in figure 1.24 of The Algorithmic Beauty of Plants
+pub const PLANT_A: LSystemPreset = preset!(5, "planta.toml");
+pub const PLANT_B: LSystemPreset = preset!(5, "plantb.toml");
+pub const PLANT_C: LSystemPreset = preset!(4, "plantc.toml");
+pub const PLANT_D: LSystemPreset = preset!(7, "plantd.toml");
+pub const PLANT_E: LSystemPreset = preset!(7, "plante.toml");
+pub const PLANT_F: LSystemPreset = preset!(5, "plantf.toml");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::TurtleProgram;
+    use crate::SelectedFractal;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_presets() {
+        let presets: Vec<_> = SelectedFractal::iter()
+            .filter_map(|fractal| Some((fractal, fractal.preset()?)))
+            .collect();
+        assert_eq!(presets.len(), 21);
+        for (fractal, preset) in presets {
+            let system = preset.system(preset.iteration);
+            assert_eq!(system.name(), fractal.name());
+            // the recommended iteration draws something, without taking too long
+            let size = LindenmayerSystemTurtleProgram::new(system)
+                .predicted_size()
+                .unwrap();
+            assert!(size.moves > 0, "{} draws nothing", fractal.name());
+            assert!(
+                size.steps < 100_000,
+                "{} takes {} steps",
+                fractal.name(),
+                size.steps
+            );
+        }
+    }
+
+    #[test]
+    fn test_angle_and_view() {
+        assert_approx_eq!(PLANT_A.angle(), 25.7, 1e-10);
+        let view = PLANT_A.view();
+        assert_point_eq!(view.start, Point { x: 0.0, y: 0.0 }, 1e-10);
+        assert_approx_eq!(view.heading, 90.0, 1e-10);
+        assert_approx_eq!(CRYSTAL.angle(), 90.0, 1e-10);
+        assert_approx_eq!(CRYSTAL.view().heading, 0.0, 1e-10);
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computing the area that a turtle program draws in, so that renderers can fit their view to it.
+
+use super::{Turtle, TurtleProgram, TurtleState};
+use crate::color::ColorF32;
+use crate::geometry::{Point, Vec2d, Vector, ViewAreaTransformer};
+
+/// The fraction of a program's larger dimension that `view_area()` leaves empty around it.
+pub const VIEW_MARGIN: f64 = 0.05;
+
+/// The view used for programs that do not draw anything. Curves that run from (0,0) to (1,0)
+/// were traditionally drawn in this view.
+pub const DEFAULT_VIEW: BoundingBox = BoundingBox {
+    min: Point { x: -0.5, y: -0.75 },
+    max: Point { x: 1.5, y: 0.75 },
+};
+
+/// An axis-aligned rectangle in the turtle's coordinate system.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// A bounding box that contains only `point`.
+    pub fn at(point: Point) -> BoundingBox {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    /// Grows the bounding box so that it contains `point`.
+    pub fn include(&mut self, point: Point) {
+        self.min.x = self.min.x.min(point.x);
+        self.min.y = self.min.y.min(point.y);
+        self.max.x = self.max.x.max(point.x);
+        self.max.y = self.max.y.max(point.y);
+    }
+
+    /// Returns a copy of the bounding box with `margin` times its larger dimension added to every
+    /// side. A bounding box around a single point grows to a unit square instead.
+    pub fn with_margin(&self, margin: f64) -> BoundingBox {
+        let size = self.width().max(self.height());
+        let pad = if size > 0.0 { size * margin } else { 0.5 };
+        BoundingBox {
+            min: Point {
+                x: self.min.x - pad,
+                y: self.min.y - pad,
+            },
+            max: Point {
+                x: self.max.x + pad,
+                y: self.max.y + pad,
+            },
+        }
+    }
+
+    /// Builds a `ViewAreaTransformer` that fits the bounding box into a view area of
+    /// `view_area_size` pixels.
+    pub fn view_area_transformer(&self, view_area_size: Vec2d) -> ViewAreaTransformer {
+        ViewAreaTransformer::new(view_area_size, self.min, self.max)
+    }
+}
+
+/// Returns the smallest bounding box that contains every line the program draws, or `None` if
+/// it does not draw anything.
+///
+/// This runs the entire program, so it takes about as long as drawing it.
+pub fn bounding_box(program: &dyn TurtleProgram) -> Option<BoundingBox> {
+    let mut turtle = BoundingBoxTurtle {
+        state: TurtleState::new(),
+        bounds: None,
+    };
+    for step in program.init_turtle() {
+        turtle.perform(step);
+    }
+    for step in program.turtle_program_iter() {
+        turtle.perform(step);
+    }
+    turtle.bounds
+}
+
+/// The area that a renderer should show in order to fit the entire program, with a
+/// `VIEW_MARGIN` around it. Uses `DEFAULT_VIEW` if the program does not draw anything.
+pub fn view_area(program: &dyn TurtleProgram) -> BoundingBox {
+    bounding_box(program)
+        .map(|bounds| bounds.with_margin(VIEW_MARGIN))
+        .unwrap_or(DEFAULT_VIEW)
+}
+
+/// A turtle that only keeps track of where it draws.
+struct BoundingBoxTurtle {
+    state: TurtleState,
+    bounds: Option<BoundingBox>,
+}
+
+impl BoundingBoxTurtle {
+    fn include(&mut self, point: Point) {
+        match self.bounds {
+            Some(ref mut bounds) => bounds.include(point),
+            None => self.bounds = Some(BoundingBox::at(point)),
+        }
+    }
+}
+
+impl Turtle for BoundingBoxTurtle {
+    fn forward(&mut self, distance: f64) {
+        let old_pos = self.state.position;
+        let new_pos = old_pos.point_at(Vector {
+            direction: self.state.angle,
+            magnitude: distance,
+        });
+        if self.state.down {
+            self.include(old_pos);
+            self.include(new_pos);
+        }
+        self.state.position = new_pos;
+    }
+
+    fn set_pos(&mut self, new_pos: Point) {
+        self.state.position = new_pos;
+    }
+
+    fn set_rad(&mut self, new_rad: f64) {
+        self.state.angle = new_rad;
+    }
+
+    fn turn_rad(&mut self, radians: f64) {
+        self.state.angle += radians;
+    }
+
+    fn down(&mut self) {
+        self.state.down = true;
+    }
+
+    fn up(&mut self) {
+        self.state.down = false;
+    }
+
+    fn set_pen_color(&mut self, _color: ColorF32) {}
+
+    fn set_pen_width(&mut self, _width: f64) {}
+
+    fn set_pen_dash(&mut self, _pattern: Vec<f64>) {}
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        self.state.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::dragon::DragonFractal;
+    use crate::turtle::{TurtleProgramIterator, TurtleStep};
+    use std::f64::consts::PI;
+
+    /// Draws an L shape, then moves away without drawing.
+    struct TestProgram;
+
+    impl TurtleProgram for TestProgram {
+        fn init_turtle(&self) -> Vec<TurtleStep> {
+            vec![
+                TurtleStep::SetPos(Point { x: 1.0, y: 1.0 }),
+                TurtleStep::Down,
+            ]
+        }
+
+        fn turtle_program_iter(&self) -> TurtleProgramIterator {
+            TurtleProgramIterator::new(Box::new(
+                vec![
+                    TurtleStep::Forward(2.0),
+                    TurtleStep::Push,
+                    TurtleStep::TurnRad(-PI / 2.0),
+                    TurtleStep::Forward(1.0),
+                    TurtleStep::Pop,
+                    TurtleStep::Up,
+                    TurtleStep::Forward(10.0),
+                ]
+                .into_iter(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounds = bounding_box(&TestProgram).unwrap();
+        assert_point_eq!(bounds.min, Point { x: 1.0, y: 0.0 }, 0.000000001);
+        assert_point_eq!(bounds.max, Point { x: 3.0, y: 1.0 }, 0.000000001);
+        assert_eq!(
+            bounds.with_margin(0.25),
+            BoundingBox {
+                min: Point { x: 0.5, y: -0.5 },
+                max: Point { x: 3.5, y: 1.5 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_dragon() {
+        // The first iteration of the dragon curve is a right angle from (0,0) to (1,0).
+        let bounds = bounding_box(&DragonFractal::new(1)).unwrap();
+        assert_point_eq!(bounds.min, Point { x: 0.0, y: -0.5 }, 0.000000001);
+        assert_point_eq!(bounds.max, Point { x: 1.0, y: 0.0 }, 0.000000001);
+    }
+
+    #[test]
+    fn test_empty() {
+        struct Empty;
+
+        impl TurtleProgram for Empty {
+            fn init_turtle(&self) -> Vec<TurtleStep> {
+                vec![TurtleStep::SetPos(Point { x: 4.0, y: 4.0 })]
+            }
+
+            fn turtle_program_iter(&self) -> TurtleProgramIterator {
+                TurtleProgramIterator::new(Box::new(vec![].into_iter()))
+            }
+        }
+
+        assert_eq!(bounding_box(&Empty), None);
+        assert_eq!(view_area(&Empty), DEFAULT_VIEW);
+    }
+
+    #[test]
+    fn test_with_margin_single_point() {
+        let bounds = BoundingBox::at(Point { x: 1.0, y: 1.0 }).with_margin(VIEW_MARGIN);
+        assert_eq!(bounds.width(), 1.0);
+        assert_eq!(bounds.height(), 1.0);
+    }
+}
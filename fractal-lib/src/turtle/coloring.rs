@@ -0,0 +1,576 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coloring the line segments of a turtle program.
+//!
+//! `ColoredTurtleProgram` wraps another `TurtleProgram` and inserts `TurtleStep::SetPenColor`
+//! steps in front of its `TurtleStep::Forward`s, so any `Turtle` that honors pen colors can draw
+//! it.
+
+use super::{
+    seek_by_replay, SegmentInfo, TurtleProgram, TurtleProgramIterator, TurtleSeek, TurtleStep,
+};
+use crate::color::palette::Palette;
+use crate::color::ColorF32;
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The ways that `SegmentColoring` can pick a color for a segment.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum SegmentColoringMode {
+    Solid,
+    Progress,
+    Symbol,
+    Depth,
+}
+
+/// How to color each line segment of a turtle program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SegmentColoring {
+    /// Every segment uses the same color.
+    Solid(ColorF32),
+    /// A gradient along the palette from the first segment to the last.
+    Progress(Palette),
+    /// Each kind of symbol that draws segments gets its own color from the palette.
+    Symbol(Palette),
+    /// Segments are colored by `SegmentInfo::depth`, from the start of the palette for the
+    /// shallowest depth to the end for the deepest.
+    Depth(Palette),
+}
+
+impl SegmentColoring {
+    /// Builds the coloring for a mode. `Solid` uses `solid_color`, and the other modes use
+    /// `palette`.
+    pub fn new(mode: SegmentColoringMode, solid_color: ColorF32, palette: Palette) -> Self {
+        match mode {
+            SegmentColoringMode::Solid => SegmentColoring::Solid(solid_color),
+            SegmentColoringMode::Progress => SegmentColoring::Progress(palette),
+            SegmentColoringMode::Symbol => SegmentColoring::Symbol(palette),
+            SegmentColoringMode::Depth => SegmentColoring::Depth(palette),
+        }
+    }
+}
+
+/// Decorates a `TurtleProgram` so that its segments are drawn according to a `SegmentColoring`.
+///
+/// `Symbol` and `Depth` rely on `TurtleProgram::segment_info()`. Programs that do not provide it
+/// are drawn using the first color of the palette.
+pub struct ColoredTurtleProgram<P: TurtleProgram> {
+    program: P,
+    coloring: SegmentColoring,
+    /// How many segments the program draws. Only counted for `SegmentColoring::Progress`, which
+    /// needs it for every pass.
+    segments: u64,
+}
+
+impl<P: TurtleProgram> ColoredTurtleProgram<P> {
+    /// `SegmentColoring::Progress` runs `program` once here to count its segments.
+    pub fn new(program: P, coloring: SegmentColoring) -> ColoredTurtleProgram<P> {
+        let segments = match coloring {
+            SegmentColoring::Progress(_) => program
+                .turtle_program_iter()
+                .filter(|step| matches!(step, TurtleStep::Forward(_)))
+                .count() as u64,
+            _ => 0,
+        };
+        ColoredTurtleProgram {
+            program,
+            coloring,
+            segments,
+        }
+    }
+
+    /// Returns a closure that will construct a colored version of the `TurtleProgram` built by
+    /// `ctor`.
+    pub fn build<C>(ctor: C, coloring: SegmentColoring) -> impl Fn(u64) -> ColoredTurtleProgram<P>
+    where
+        C: Fn(u64) -> P,
+    {
+        move |iteration| ColoredTurtleProgram::new(ctor(iteration), coloring.clone())
+    }
+
+    /// Builds the source of colors for a new pass through the program.
+    fn segment_colors(&self) -> SegmentColors {
+        match &self.coloring {
+            SegmentColoring::Solid(color) => SegmentColors::Constant(*color),
+            SegmentColoring::Progress(palette) => SegmentColors::Progress {
+                palette: palette.clone(),
+                scale: self.segments.saturating_sub(1).max(1) as f64,
+                count: self.segments,
+                segment: 0,
+            },
+            SegmentColoring::Symbol(palette) | SegmentColoring::Depth(palette) => {
+                let by_depth = matches!(self.coloring, SegmentColoring::Depth(_));
+                match self.program.segment_info() {
+                    None => SegmentColors::Constant(palette.color_at(0.0).into()),
+                    Some(infos) => {
+                        let max = infos
+                            .map(|info| info_value(info, by_depth))
+                            .max()
+                            .unwrap_or(0);
+                        SegmentColors::Info {
+                            palette: palette.clone(),
+                            scale: max.max(1) as f64,
+                            infos: self.program.segment_info().unwrap(),
+                            by_depth,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<P: TurtleProgram> TurtleProgram for ColoredTurtleProgram<P> {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        self.program.init_turtle()
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        TurtleProgramIterator::new(Box::new(ColoredTurtleProgramIterator {
+            steps: self.program.turtle_program_iter(),
+            colors: self.segment_colors(),
+            pending: None,
+            last_color: None,
+            saved_colors: Vec::new(),
+        }))
+    }
+
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        self.program.segment_info()
+    }
+
+    /// Seeks the undecorated program, and then skips the colors of the skipped segments. Seeking
+    /// into a branch replays the program instead, since the colors saved by each
+    /// `TurtleStep::Push` depend on the segments drawn before it.
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        let TurtleSeek { mut state, rest } = self.program.seek(moves);
+        if !state.stack.is_empty() {
+            return seek_by_replay(self, moves);
+        }
+        let mut colors = self.segment_colors();
+        let last_color = colors.skip(moves);
+        if let Some(color) = last_color {
+            state.color = color;
+        }
+        TurtleSeek {
+            state,
+            rest: TurtleProgramIterator::new(Box::new(ColoredTurtleProgramIterator {
+                steps: rest,
+                colors,
+                pending: None,
+                last_color,
+                saved_colors: Vec::new(),
+            })),
+        }
+    }
+}
+
+/// Produces the color for each segment, in order.
+enum SegmentColors {
+    Constant(ColorF32),
+    Progress {
+        palette: Palette,
+        scale: f64,
+        /// How many segments there are.
+        count: u64,
+        segment: u64,
+    },
+    Info {
+        palette: Palette,
+        scale: f64,
+        infos: Box<dyn Iterator<Item = SegmentInfo>>,
+        by_depth: bool,
+    },
+}
+
+/// The part of `info` that is being colored.
+fn info_value(info: SegmentInfo, by_depth: bool) -> u64 {
+    if by_depth {
+        info.depth
+    } else {
+        info.symbol as u64
+    }
+}
+
+impl SegmentColors {
+    /// Skips the colors of the next `segments` segments, returning the last one skipped.
+    fn skip(&mut self, segments: u64) -> Option<ColorF32> {
+        if segments == 0 {
+            return None;
+        }
+        match self {
+            SegmentColors::Constant(color) => Some(*color),
+            SegmentColors::Progress { count, segment, .. } => {
+                let skipped = segments.min(*count);
+                if skipped == 0 {
+                    return None;
+                }
+                *segment += skipped - 1;
+                Some(self.next_color())
+            }
+            SegmentColors::Info {
+                palette,
+                scale,
+                infos,
+                by_depth,
+            } => {
+                let info = infos.by_ref().take(segments as usize).last()?;
+                Some(
+                    palette
+                        .color_at(info_value(info, *by_depth) as f64 / *scale)
+                        .into(),
+                )
+            }
+        }
+    }
+
+    fn next_color(&mut self) -> ColorF32 {
+        match self {
+            SegmentColors::Constant(color) => *color,
+            SegmentColors::Progress {
+                palette,
+                scale,
+                segment,
+                ..
+            } => {
+                let position = *segment as f64 / *scale;
+                *segment += 1;
+                palette.color_at(position).into()
+            }
+            SegmentColors::Info {
+                palette,
+                scale,
+                infos,
+                by_depth,
+            } => {
+                let info = infos.next().unwrap_or(SegmentInfo {
+                    symbol: 0,
+                    depth: 0,
+                });
+                palette
+                    .color_at(info_value(info, *by_depth) as f64 / *scale)
+                    .into()
+            }
+        }
+    }
+}
+
+/// Iterator that emits a `TurtleStep::SetPenColor` before every `TurtleStep::Forward` that needs
+/// a different color than the previous one.
+struct ColoredTurtleProgramIterator {
+    steps: TurtleProgramIterator,
+    colors: SegmentColors,
+    /// A forward step that is waiting on its color to be emitted first.
+    pending: Option<TurtleStep>,
+    last_color: Option<ColorF32>,
+    /// `last_color` at each `TurtleStep::Push`, since a `TurtleStep::Pop` restores the pen.
+    saved_colors: Vec<Option<ColorF32>>,
+}
+
+impl Iterator for ColoredTurtleProgramIterator {
+    type Item = TurtleStep;
+
+    fn next(&mut self) -> Option<TurtleStep> {
+        if let Some(step) = self.pending.take() {
+            return Some(step);
+        }
+        let step = self.steps.next()?;
+        match step {
+            TurtleStep::Forward(_) => {
+                let color = self.colors.next_color();
+                if self.last_color != Some(color) {
+                    self.last_color = Some(color);
+                    self.pending = Some(step);
+                    return Some(TurtleStep::SetPenColor(color));
+                }
+            }
+            TurtleStep::Push => self.saved_colors.push(self.last_color),
+            TurtleStep::Pop => {
+                if let Some(color) = self.saved_colors.pop() {
+                    self.last_color = color;
+                }
+            }
+            _ => {}
+        }
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::palette::ColorStop;
+    use crate::color::{ColorU8, BLACK_F32};
+    use crate::curves::dragon::DragonFractal;
+    use crate::curves::fractalplant::FractalPlant;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Draws three segments, where the middle one is a different symbol and deeper.
+    struct TestProgram;
+
+    impl TurtleProgram for TestProgram {
+        fn init_turtle(&self) -> Vec<TurtleStep> {
+            vec![TurtleStep::Down]
+        }
+
+        fn turtle_program_iter(&self) -> TurtleProgramIterator {
+            TurtleProgramIterator::new(Box::new(
+                vec![
+                    TurtleStep::Forward(1.0),
+                    TurtleStep::TurnRad(1.0),
+                    TurtleStep::Forward(1.0),
+                    TurtleStep::Forward(1.0),
+                ]
+                .into_iter(),
+            ))
+        }
+
+        fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+            Some(Box::new(
+                vec![
+                    SegmentInfo {
+                        symbol: 0,
+                        depth: 0,
+                    },
+                    SegmentInfo {
+                        symbol: 1,
+                        depth: 2,
+                    },
+                    SegmentInfo {
+                        symbol: 0,
+                        depth: 1,
+                    },
+                ]
+                .into_iter(),
+            ))
+        }
+    }
+
+    fn black_to_white() -> Palette {
+        Palette::new(vec![
+            ColorStop::new(0.0, ColorU8([0, 0, 0, 255])),
+            ColorStop::new(1.0, ColorU8([255, 255, 255, 255])),
+        ])
+    }
+
+    fn grey(level: u8) -> TurtleStep {
+        TurtleStep::SetPenColor(ColorU8([level, level, level, 255]).into())
+    }
+
+    #[test]
+    fn test_solid() {
+        let program = ColoredTurtleProgram::new(TestProgram, SegmentColoring::Solid(BLACK_F32));
+        assert_eq!(program.init_turtle(), vec![TurtleStep::Down]);
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![
+                TurtleStep::SetPenColor(BLACK_F32),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(1.0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::Forward(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        let program =
+            ColoredTurtleProgram::new(TestProgram, SegmentColoring::Progress(black_to_white()));
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![
+                grey(0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(1.0),
+                grey(128),
+                TurtleStep::Forward(1.0),
+                grey(255),
+                TurtleStep::Forward(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_progress_counts_once() {
+        /// Counts how many times the program is run.
+        struct Counted(Rc<Cell<usize>>);
+
+        impl TurtleProgram for Counted {
+            fn init_turtle(&self) -> Vec<TurtleStep> {
+                TestProgram.init_turtle()
+            }
+
+            fn turtle_program_iter(&self) -> TurtleProgramIterator {
+                self.0.set(self.0.get() + 1);
+                TestProgram.turtle_program_iter()
+            }
+        }
+
+        let runs = Rc::new(Cell::new(0));
+        let program = ColoredTurtleProgram::new(
+            Counted(runs.clone()),
+            SegmentColoring::Progress(black_to_white()),
+        );
+        assert_eq!(runs.get(), 1);
+        for _ in 0..3 {
+            assert_eq!(program.turtle_program_iter().count(), 7);
+        }
+        assert_eq!(runs.get(), 4, "each pass runs the program once");
+    }
+
+    #[test]
+    fn test_symbol() {
+        let program =
+            ColoredTurtleProgram::new(TestProgram, SegmentColoring::Symbol(black_to_white()));
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![
+                grey(0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(1.0),
+                grey(255),
+                TurtleStep::Forward(1.0),
+                grey(0),
+                TurtleStep::Forward(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        let program =
+            ColoredTurtleProgram::new(TestProgram, SegmentColoring::Depth(black_to_white()));
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![
+                grey(0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(1.0),
+                grey(255),
+                TurtleStep::Forward(1.0),
+                grey(128),
+                TurtleStep::Forward(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_branches() {
+        /// Draws a segment within a branch, and then the same segment after the branch.
+        struct Branching;
+
+        impl TurtleProgram for Branching {
+            fn init_turtle(&self) -> Vec<TurtleStep> {
+                vec![]
+            }
+
+            fn turtle_program_iter(&self) -> TurtleProgramIterator {
+                TurtleProgramIterator::new(Box::new(
+                    vec![
+                        TurtleStep::Forward(1.0),
+                        TurtleStep::Push,
+                        TurtleStep::Forward(1.0),
+                        TurtleStep::Pop,
+                        TurtleStep::Forward(1.0),
+                    ]
+                    .into_iter(),
+                ))
+            }
+
+            fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+                Some(Box::new(
+                    [0, 1, 1]
+                        .iter()
+                        .map(|&symbol| SegmentInfo { symbol, depth: 0 }),
+                ))
+            }
+        }
+
+        // the pop restores the pen to black, so the last segment needs to set white again
+        let program =
+            ColoredTurtleProgram::new(Branching, SegmentColoring::Symbol(black_to_white()));
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![
+                grey(0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::Push,
+                grey(255),
+                TurtleStep::Forward(1.0),
+                TurtleStep::Pop,
+                grey(255),
+                TurtleStep::Forward(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_segment_info() {
+        /// Only draws one segment, and has no segment info.
+        struct Plain;
+
+        impl TurtleProgram for Plain {
+            fn init_turtle(&self) -> Vec<TurtleStep> {
+                vec![]
+            }
+
+            fn turtle_program_iter(&self) -> TurtleProgramIterator {
+                TurtleProgramIterator::new(Box::new(vec![TurtleStep::Forward(1.0)].into_iter()))
+            }
+        }
+
+        let program = ColoredTurtleProgram::new(Plain, SegmentColoring::Depth(black_to_white()));
+        assert_eq!(
+            program.turtle_program_iter().collect::<Vec<TurtleStep>>(),
+            vec![grey(0), TurtleStep::Forward(1.0)]
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let colorings = vec![
+            SegmentColoring::Solid(BLACK_F32),
+            SegmentColoring::Progress(black_to_white()),
+            SegmentColoring::Depth(black_to_white()),
+        ];
+        for coloring in colorings {
+            let program = ColoredTurtleProgram::new(DragonFractal::new(4), coloring);
+            for moves in 0..=17 {
+                let seek = program.seek(moves);
+                let replay = seek_by_replay(&program, moves);
+                assert_eq!(seek.state.color, replay.state.color);
+                assert_eq!(
+                    seek.rest.collect::<Vec<TurtleStep>>(),
+                    replay.rest.collect::<Vec<TurtleStep>>()
+                );
+            }
+        }
+
+        // seeking into a branch replays the colors saved by the branch
+        let plant = ColoredTurtleProgram::new(
+            LindenmayerSystemTurtleProgram::new(FractalPlant::new(3)),
+            SegmentColoring::Depth(black_to_white()),
+        );
+        let seek = plant.seek(20);
+        assert!(!seek.state.stack.is_empty());
+        assert_eq!(
+            seek.rest.collect::<Vec<TurtleStep>>(),
+            seek_by_replay(&plant, 20).rest.collect::<Vec<TurtleStep>>()
+        );
+    }
+}
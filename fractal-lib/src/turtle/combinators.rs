@@ -0,0 +1,360 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Combinators that build composite figures out of other turtle programs.
+//!
+//! For example, four Lévy C curves, each rotated a quarter turn about the center of the square
+//! they form, make a closed figure:
+//!
+//! ```
+//! use fractal_lib::curves::levyccurve::LevyCCurve;
+//! use fractal_lib::geometry::Point;
+//! use fractal_lib::lindenmayer::LindenmayerSystemTurtleProgram;
+//! use fractal_lib::turtle::combinators::Concatenated;
+//! use std::f64::consts::PI;
+//!
+//! let square = Concatenated::repeated(
+//!     LindenmayerSystemTurtleProgram::new(LevyCCurve::new(8)),
+//!     4,
+//!     -PI / 2.0,
+//!     Point { x: 0.25, y: -0.25 },
+//! );
+//! ```
+
+use super::{
+    ProgramSize, SegmentInfo, TurtleProgram, TurtleProgramIterator, TurtleState, TurtleStep,
+};
+use crate::geometry::{
+    reflection_across, rotation_about, AffineTransform, CartesianAffineTransform, Point, Vector,
+};
+use std::rc::Rc;
+
+/// Draws another program with an affine transform applied to everything it draws.
+///
+/// Transforms that stretch, shear, or mirror the drawing change the angles between lines, so
+/// instead of transforming turns, each `Forward` is preceded by a `SetRad` that points the turtle
+/// along the transformed line. Pen widths and dash patterns are not transformed.
+pub struct Transformed<P: TurtleProgram> {
+    program: P,
+    transform: CartesianAffineTransform,
+}
+
+impl<P: TurtleProgram> Transformed<P> {
+    pub fn new(program: P, transform: CartesianAffineTransform) -> Transformed<P> {
+        Transformed { program, transform }
+    }
+
+    /// Rotates `program` by `radians` (counter-clockwise) about `center`.
+    pub fn rotated(program: P, radians: f64, center: Point) -> Transformed<P> {
+        Transformed::new(program, rotation_about(center, radians))
+    }
+
+    /// Mirrors `program` across the line through `through` at an angle of `radians` from the X
+    /// axis.
+    pub fn mirrored(program: P, through: Point, radians: f64) -> Transformed<P> {
+        Transformed::new(program, reflection_across(through, radians))
+    }
+
+    /// The state of the untransformed turtle once `program` has initialized it.
+    fn initial_state(&self) -> TurtleState {
+        let mut state = TurtleState::new();
+        for step in self.program.init_turtle() {
+            transform_step(&self.transform, &mut state, step);
+        }
+        state
+    }
+}
+
+/// Follows `step` with `state`, the untransformed turtle, and returns the steps that draw the
+/// same thing with `transform` applied.
+fn transform_step(
+    transform: &CartesianAffineTransform,
+    state: &mut TurtleState,
+    step: TurtleStep,
+) -> Vec<TurtleStep> {
+    match step {
+        TurtleStep::Forward(distance) => {
+            let start = state.position;
+            state.position = start.point_at(Vector {
+                direction: state.angle,
+                magnitude: distance,
+            });
+            let start = transform.transform(start);
+            let end = transform.transform(state.position);
+            vec![
+                TurtleStep::SetRad((end.y - start.y).atan2(end.x - start.x)),
+                TurtleStep::Forward(start.distance_to(end)),
+            ]
+        }
+        TurtleStep::SetPos(point) => {
+            state.position = point;
+            vec![TurtleStep::SetPos(transform.transform(point))]
+        }
+        TurtleStep::SetRad(angle) => {
+            state.angle = angle;
+            vec![]
+        }
+        TurtleStep::TurnRad(angle) => {
+            state.angle += angle;
+            vec![]
+        }
+        TurtleStep::Push => {
+            state.push();
+            vec![step]
+        }
+        TurtleStep::Pop => {
+            state.pop();
+            vec![step]
+        }
+        _ => vec![step],
+    }
+}
+
+impl<P: TurtleProgram> TurtleProgram for Transformed<P> {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        let mut state = TurtleState::new();
+        self.program
+            .init_turtle()
+            .into_iter()
+            .flat_map(|step| transform_step(&self.transform, &mut state, step))
+            .collect()
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        let transform = self.transform;
+        let mut state = self.initial_state();
+        TurtleProgramIterator::new(Box::new(
+            self.program
+                .turtle_program_iter()
+                .flat_map(move |step| transform_step(&transform, &mut state, step)),
+        ))
+    }
+
+    /// Every `Forward` is transformed into exactly one `Forward`, so segments keep their info.
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        self.program.segment_info()
+    }
+
+    /// Turns are dropped and each `Forward` gains a `SetRad`, so this is at most one more step per
+    /// segment than `program`.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let size = self.program.predicted_size()?;
+        Some(ProgramSize {
+            steps: size.steps.saturating_add(size.moves),
+            ..size
+        })
+    }
+}
+
+/// Draws several programs, one after another. Each program after the first is initialized by
+/// its own `init_turtle()` steps, so each one draws where it would if it were drawn alone.
+pub struct Concatenated {
+    programs: Vec<Box<dyn TurtleProgram>>,
+}
+
+impl Concatenated {
+    pub fn new(programs: Vec<Box<dyn TurtleProgram>>) -> Concatenated {
+        Concatenated { programs }
+    }
+
+    /// Draws `count` copies of `program`, where each copy is rotated by `radians` (counter-
+    /// clockwise) about `center` from the copy before it. The first copy is not rotated.
+    pub fn repeated<P>(program: P, count: usize, radians: f64, center: Point) -> Concatenated
+    where
+        P: TurtleProgram + 'static,
+    {
+        let program = Rc::new(program);
+        Concatenated::new(
+            (0..count)
+                .map(|i| {
+                    Box::new(Transformed::rotated(
+                        program.clone(),
+                        radians * i as f64,
+                        center,
+                    )) as Box<dyn TurtleProgram>
+                })
+                .collect(),
+        )
+    }
+}
+
+impl TurtleProgram for Concatenated {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        match self.programs.first() {
+            Some(program) => program.init_turtle(),
+            None => vec![],
+        }
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        let mut parts: Vec<Box<dyn Iterator<Item = TurtleStep>>> = vec![];
+        for (i, program) in self.programs.iter().enumerate() {
+            if i > 0 {
+                parts.push(Box::new(program.init_turtle().into_iter()));
+            }
+            parts.push(Box::new(program.turtle_program_iter()));
+        }
+        TurtleProgramIterator::new(Box::new(parts.into_iter().flatten()))
+    }
+
+    /// Only available if every program provides segment info.
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        let infos = self
+            .programs
+            .iter()
+            .map(|program| program.segment_info())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Box::new(infos.into_iter().flatten()))
+    }
+
+    /// Sums the sizes of the programs, and grows as fast as the fastest of them. Only available
+    /// if every program predicts its size.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let mut total = ProgramSize {
+            steps: 0,
+            moves: 0,
+            growth_rate: None,
+        };
+        for (i, program) in self.programs.iter().enumerate() {
+            let size = program.predicted_size()?;
+            if i > 0 {
+                total.steps = total
+                    .steps
+                    .saturating_add(program.init_turtle().len() as u128);
+            }
+            total.steps = total.steps.saturating_add(size.steps);
+            total.moves = total.moves.saturating_add(size.moves);
+            total.growth_rate = match (total.growth_rate, size.growth_rate) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            };
+        }
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::dragon::DragonFractal;
+    use crate::curves::kochcurve::KochCurve;
+    use crate::curves::levyccurve::LevyCCurve;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::polyline::{self, Polyline};
+    use std::f64::consts::PI;
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            actual.distance_to(expected) < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    fn forwards(program: &dyn TurtleProgram) -> usize {
+        program
+            .turtle_program_iter()
+            .filter(|step| matches!(step, TurtleStep::Forward(_)))
+            .count()
+    }
+
+    #[test]
+    fn test_transformed() {
+        let koch = || LindenmayerSystemTurtleProgram::new(KochCurve::new(2));
+        let original: Vec<Point> = polyline::record(&koch()).concat();
+        // stretch horizontally, squash vertically, and move up
+        let stretched = Transformed::new(koch(), [[2.0, 0.0, 0.0], [0.0, 0.5, 1.0]]);
+        let transformed: Vec<Point> = polyline::record(&stretched).concat();
+        assert_eq!(original.len(), transformed.len());
+        for (original, transformed) in original.iter().zip(transformed) {
+            assert_close(
+                transformed,
+                Point {
+                    x: original.x * 2.0,
+                    y: original.y * 0.5 + 1.0,
+                },
+            );
+        }
+        assert_eq!(forwards(&stretched), forwards(&koch()));
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let levy = || LindenmayerSystemTurtleProgram::new(LevyCCurve::new(4));
+        let original: Vec<Point> = polyline::record(&levy()).concat();
+        let mirrored = Transformed::mirrored(levy(), Point { x: 0.0, y: 0.0 }, 0.0);
+        let mirrored: Vec<Point> = polyline::record(&mirrored).concat();
+        for (original, mirrored) in original.iter().zip(mirrored) {
+            assert_close(
+                mirrored,
+                Point {
+                    x: original.x,
+                    y: -original.y,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_closed_levy_curves() {
+        let square = Concatenated::repeated(
+            LindenmayerSystemTurtleProgram::new(LevyCCurve::new(4)),
+            4,
+            -PI / 2.0,
+            Point { x: 0.25, y: -0.25 },
+        );
+        let sides: Vec<Polyline> = polyline::record(&square);
+        assert_eq!(sides.len(), 4);
+        // each side starts where the last one ended, closing the figure
+        for i in 0..4 {
+            let end = sides[i][sides[i].len() - 1];
+            assert_close(sides[(i + 1) % 4][0], end);
+        }
+        assert_close(sides[2][0], Point { x: 0.5, y: -0.5 });
+        assert_eq!(forwards(&square), 4 * 16);
+    }
+
+    #[test]
+    fn test_dragon_tiling() {
+        let origin = Point { x: 0.0, y: 0.0 };
+        let tiles = Concatenated::repeated(DragonFractal::new(3), 4, PI / 2.0, origin);
+        let dragons = polyline::record(&tiles);
+        assert_eq!(dragons.len(), 4);
+        let ends: Vec<Point> = dragons.iter().map(|d| d[d.len() - 1]).collect();
+        assert_close(ends[0], Point { x: 1.0, y: 0.0 });
+        assert_close(ends[1], Point { x: 0.0, y: 1.0 });
+        assert_close(ends[2], Point { x: -1.0, y: 0.0 });
+        assert_close(ends[3], Point { x: 0.0, y: -1.0 });
+        for dragon in dragons {
+            assert_close(dragon[0], origin);
+        }
+        assert_eq!(
+            tiles.segment_info().unwrap().count(),
+            forwards(&tiles),
+            "segment info lines up with the concatenated segments"
+        );
+        let size = tiles.predicted_size().unwrap();
+        assert_eq!(size.moves, forwards(&tiles) as u128);
+        assert!(size.steps >= tiles.turtle_program_iter().count() as u128);
+        assert_eq!(size.growth_rate, Some(2.0));
+    }
+
+    #[test]
+    fn test_empty() {
+        let nothing = Concatenated::new(vec![]);
+        assert!(nothing.init_turtle().is_empty());
+        assert_eq!(nothing.turtle_program_iter().count(), 0);
+    }
+}
@@ -0,0 +1,754 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Turtle program abstractions.
+
+pub mod bounds;
+pub mod coloring;
+pub mod combinators;
+pub mod plotter;
+pub mod polyline;
+pub mod svg;
+
+use crate::color::{self, ColorF32};
+use crate::geometry::{deg2rad, Point, Vector};
+use std::mem;
+use std::rc::Rc;
+
+/// A Turtle is an abstraction for drawing lines in a space. It has a position and it faces a
+/// particular direction. A program usually tells a turtle to move forward based upon its facing,
+/// to change direction, and to start or stop drawing.
+///
+/// The implementation must implement `set_rad()` and `turn_rad()` itself, while it gets
+/// `set_deg()` and `turn_deg()` for free.
+pub trait Turtle {
+    /// How far the turtle should move forward.
+    fn forward(&mut self, distance: f64);
+
+    /// Move the turtle to the specified coordinates.
+    fn set_pos(&mut self, new_pos: Point);
+
+    /// Set the turtle's direction.
+    fn set_deg(&mut self, new_deg: f64) {
+        self.set_rad(deg2rad(new_deg));
+    }
+
+    fn set_rad(&mut self, new_rad: f64);
+
+    /// Rotate the turtle, in degrees.
+    ///
+    /// Positive values turn the turtle "left" or counter-clockwise. Negative values turn the
+    /// turtle "right" or clockwise.
+    fn turn_deg(&mut self, degrees: f64) {
+        self.turn_rad(deg2rad(degrees));
+    }
+
+    /// Convenience method for rotating the turtle in radians instead of degrees.
+    ///
+    /// 2*PI radians = 360 degrees.
+    fn turn_rad(&mut self, radians: f64);
+
+    /// Touch the turtle's pen to the drawing surface.
+    fn down(&mut self);
+
+    /// Lift the turtle's pen off of the drawing surface.
+    fn up(&mut self);
+
+    /// Change the color that the turtle draws with.
+    fn set_pen_color(&mut self, color: ColorF32);
+
+    /// Change the width of the turtle's pen, in pixels.
+    fn set_pen_width(&mut self, width: f64);
+
+    /// Change the dash pattern that the turtle draws with (see `TurtleStep::SetPenDash`).
+    fn set_pen_dash(&mut self, pattern: Vec<f64>);
+
+    /// Save the turtle's position, direction, and pen onto a stack.
+    fn push(&mut self);
+
+    /// Restore the turtle's most recently pushed position, direction, and pen.
+    fn pop(&mut self);
+
+    /// Perform the action represented by `step`.
+    fn perform(&mut self, step: TurtleStep) {
+        match step {
+            TurtleStep::Forward(dist) => self.forward(dist),
+            TurtleStep::SetPos(point) => self.set_pos(point),
+            TurtleStep::SetRad(angle) => self.set_rad(angle),
+            TurtleStep::TurnRad(angle) => self.turn_rad(angle),
+            TurtleStep::Down => self.down(),
+            TurtleStep::Up => self.up(),
+            TurtleStep::SetPenColor(color) => self.set_pen_color(color),
+            TurtleStep::SetPenWidth(width) => self.set_pen_width(width),
+            TurtleStep::SetPenDash(pattern) => self.set_pen_dash(pattern),
+            TurtleStep::Push => self.push(),
+            TurtleStep::Pop => self.pop(),
+        }
+    }
+}
+
+/// Represents the possible actions that a `TurtleProgram` can perform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurtleStep {
+    /// Make the turtle move forward some distance in the coordinate system.
+    Forward(f64),
+    /// Move the turtle to the specified Point in the coordinate system.
+    SetPos(Point),
+    /// Set the turtle's angle. 0 and 2π are facing towards the positive X direction.
+    SetRad(f64),
+    /// Rotate the turtle the specified amount in radians.
+    TurnRad(f64),
+    /// Touch the turtle's pen to the drawing surface.
+    Down,
+    /// Lift the turtle's pen off of the drawing surface.
+    Up,
+    /// Change the color that the turtle draws with.
+    SetPenColor(ColorF32),
+    /// Change the width of the turtle's pen, in pixels.
+    SetPenWidth(f64),
+    /// Change the dash pattern of the turtle's pen. The pattern alternates between the lengths
+    /// (in pixels) of drawn and skipped parts of a line, and repeats along the turtle's path. An
+    /// empty pattern draws solid lines.
+    SetPenDash(Vec<f64>),
+    /// Save the turtle's position, direction, and pen, such as at the start of a branch.
+    Push,
+    /// Return the turtle to the position, direction, and pen saved by the matching `Push`,
+    /// without drawing. Does nothing if nothing has been pushed.
+    Pop,
+}
+
+/// The width, in pixels, of a new turtle's pen.
+pub const DEFAULT_PEN_WIDTH: f64 = 1.0;
+
+/// Internal state of a turtle. Can be used by turtle implementations to store/pause their drawing.
+#[derive(Clone, Debug)]
+pub struct TurtleState {
+    pub position: Point,
+    pub angle: f64,
+    pub down: bool,
+    pub color: ColorF32,
+    pub width: f64,
+    /// The current dash pattern, which is empty for solid lines. Use `set_dash()` to change it.
+    pub dash: Vec<f64>,
+    /// How far into `dash` the turtle's pen is, in pixels.
+    pub dash_offset: f64,
+    /// States saved by `push()`.
+    pub stack: Vec<TurtleState>,
+}
+
+impl TurtleState {
+    /// Initializes a new TurtleState. A new turtle starts at (0,0), faces towards the positive X
+    /// axis, and draws solid black lines that are `DEFAULT_PEN_WIDTH` wide.
+    pub fn new() -> TurtleState {
+        TurtleState {
+            position: Point { x: 0.0, y: 0.0 },
+            angle: 0.0,
+            down: true,
+            color: color::BLACK_F32,
+            width: DEFAULT_PEN_WIDTH,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Saves a copy of everything but the stack onto the stack.
+    pub fn push(&mut self) {
+        let stack = mem::take(&mut self.stack);
+        let saved = self.clone();
+        self.stack = stack;
+        self.stack.push(saved);
+    }
+
+    /// Restores the most recently pushed state. Returns false, without changing anything, if
+    /// the stack is empty.
+    pub fn pop(&mut self) -> bool {
+        match self.stack.pop() {
+            Some(saved) => {
+                let stack = mem::take(&mut self.stack);
+                *self = saved;
+                self.stack = stack;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Changes the dash pattern and restarts it. Like an HTML canvas, a pattern with an odd
+    /// number of lengths is repeated to make it even. A pattern with negative or non-finite
+    /// lengths, or that adds up to zero, draws solid lines.
+    pub fn set_dash(&mut self, pattern: Vec<f64>) {
+        let valid = pattern.iter().all(|len| len.is_finite() && *len >= 0.0)
+            && pattern.iter().sum::<f64>() > 0.0;
+        self.dash = if !valid {
+            Vec::new()
+        } else if pattern.len() % 2 == 1 {
+            pattern.iter().chain(pattern.iter()).cloned().collect()
+        } else {
+            pattern
+        };
+        self.dash_offset = 0.0;
+    }
+
+    /// Returns the parts of a line `length` pixels long, starting at the current `dash_offset`,
+    /// that should be drawn. Each part is the distance along the line where it starts and ends.
+    /// Dashed lines that are not finitely long have no parts.
+    pub fn dash_intervals(&self, length: f64) -> Vec<[f64; 2]> {
+        if self.dash.is_empty() {
+            return vec![[0.0, length]];
+        }
+        if !length.is_finite() {
+            // the pattern would never reach the end of the line
+            return Vec::new();
+        }
+        let mut intervals = Vec::new();
+        // find where in the pattern the line starts
+        let mut index = 0;
+        let mut remaining = self.dash[0] - self.dash_offset;
+        while remaining <= 0.0 {
+            index = (index + 1) % self.dash.len();
+            remaining += self.dash[index];
+        }
+        let mut start = 0.0;
+        while start < length {
+            let end = (start + remaining).min(length);
+            if index % 2 == 0 && end > start {
+                intervals.push([start, end]);
+            }
+            start = end;
+            index = (index + 1) % self.dash.len();
+            remaining = self.dash[index];
+        }
+        intervals
+    }
+
+    /// Moves `dash_offset` along by a line that is `length` pixels long. Lines that are not
+    /// finitely long leave it where it is.
+    pub fn advance_dash(&mut self, length: f64) {
+        if !self.dash.is_empty() && length.is_finite() {
+            self.dash_offset = (self.dash_offset + length) % self.dash.iter().sum::<f64>();
+        }
+    }
+}
+
+impl Default for TurtleState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `TurtleState` is also a turtle that keeps track of where it goes without drawing anything.
+/// Since dash patterns are measured in pixels, it does not advance `dash_offset`.
+impl Turtle for TurtleState {
+    fn forward(&mut self, distance: f64) {
+        self.position = self.position.point_at(Vector {
+            direction: self.angle,
+            magnitude: distance,
+        });
+    }
+
+    fn set_pos(&mut self, new_pos: Point) {
+        self.position = new_pos;
+    }
+
+    fn set_rad(&mut self, new_rad: f64) {
+        self.angle = new_rad;
+    }
+
+    fn turn_rad(&mut self, radians: f64) {
+        self.angle += radians;
+    }
+
+    fn down(&mut self) {
+        self.down = true;
+    }
+
+    fn up(&mut self) {
+        self.down = false;
+    }
+
+    fn set_pen_color(&mut self, color: ColorF32) {
+        self.color = color;
+    }
+
+    fn set_pen_width(&mut self, width: f64) {
+        self.width = width;
+    }
+
+    fn set_pen_dash(&mut self, pattern: Vec<f64>) {
+        self.set_dash(pattern);
+    }
+
+    fn push(&mut self) {
+        TurtleState::push(self);
+    }
+
+    fn pop(&mut self) {
+        TurtleState::pop(self);
+    }
+}
+
+/// An object that knows how to draw something using a Turtle. Turtle programs are broken up into
+/// two parts: an initializer method that should place the Turtle into its initial state, and a
+/// method that returns a `TurtleProgramIterator` (which should wrap a Boxed internal iterator
+/// implementation) that yields the sequence of steps for the main turtle program.
+///
+/// This approach adds some extra complexity and scaffolding by requiring an iterator (Rust doesn't
+/// provide something equivalent to a generator function or coroutine yet), but it grants the
+/// renderer renderer a huge amount of flexibility about how to draw/animate the turtle program.
+pub trait TurtleProgram {
+    /// Returns a sequence of steps that initialize the turtle's starting position and angle.
+    fn init_turtle(&self) -> Vec<TurtleStep>;
+
+    /// Should return an iterator object that yields TurtleSteps representing each command the
+    /// turtle will take.
+    fn turtle_program_iter(&self) -> TurtleProgramIterator;
+
+    /// Optionally describes the structure behind each line segment, which can be used to color
+    /// the segments (see `coloring::ColoredTurtleProgram`). If provided, the iterator must yield
+    /// one `SegmentInfo` for each `TurtleStep::Forward` yielded by `turtle_program_iter()`, in
+    /// the same order.
+    ///
+    /// The default implementation returns `None`, for programs that lack any such structure.
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        None
+    }
+
+    /// Skips the first `moves` line segments of the program: every step up to and including the
+    /// `moves`th `TurtleStep::Forward`. Returns the turtle's state at that point (starting from
+    /// `TurtleState::new()` and `init_turtle()`) and the steps that follow. Seeking past the last
+    /// segment ends at the end of the program.
+    ///
+    /// The default implementation replays the program (see `seek_by_replay()`). Programs that can
+    /// work out their state more directly should override it.
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        seek_by_replay(self, moves)
+    }
+
+    /// Predicts how big the program is without running it, for programs that can work that out
+    /// much faster than running them, such as L-systems (see `lindenmayer::growth`).
+    ///
+    /// The default implementation returns `None`.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        None
+    }
+}
+
+impl<P: TurtleProgram + ?Sized> TurtleProgram for Box<P> {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        (**self).init_turtle()
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        (**self).turtle_program_iter()
+    }
+
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        (**self).segment_info()
+    }
+
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        (**self).seek(moves)
+    }
+
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        (**self).predicted_size()
+    }
+}
+
+impl<P: TurtleProgram + ?Sized> TurtleProgram for Rc<P> {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        (**self).init_turtle()
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        (**self).turtle_program_iter()
+    }
+
+    fn segment_info(&self) -> Option<Box<dyn Iterator<Item = SegmentInfo>>> {
+        (**self).segment_info()
+    }
+
+    fn seek(&self, moves: u64) -> TurtleSeek {
+        (**self).seek(moves)
+    }
+
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        (**self).predicted_size()
+    }
+}
+
+/// How big a turtle program is, as predicted by `TurtleProgram::predicted_size()`. Counts
+/// saturate at `u128::MAX`. Programs that rewrite the steps of other programs, such as
+/// `combinators::Transformed`, may only know the most steps they could take.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProgramSize {
+    /// How many steps `turtle_program_iter()` yields.
+    pub steps: u128,
+    /// How many line segments the program draws.
+    pub moves: u128,
+    /// Roughly how many times bigger the program gets with each iteration, for programs that
+    /// draw an iteration of something.
+    pub growth_rate: Option<f64>,
+}
+
+/// A turtle program that has skipped ahead, as returned by `TurtleProgram::seek()`.
+pub struct TurtleSeek {
+    /// The turtle's state after the skipped steps.
+    pub state: TurtleState,
+    /// The rest of the program's steps.
+    pub rest: TurtleProgramIterator,
+}
+
+/// Seeks by running `program` on a `TurtleState` until `moves` line segments have been drawn.
+pub fn seek_by_replay<P: TurtleProgram + ?Sized>(program: &P, moves: u64) -> TurtleSeek {
+    let mut state = TurtleState::new();
+    for step in program.init_turtle() {
+        state.perform(step);
+    }
+    let mut rest = program.turtle_program_iter();
+    let mut skipped = 0;
+    while skipped < moves {
+        match rest.next() {
+            Some(step) => {
+                if let TurtleStep::Forward(_) = step {
+                    skipped += 1;
+                }
+                state.perform(step);
+            }
+            None => break,
+        }
+    }
+    TurtleSeek { state, rest }
+}
+
+/// Describes what produced a single line segment of a `TurtleProgram`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// Which kind of symbol drew the segment, such as an L-system symbol. Kinds are numbered from
+    /// 0 in the order they first appear in the program.
+    pub symbol: usize,
+    /// How deep within the program's recursive structure this segment diverged from the
+    /// previous one. For example, two consecutive segments of a dragon curve that are separated
+    /// by the first fold have a depth of 1, while those separated by the last fold have a depth
+    /// equal to the iteration. The first segment always has a depth of 0.
+    pub depth: u64,
+}
+
+pub struct NullTurtleProgramIterator;
+
+impl Iterator for NullTurtleProgramIterator {
+    type Item = TurtleStep;
+
+    fn next(&mut self) -> Option<TurtleStep> {
+        None
+    }
+}
+
+/// The return type for `TurtleProgram::turtle_program_iter()`. Since Rust does not yet support
+/// abstract return types (eg, a trait return type), the next best thing is a wrapper around a
+/// boxed type.
+///
+/// The Iterator it is initialized with must not have a lifetime, so that the TurtleProgramIterator
+/// can potentially outlive the lifetime the TurtleProgram it is created from. This means the
+/// TurtleProgram probably needs to clone/copy itself and move that copy into the iterator.
+pub struct TurtleProgramIterator {
+    iter: Box<dyn Iterator<Item = TurtleStep>>,
+}
+
+impl<'a> TurtleProgramIterator {
+    pub fn new(iter: Box<dyn Iterator<Item = TurtleStep>>) -> TurtleProgramIterator {
+        TurtleProgramIterator { iter }
+    }
+
+    /// Turns the TurtleProgramIterator into an iterator that will return Vec<TurtleStep>s that
+    /// each contain all steps up to the next TurtleStep::Forward. This allows a renderer to
+    /// render a TurtleProgram in chunks that are broken up by moves that actually draw
+    /// something.
+    pub fn collect_to_next_forward(self) -> TurtleCollectToNextForwardIterator {
+        TurtleCollectToNextForwardIterator { iter: self }
+    }
+}
+
+impl<'a> Iterator for TurtleProgramIterator {
+    type Item = TurtleStep;
+
+    fn next(&mut self) -> Option<TurtleStep> {
+        self.iter.next()
+    }
+}
+
+/// Iterator that yields vectors of `TurtleSteps` until the next `TurtleStep::Forward` or until the
+/// underlying iterator starts yielding None. This allows us to do perform a finite number of
+/// drawing actions at a time.
+pub struct TurtleCollectToNextForwardIterator {
+    iter: TurtleProgramIterator,
+}
+
+impl<'a> TurtleCollectToNextForwardIterator {
+    pub fn new_null_iter() -> TurtleCollectToNextForwardIterator {
+        TurtleProgramIterator::new(Box::new(NullTurtleProgramIterator)).collect_to_next_forward()
+    }
+}
+
+impl<'a> Iterator for TurtleCollectToNextForwardIterator {
+    type Item = Vec<TurtleStep>;
+    fn next(&mut self) -> Option<Vec<TurtleStep>> {
+        let mut retval: Vec<TurtleStep> = Vec::new();
+        loop {
+            match self.iter.next() {
+                None => {
+                    if !retval.is_empty() {
+                        return Some(retval);
+                    } else {
+                        return None;
+                    }
+                }
+                Some(TurtleStep::Forward(f)) => {
+                    // add the forward and return
+                    retval.push(TurtleStep::Forward(f));
+                    return Some(retval);
+                }
+                Some(step) => {
+                    // add the step
+                    retval.push(step);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TurtleCollectToNextForwardIterator;
+    use super::TurtleProgramIterator;
+    use super::TurtleState;
+    use super::TurtleStep;
+    use super::{seek_by_replay, TurtleProgram};
+    use crate::geometry::Point;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_dash_intervals_solid() {
+        let state = TurtleState::new();
+        assert_eq!(state.dash_intervals(5.0), vec![[0.0, 5.0]]);
+    }
+
+    #[test]
+    fn test_dash_intervals() {
+        let mut state = TurtleState::new();
+        state.set_dash(vec![2.0, 1.0]);
+        assert_eq!(
+            state.dash_intervals(7.0),
+            vec![[0.0, 2.0], [3.0, 5.0], [6.0, 7.0]]
+        );
+
+        // the pattern continues from where the previous line stopped
+        state.advance_dash(7.0);
+        assert_eq!(state.dash_offset, 1.0);
+        assert_eq!(state.dash_intervals(4.0), vec![[0.0, 1.0], [2.0, 4.0]]);
+        state.advance_dash(1.0);
+        assert_eq!(state.dash_intervals(1.5), vec![[1.0, 1.5]]);
+
+        assert!(state.dash_intervals(f64::INFINITY).is_empty());
+        assert!(state.dash_intervals(f64::NAN).is_empty());
+        state.advance_dash(f64::INFINITY);
+        assert_eq!(state.dash_offset, 2.0);
+    }
+
+    #[test]
+    fn test_set_dash() {
+        let mut state = TurtleState::new();
+        state.set_dash(vec![1.0, 2.0, 3.0]);
+        assert_eq!(state.dash, vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+        state.advance_dash(4.0);
+        state.set_dash(vec![1.0, -1.0]);
+        assert!(state.dash.is_empty());
+        assert_eq!(state.dash_offset, 0.0);
+        state.set_dash(vec![0.0, 0.0]);
+        assert!(state.dash.is_empty());
+        state.set_dash(vec![0.0, 2.0]);
+        assert!(state.dash_intervals(3.0).is_empty());
+    }
+
+    #[test]
+    fn test_collect_forward_iterator() {
+        let base_iter = TurtleProgramIterator::new(Box::new(
+            vec![
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(9.0),
+                TurtleStep::Forward(2.0),
+            ]
+            .into_iter(),
+        ));
+        let mut test_iter = TurtleCollectToNextForwardIterator { iter: base_iter };
+
+        let first_vec = test_iter.next().expect("no first vector");
+        assert_eq!(first_vec.len(), 1);
+        assert_eq!(
+            *first_vec.get(0).expect("no first_vec[0]"),
+            TurtleStep::Forward(1.0)
+        );
+
+        let second_vec = test_iter.next().expect("no second vector");
+        assert_eq!(second_vec.len(), 2);
+        assert_eq!(
+            *second_vec.get(0).expect("no second_vec[0]"),
+            TurtleStep::TurnRad(9.0)
+        );
+        assert_eq!(
+            *second_vec.get(1).expect("no second_vec[1]"),
+            TurtleStep::Forward(2.0)
+        );
+
+        assert!(test_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_collect_forward_iterator_branches() {
+        let base_iter = TurtleProgramIterator::new(Box::new(
+            vec![
+                TurtleStep::Push,
+                TurtleStep::Forward(1.0),
+                TurtleStep::Pop,
+                TurtleStep::Push,
+                TurtleStep::TurnRad(1.0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::Pop,
+            ]
+            .into_iter(),
+        ));
+        let test_iter = TurtleCollectToNextForwardIterator { iter: base_iter };
+
+        assert_eq!(
+            test_iter.collect::<Vec<Vec<TurtleStep>>>(),
+            vec![
+                vec![TurtleStep::Push, TurtleStep::Forward(1.0)],
+                vec![
+                    TurtleStep::Pop,
+                    TurtleStep::Push,
+                    TurtleStep::TurnRad(1.0),
+                    TurtleStep::Forward(1.0)
+                ],
+                vec![TurtleStep::Pop],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut state = TurtleState::new();
+        state.push();
+        state.position = Point { x: 1.0, y: 2.0 };
+        state.angle = 1.0;
+        state.width = 3.0;
+        state.push();
+        state.position = Point { x: 5.0, y: 5.0 };
+        state.down = false;
+
+        assert!(state.pop());
+        assert_eq!(state.position, Point { x: 1.0, y: 2.0 });
+        assert_eq!(state.angle, 1.0);
+        assert_eq!(state.width, 3.0);
+        assert!(state.down);
+        assert_eq!(state.stack.len(), 1);
+
+        assert!(state.pop());
+        assert_eq!(state.position, Point { x: 0.0, y: 0.0 });
+        assert_eq!(state.width, 1.0);
+        assert!(state.stack.is_empty());
+
+        assert!(!state.pop());
+        assert_eq!(state.position, Point { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_collect_forward_iterator_empty() {
+        let base_iter = TurtleProgramIterator::new(Box::new(vec![].into_iter()));
+        let mut test_iter = TurtleCollectToNextForwardIterator { iter: base_iter };
+
+        assert!(test_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_collect_forward_iterator_actions_after_last_forward() {
+        let base_iter = TurtleProgramIterator::new(Box::new(
+            vec![
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(9.0),
+                TurtleStep::Forward(2.0),
+                TurtleStep::TurnRad(-1.0),
+            ]
+            .into_iter(),
+        ));
+        let mut test_iter = TurtleCollectToNextForwardIterator { iter: base_iter };
+
+        test_iter.next();
+        test_iter.next();
+        let lacks_forward = test_iter.next().expect("no third vec");
+        assert_eq!(lacks_forward.len(), 1);
+        assert_eq!(
+            *lacks_forward.get(0).expect("no lacks_forward[0]"),
+            TurtleStep::TurnRad(-1.0)
+        );
+        assert!(test_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_seek_by_replay() {
+        struct Square;
+
+        impl TurtleProgram for Square {
+            fn init_turtle(&self) -> Vec<TurtleStep> {
+                vec![
+                    TurtleStep::SetPos(Point { x: 1.0, y: 1.0 }),
+                    TurtleStep::Down,
+                ]
+            }
+
+            fn turtle_program_iter(&self) -> TurtleProgramIterator {
+                let side = vec![TurtleStep::Forward(1.0), TurtleStep::TurnRad(PI / 2.0)];
+                TurtleProgramIterator::new(Box::new(
+                    (0..4).flat_map(move |_| side.clone().into_iter()),
+                ))
+            }
+        }
+
+        let start = Square.seek(0);
+        assert_point_eq!(start.state.position, Point { x: 1.0, y: 1.0 }, 0.000000001);
+        assert_eq!(start.rest.count(), 8);
+
+        let seek = seek_by_replay(&Square, 2);
+        assert_point_eq!(seek.state.position, Point { x: 2.0, y: 2.0 }, 0.000000001);
+        assert_approx_eq!(seek.state.angle, PI / 2.0, 0.000000001);
+        assert_eq!(
+            seek.rest.collect::<Vec<TurtleStep>>(),
+            vec![
+                TurtleStep::TurnRad(PI / 2.0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(PI / 2.0),
+                TurtleStep::Forward(1.0),
+                TurtleStep::TurnRad(PI / 2.0),
+            ]
+        );
+
+        let past_the_end = Square.seek(10);
+        assert_point_eq!(
+            past_the_end.state.position,
+            Point { x: 1.0, y: 1.0 },
+            0.000000001
+        );
+        assert_eq!(past_the_end.rest.count(), 0);
+    }
+}
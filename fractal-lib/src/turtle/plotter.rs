@@ -0,0 +1,505 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pen plotter output for turtle programs.
+//!
+//! `plot()` records the paths that a program draws with a `PolylineTurtle`, scales them to fit
+//! the paper, reorders them to reduce how far the pen travels while lifted, and writes them as HPGL
+//! or G-code. Plotters draw with a single pen, so pen colors, widths, and dashes are ignored.
+
+use super::bounds::BoundingBox;
+use super::polyline::{self, Polyline};
+use super::svg::number;
+use super::TurtleProgram;
+use crate::geometry::Point;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// HPGL plotter units per millimeter.
+const HPGL_UNITS_PER_MM: f64 = 40.0;
+
+/// Points closer together than this, in millimeters, are treated as the same point when deciding
+/// whether the pen needs to be lifted.
+const SAME_POINT_MM: f64 = 0.001;
+
+/// The file formats that `plot()` can write.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum PlotterFormat {
+    Hpgl,
+    Gcode,
+}
+
+impl PlotterFormat {
+    /// Determines the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<PlotterFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "hpgl" | "hpg" | "plt" => Some(PlotterFormat::Hpgl),
+            "gcode" | "gco" | "nc" => Some(PlotterFormat::Gcode),
+            _ => None,
+        }
+    }
+}
+
+/// The paper and machine settings used by `plot()`. All lengths are in millimeters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlotterSettings {
+    pub paper_width: f64,
+    pub paper_height: f64,
+    /// Space left empty on every side of the paper.
+    pub margin: f64,
+    /// How fast the pen moves while drawing, in millimeters per minute.
+    pub feed_rate: f64,
+    /// How high G-code lifts the pen (on the Z axis) while it is up.
+    pub pen_lift: f64,
+}
+
+impl Default for PlotterSettings {
+    /// A landscape A4 sheet with a 10mm margin.
+    fn default() -> PlotterSettings {
+        PlotterSettings {
+            paper_width: 297.0,
+            paper_height: 210.0,
+            margin: 10.0,
+            feed_rate: 3000.0,
+            pen_lift: 2.0,
+        }
+    }
+}
+
+/// Scales `paths` to fit within the margins of the paper, centered, without distorting them.
+/// The result is in millimeters, with the origin at the bottom left corner of the paper.
+pub fn fit_to_paper(paths: &[Polyline], settings: &PlotterSettings) -> Vec<Polyline> {
+    let mut points = paths.iter().flatten();
+    let mut bounds = match points.next() {
+        Some(point) => BoundingBox::at(*point),
+        None => return Vec::new(),
+    };
+    points.for_each(|point| bounds.include(*point));
+
+    let area_width = (settings.paper_width - 2.0 * settings.margin).max(0.0);
+    let area_height = (settings.paper_height - 2.0 * settings.margin).max(0.0);
+    let scale = match (bounds.width() > 0.0, bounds.height() > 0.0) {
+        (true, true) => (area_width / bounds.width()).min(area_height / bounds.height()),
+        (true, false) => area_width / bounds.width(),
+        (false, true) => area_height / bounds.height(),
+        (false, false) => 1.0,
+    };
+    let offset_x = settings.margin + (area_width - bounds.width() * scale) / 2.0;
+    let offset_y = settings.margin + (area_height - bounds.height() * scale) / 2.0;
+
+    paths
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|point| Point {
+                    x: (point.x - bounds.min.x) * scale + offset_x,
+                    y: (point.y - bounds.min.y) * scale + offset_y,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Reorders, and possibly reverses, paths to reduce the distance the pen travels while lifted.
+///
+/// Starting from `start`, it repeatedly draws whichever remaining path has an end closest to the
+/// pen. This greedy approach is not optimal, but it avoids most of the back-and-forth of drawing
+/// the paths in program order.
+pub fn optimize_paths(paths: Vec<Polyline>, start: Point) -> Vec<Polyline> {
+    let mut remaining: Vec<Option<Polyline>> = paths
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(Some)
+        .collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    let mut grid = EndpointGrid::new(&remaining);
+    let mut pen = start;
+    while ordered.len() < remaining.len() {
+        if (remaining.len() - ordered.len()) * 2 < grid.paths {
+            grid = EndpointGrid::new(&remaining);
+        }
+        let (index, reverse) = match grid.nearest(pen, &remaining) {
+            Some(nearest) => nearest,
+            None => break,
+        };
+        let mut path = remaining[index].take().unwrap();
+        if reverse {
+            path.reverse();
+        }
+        pen = path[path.len() - 1];
+        ordered.push(path);
+    }
+    ordered
+}
+
+/// An end of a path: its point, the index of the path, and whether it is the path's last point.
+type PathEnd = (Point, usize, bool);
+
+/// The ends of the paths that `optimize_paths` still has to draw, bucketed into a grid so that
+/// the closest end to the pen can be found by searching the cells around it.
+///
+/// Ends are not removed from the grid when their path is drawn; they are skipped while searching
+/// instead, and the grid is rebuilt once most of its paths are gone.
+struct EndpointGrid {
+    cell: f64,
+    /// The lowest and highest cells that hold any ends.
+    min: (i64, i64),
+    max: (i64, i64),
+    cells: HashMap<(i64, i64), Vec<PathEnd>>,
+    /// How many paths the grid was built from.
+    paths: usize,
+}
+
+impl EndpointGrid {
+    fn new(remaining: &[Option<Polyline>]) -> EndpointGrid {
+        let ends: Vec<PathEnd> = remaining
+            .iter()
+            .enumerate()
+            .filter_map(|(index, path)| path.as_ref().map(|path| (index, path)))
+            .flat_map(|(index, path)| {
+                [(path[0], index, false), (path[path.len() - 1], index, true)]
+            })
+            .collect();
+        let mut bounds: Option<BoundingBox> = None;
+        for &(point, _, _) in &ends {
+            match bounds.as_mut() {
+                Some(bounds) => bounds.include(point),
+                None => bounds = Some(BoundingBox::at(point)),
+            }
+        }
+        // About one end per cell
+        let extent = bounds.map_or(0.0, |bounds| bounds.width().max(bounds.height()));
+        let mut cell = extent / (ends.len() as f64).sqrt();
+        if !(cell > 0.0 && cell.is_finite()) {
+            cell = 1.0;
+        }
+
+        let mut grid = EndpointGrid {
+            cell,
+            min: (i64::MAX, i64::MAX),
+            max: (i64::MIN, i64::MIN),
+            cells: HashMap::new(),
+            paths: ends.len() / 2,
+        };
+        for end in ends {
+            let (cx, cy) = grid.cell_of(end.0);
+            grid.min = (grid.min.0.min(cx), grid.min.1.min(cy));
+            grid.max = (grid.max.0.max(cx), grid.max.1.max(cy));
+            grid.cells.entry((cx, cy)).or_default().push(end);
+        }
+        grid
+    }
+
+    fn cell_of(&self, point: Point) -> (i64, i64) {
+        (
+            (point.x / self.cell).floor() as i64,
+            (point.y / self.cell).floor() as i64,
+        )
+    }
+
+    /// Finds the path with the end closest to `pen`, and whether that end is its last point. Ties
+    /// go to the earliest path, and to its first point.
+    fn nearest(&mut self, pen: Point, remaining: &[Option<Polyline>]) -> Option<(usize, bool)> {
+        let (px, py) = self.cell_of(pen);
+        let rings = [
+            px - self.min.0,
+            self.max.0 - px,
+            py - self.min.1,
+            self.max.1 - py,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+        let mut best: Option<(f64, usize, bool)> = None;
+        for ring in 0..=rings.max(0) {
+            // Every end in this ring, or further out, is at least this far from the pen
+            if let Some((distance, _, _)) = best {
+                if distance <= (ring - 1) as f64 * self.cell {
+                    break;
+                }
+            }
+            for cx in (px - ring).max(self.min.0)..=(px + ring).min(self.max.0) {
+                // The ring's left and right columns are whole, the others only have their top
+                // and bottom cells
+                let column = if (cx - px).abs() == ring {
+                    ((py - ring).max(self.min.1)..=(py + ring).min(self.max.1)).step_by(1)
+                } else {
+                    (py - ring..=py + ring).step_by(2 * ring as usize)
+                };
+                for cy in column {
+                    let ends = match self.cells.get_mut(&(cx, cy)) {
+                        Some(ends) => ends,
+                        None => continue,
+                    };
+                    ends.retain(|&(_, index, _)| remaining[index].is_some());
+                    for &(point, index, reverse) in ends.iter() {
+                        let distance = pen.distance_to(point);
+                        let closer = match best {
+                            None => true,
+                            Some(best) => (distance, index, reverse) < best,
+                        };
+                        if closer {
+                            best = Some((distance, index, reverse));
+                        }
+                    }
+                }
+            }
+        }
+        best.map(|(_, index, reverse)| (index, reverse))
+    }
+}
+
+/// The total distance the pen travels while lifted to draw `paths` in order, starting from
+/// `start`.
+pub fn pen_up_distance(paths: &[Polyline], start: Point) -> f64 {
+    let mut pen = start;
+    let mut distance = 0.0;
+    for path in paths.iter().filter(|p| !p.is_empty()) {
+        distance += pen.distance_to(path[0]);
+        pen = path[path.len() - 1];
+    }
+    distance
+}
+
+/// Draws `program`, fits it to the paper, optimizes the path order, and writes it as `format`.
+pub fn plot(
+    program: &dyn TurtleProgram,
+    format: PlotterFormat,
+    settings: &PlotterSettings,
+) -> String {
+    let origin = Point { x: 0.0, y: 0.0 };
+    let paths = optimize_paths(fit_to_paper(&polyline::record(program), settings), origin);
+    match format {
+        PlotterFormat::Hpgl => write_hpgl(&paths, settings),
+        PlotterFormat::Gcode => write_gcode(&paths, settings),
+    }
+}
+
+/// Groups paths into strokes: each stroke starts with a pen-up move to its first point, and
+/// paths that start where the previous one ended are joined to it without lifting the pen.
+fn strokes(paths: &[Polyline]) -> Vec<Vec<Point>> {
+    let mut strokes: Vec<Vec<Point>> = Vec::new();
+    for path in paths {
+        match strokes.last_mut() {
+            Some(stroke) if stroke[stroke.len() - 1].distance_to(path[0]) < SAME_POINT_MM => {
+                stroke.extend_from_slice(&path[1..])
+            }
+            _ => strokes.push(path.clone()),
+        }
+    }
+    strokes
+}
+
+/// Writes already fitted and ordered paths as HPGL.
+pub fn write_hpgl(paths: &[Polyline], settings: &PlotterSettings) -> String {
+    let units = |value: f64| (value * HPGL_UNITS_PER_MM).round() as i64;
+    let mut out = String::new();
+    out.push_str("IN;\nSP1;\n");
+    // velocity is in centimeters per second
+    writeln!(out, "VS{};", number(settings.feed_rate / 600.0)).unwrap();
+    for stroke in strokes(paths) {
+        writeln!(out, "PU{},{};", units(stroke[0].x), units(stroke[0].y)).unwrap();
+        let points: Vec<String> = stroke[1..]
+            .iter()
+            .map(|point| format!("{},{}", units(point.x), units(point.y)))
+            .collect();
+        writeln!(out, "PD{};", points.join(",")).unwrap();
+    }
+    out.push_str("PU0,0;\nSP0;\n");
+    out
+}
+
+/// Writes already fitted and ordered paths as G-code. The pen is raised and lowered on the Z
+/// axis.
+pub fn write_gcode(paths: &[Polyline], settings: &PlotterSettings) -> String {
+    let lift = number(settings.pen_lift);
+    let feed = number(settings.feed_rate);
+    let mut out = String::new();
+    out.push_str("G21 ; millimeters\nG90 ; absolute positioning\n");
+    writeln!(out, "G0 Z{}", lift).unwrap();
+    for stroke in strokes(paths) {
+        writeln!(out, "G0 X{} Y{}", number(stroke[0].x), number(stroke[0].y)).unwrap();
+        writeln!(out, "G1 Z0 F{}", feed).unwrap();
+        for point in &stroke[1..] {
+            writeln!(
+                out,
+                "G1 X{} Y{} F{}",
+                number(point.x),
+                number(point.y),
+                feed
+            )
+            .unwrap();
+        }
+        writeln!(out, "G0 Z{}", lift).unwrap();
+    }
+    out.push_str("G0 X0 Y0\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::fractalplant::FractalPlant;
+    use crate::lindenmayer::LindenmayerSystemTurtleProgram;
+    use crate::turtle::{TurtleProgramIterator, TurtleStep};
+    use std::time::{Duration, Instant};
+
+    fn p(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn test_fit_to_paper() {
+        let settings = PlotterSettings {
+            paper_width: 120.0,
+            paper_height: 70.0,
+            margin: 10.0,
+            ..PlotterSettings::default()
+        };
+        // a 2x1 drawing fits the 100x50 area exactly
+        let fitted = fit_to_paper(&[vec![p(-1.0, 0.0), p(1.0, 1.0)]], &settings);
+        assert_eq!(fitted, vec![vec![p(10.0, 10.0), p(110.0, 60.0)]]);
+
+        // a 1x1 drawing is limited by the height, and centered horizontally
+        let fitted = fit_to_paper(&[vec![p(0.0, 0.0), p(1.0, 1.0)]], &settings);
+        assert_eq!(fitted, vec![vec![p(35.0, 10.0), p(85.0, 60.0)]]);
+
+        // a vertical line doesn't divide by zero
+        let fitted = fit_to_paper(&[vec![p(0.0, 0.0), p(0.0, 1.0)]], &settings);
+        assert_eq!(fitted, vec![vec![p(60.0, 10.0), p(60.0, 60.0)]]);
+    }
+
+    #[test]
+    fn test_optimize_paths() {
+        let paths = vec![
+            vec![p(10.0, 0.0), p(11.0, 0.0)],
+            vec![p(2.0, 0.0), p(1.0, 0.0)],
+            vec![p(5.0, 0.0), p(3.0, 0.0)],
+        ];
+        let origin = p(0.0, 0.0);
+        let optimized = optimize_paths(paths.clone(), origin);
+        assert_eq!(
+            optimized,
+            vec![
+                vec![p(1.0, 0.0), p(2.0, 0.0)],
+                vec![p(3.0, 0.0), p(5.0, 0.0)],
+                vec![p(10.0, 0.0), p(11.0, 0.0)],
+            ]
+        );
+        assert_eq!(pen_up_distance(&paths, origin), 23.0);
+        assert_eq!(pen_up_distance(&optimized, origin), 7.0);
+    }
+
+    #[test]
+    fn test_optimize_many_paths() {
+        // A branching plant draws many short polylines; ordering them shouldn't compare every
+        // pair.
+        let program = LindenmayerSystemTurtleProgram::new(FractalPlant::new(8));
+        let paths = polyline::record(&program);
+        assert!(paths.len() > 30000, "{} paths", paths.len());
+        let origin = p(0.0, 0.0);
+        let started = Instant::now();
+        let optimized = optimize_paths(paths.clone(), origin);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(optimized.len(), paths.len());
+        assert!(pen_up_distance(&optimized, origin) < pen_up_distance(&paths, origin));
+    }
+
+    /// Two separate lines: one from (0,0) to (1,0), and one from (0,1) to (1,1).
+    struct TwoLines;
+
+    impl TurtleProgram for TwoLines {
+        fn init_turtle(&self) -> Vec<TurtleStep> {
+            vec![TurtleStep::Down]
+        }
+
+        fn turtle_program_iter(&self) -> TurtleProgramIterator {
+            TurtleProgramIterator::new(Box::new(
+                vec![
+                    TurtleStep::Forward(1.0),
+                    TurtleStep::SetPos(p(0.0, 1.0)),
+                    TurtleStep::Forward(1.0),
+                ]
+                .into_iter(),
+            ))
+        }
+    }
+
+    fn settings() -> PlotterSettings {
+        PlotterSettings {
+            paper_width: 30.0,
+            paper_height: 30.0,
+            margin: 10.0,
+            feed_rate: 1200.0,
+            pen_lift: 3.0,
+        }
+    }
+
+    #[test]
+    fn test_hpgl() {
+        // the second line ends up reversed, so the pen only needs to lift once
+        let hpgl = plot(&TwoLines, PlotterFormat::Hpgl, &settings());
+        assert_eq!(
+            hpgl,
+            "IN;\nSP1;\nVS2;\nPU400,400;\nPD800,400;\nPU800,800;\nPD400,800;\nPU0,0;\nSP0;\n"
+        );
+    }
+
+    #[test]
+    fn test_gcode() {
+        let gcode = plot(&TwoLines, PlotterFormat::Gcode, &settings());
+        assert_eq!(
+            gcode,
+            "G21 ; millimeters\n\
+             G90 ; absolute positioning\n\
+             G0 Z3\n\
+             G0 X10 Y10\n\
+             G1 Z0 F1200\n\
+             G1 X20 Y10 F1200\n\
+             G0 Z3\n\
+             G0 X20 Y20\n\
+             G1 Z0 F1200\n\
+             G1 X10 Y20 F1200\n\
+             G0 Z3\n\
+             G0 X0 Y0\n"
+        );
+    }
+
+    #[test]
+    fn test_joins_touching_paths() {
+        let paths = vec![
+            vec![p(0.0, 0.0), p(1.0, 0.0)],
+            vec![p(1.0, 0.0), p(1.0, 1.0)],
+        ];
+        let hpgl = write_hpgl(&paths, &settings());
+        assert!(hpgl.contains("PU0,0;\nPD40,0,40,40;\n"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            PlotterFormat::from_path(Path::new("a.HPGL")),
+            Some(PlotterFormat::Hpgl)
+        );
+        assert_eq!(
+            PlotterFormat::from_path(Path::new("a.gcode")),
+            Some(PlotterFormat::Gcode)
+        );
+        assert_eq!(PlotterFormat::from_path(Path::new("a.svg")), None);
+    }
+}
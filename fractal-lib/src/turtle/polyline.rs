@@ -0,0 +1,493 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording turtle programs as polylines, and measuring them.
+
+use super::bounds::BoundingBox;
+use super::{Turtle, TurtleProgram, TurtleState};
+use crate::color::ColorF32;
+use crate::geometry::{Point, Vector};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A sequence of connected points that are drawn without lifting the pen.
+pub type Polyline = Vec<Point>;
+
+/// How close, relative to the average segment length, points need to be to count as touching.
+/// Turtle positions accumulate floating point error, so points that should coincide rarely do
+/// exactly.
+const RELATIVE_TOLERANCE: f64 = 1e-6;
+
+/// A `Turtle` that records the lines it draws as polylines. Consecutive line segments are
+/// merged into one polyline until the pen is lifted or the turtle jumps somewhere else. Pen
+/// colors, widths, and dashes are ignored.
+pub struct PolylineTurtle {
+    state: TurtleState,
+    polylines: Vec<Polyline>,
+    /// Whether the next segment can be added to the last polyline.
+    continuing: bool,
+}
+
+impl PolylineTurtle {
+    pub fn new() -> PolylineTurtle {
+        PolylineTurtle {
+            state: TurtleState::new(),
+            polylines: Vec::new(),
+            continuing: false,
+        }
+    }
+
+    /// Returns the recorded polylines, in the order they were drawn.
+    pub fn into_polylines(self) -> Vec<Polyline> {
+        self.polylines
+    }
+}
+
+impl Default for PolylineTurtle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Turtle for PolylineTurtle {
+    fn forward(&mut self, distance: f64) {
+        let old_pos = self.state.position;
+        let new_pos = old_pos.point_at(Vector {
+            direction: self.state.angle,
+            magnitude: distance,
+        });
+
+        if self.state.down {
+            match self.polylines.last_mut() {
+                Some(polyline) if self.continuing => polyline.push(new_pos),
+                _ => self.polylines.push(vec![old_pos, new_pos]),
+            }
+            self.continuing = true;
+        }
+
+        self.state.position = new_pos;
+    }
+
+    fn set_pos(&mut self, new_pos: Point) {
+        self.state.position = new_pos;
+        self.continuing = false;
+    }
+
+    fn set_rad(&mut self, new_rad: f64) {
+        self.state.angle = new_rad;
+    }
+
+    fn turn_rad(&mut self, radians: f64) {
+        self.state.angle += radians;
+    }
+
+    fn down(&mut self) {
+        self.state.down = true;
+    }
+
+    fn up(&mut self) {
+        self.state.down = false;
+        self.continuing = false;
+    }
+
+    fn set_pen_color(&mut self, _color: ColorF32) {}
+
+    fn set_pen_width(&mut self, _width: f64) {}
+
+    fn set_pen_dash(&mut self, _pattern: Vec<f64>) {}
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        self.state.pop();
+        self.continuing = false;
+    }
+}
+
+/// Runs `program` and returns the polylines it draws.
+pub fn record(program: &dyn TurtleProgram) -> Vec<Polyline> {
+    let mut turtle = PolylineTurtle::new();
+    for step in program.init_turtle() {
+        turtle.perform(step);
+    }
+    for step in program.turtle_program_iter() {
+        turtle.perform(step);
+    }
+    turtle.into_polylines()
+}
+
+/// Geometric statistics about what a turtle program draws.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurtleStats {
+    pub polylines: usize,
+    pub segments: usize,
+    /// The total length of every segment.
+    pub length: f64,
+    /// `None` if nothing is drawn.
+    pub bounds: Option<BoundingBox>,
+    /// The first point that is drawn.
+    pub start: Option<Point>,
+    /// The last point that is drawn.
+    pub end: Option<Point>,
+    /// The number of pairs of segments that touch or cross, not counting consecutive segments
+    /// of a polyline meeting at their shared point, or segments that overlap.
+    pub intersections: usize,
+    /// The number of pairs of segments that lie on top of each other for some distance.
+    pub overlaps: usize,
+}
+
+impl TurtleStats {
+    /// Computes the statistics for a set of polylines.
+    pub fn new(polylines: &[Polyline]) -> TurtleStats {
+        let segments = segments(polylines);
+        let length = segments.iter().fold(0.0, |total, s| total + s.length());
+
+        let mut points = polylines.iter().flatten();
+        let bounds = points.next().map(|first| {
+            let mut bounds = BoundingBox::at(*first);
+            points.for_each(|point| bounds.include(*point));
+            bounds
+        });
+
+        let (intersections, overlaps) = count_contacts(&segments, length, bounds);
+        TurtleStats {
+            polylines: polylines.iter().filter(|p| p.len() > 1).count(),
+            segments: segments.len(),
+            length,
+            bounds,
+            start: polylines.iter().find_map(|p| p.first()).cloned(),
+            end: polylines.iter().rev().find_map(|p| p.last()).cloned(),
+            intersections,
+            overlaps,
+        }
+    }
+
+    /// Runs `program` and computes the statistics for what it draws.
+    pub fn of_program(program: &dyn TurtleProgram) -> TurtleStats {
+        TurtleStats::new(&record(program))
+    }
+}
+
+impl fmt::Display for TurtleStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // rounding error often leaves values like -0.0000000001, which shouldn't print as -0
+        let decimal = |value: f64| match format!("{:.6}", value) {
+            text if text == "-0.000000" => "0.000000".to_string(),
+            text => text,
+        };
+        let point = |p: Option<Point>| match p {
+            Some(p) => format!("({}, {})", decimal(p.x), decimal(p.y)),
+            None => "none".to_string(),
+        };
+        writeln!(f, "polylines:     {}", self.polylines)?;
+        writeln!(f, "segments:      {}", self.segments)?;
+        writeln!(f, "length:        {}", decimal(self.length))?;
+        match self.bounds {
+            Some(bounds) => writeln!(
+                f,
+                "bounds:        {} to {} ({} x {})",
+                point(Some(bounds.min)),
+                point(Some(bounds.max)),
+                decimal(bounds.width()),
+                decimal(bounds.height())
+            )?,
+            None => writeln!(f, "bounds:        none")?,
+        }
+        writeln!(f, "start:         {}", point(self.start))?;
+        writeln!(f, "end:           {}", point(self.end))?;
+        writeln!(f, "intersections: {}", self.intersections)?;
+        write!(f, "overlaps:      {}", self.overlaps)
+    }
+}
+
+/// A line segment, along with where it came from.
+#[derive(Copy, Clone, Debug)]
+struct Segment {
+    a: Point,
+    b: Point,
+    polyline: usize,
+    /// The position of the segment within its polyline.
+    index: usize,
+}
+
+impl Segment {
+    fn length(&self) -> f64 {
+        self.a.distance_to(self.b)
+    }
+
+    /// Whether `other` follows or precedes this segment in the same polyline.
+    fn is_adjacent(&self, other: &Segment) -> bool {
+        self.polyline == other.polyline
+            && (self.index + 1 == other.index || other.index + 1 == self.index)
+    }
+}
+
+fn segments(polylines: &[Polyline]) -> Vec<Segment> {
+    polylines
+        .iter()
+        .enumerate()
+        .flat_map(|(polyline, points)| {
+            points
+                .windows(2)
+                .enumerate()
+                .map(move |(index, pair)| Segment {
+                    a: pair[0],
+                    b: pair[1],
+                    polyline,
+                    index,
+                })
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Contact {
+    Apart,
+    /// The segments meet at a single point.
+    Touch,
+    /// The segments share some length.
+    Overlap,
+}
+
+fn cross(u: [f64; 2], v: [f64; 2]) -> f64 {
+    u[0] * v[1] - u[1] * v[0]
+}
+
+fn dot(u: [f64; 2], v: [f64; 2]) -> f64 {
+    u[0] * v[0] + u[1] * v[1]
+}
+
+fn delta(from: Point, to: Point) -> [f64; 2] {
+    [to.x - from.x, to.y - from.y]
+}
+
+/// Determines whether two segments, neither of which has zero length, meet. Points within
+/// `tolerance` of each other are considered to meet.
+fn contact(s1: &Segment, s2: &Segment, tolerance: f64) -> Contact {
+    let r = delta(s1.a, s1.b);
+    let s = delta(s2.a, s2.b);
+    let qp = delta(s1.a, s2.a);
+    let r_len = dot(r, r).sqrt();
+    let s_len = dot(s, s).sqrt();
+    let denom = cross(r, s);
+
+    if denom.abs() <= tolerance / r_len.max(s_len) * r_len * s_len {
+        // parallel. If they are on the same line, compare their extents along s1.
+        if (cross(qp, r) / r_len).abs() > tolerance {
+            return Contact::Apart;
+        }
+        let t0 = dot(qp, r) / r_len;
+        let t1 = t0 + dot(s, r) / r_len;
+        let shared = t0.max(t1).min(r_len) - t0.min(t1).max(0.0);
+        return if shared > tolerance {
+            Contact::Overlap
+        } else if shared >= -tolerance {
+            Contact::Touch
+        } else {
+            Contact::Apart
+        };
+    }
+
+    let t = cross(qp, s) / denom;
+    let u = cross(qp, r) / denom;
+    let t_tolerance = tolerance / r_len;
+    let u_tolerance = tolerance / s_len;
+    if (-t_tolerance..=1.0 + t_tolerance).contains(&t)
+        && (-u_tolerance..=1.0 + u_tolerance).contains(&u)
+    {
+        Contact::Touch
+    } else {
+        Contact::Apart
+    }
+}
+
+/// Counts the pairs of segments that touch and that overlap.
+///
+/// Segments are bucketed into a grid so that only segments that share a cell need to be compared.
+/// Cells are about as large as an average segment, but never so small that `bounds` holds more
+/// cells than there are segments, which would let a few long segments cover a huge number of
+/// cells.
+fn count_contacts(
+    segments: &[Segment],
+    total_length: f64,
+    bounds: Option<BoundingBox>,
+) -> (usize, usize) {
+    let segments: Vec<&Segment> = segments.iter().filter(|s| s.length() > 0.0).collect();
+    if segments.len() < 2 {
+        return (0, 0);
+    }
+    let average = total_length / segments.len() as f64;
+    let tolerance = average * RELATIVE_TOLERANCE;
+    let extent = bounds.map_or(0.0, |bounds| bounds.width().max(bounds.height()));
+    let cell = average.max(extent / (segments.len() as f64).sqrt());
+
+    let cell_of = |value: f64| ((value) / cell).floor() as i64;
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let (x0, x1) = (segment.a.x.min(segment.b.x), segment.a.x.max(segment.b.x));
+        let (y0, y1) = (segment.a.y.min(segment.b.y), segment.a.y.max(segment.b.y));
+        for cx in cell_of(x0 - tolerance)..=cell_of(x1 + tolerance) {
+            for cy in cell_of(y0 - tolerance)..=cell_of(y1 + tolerance) {
+                grid.entry((cx, cy)).or_default().push(i);
+            }
+        }
+    }
+
+    let mut checked = HashSet::new();
+    let (mut touches, mut overlaps) = (0, 0);
+    for members in grid.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                if !checked.insert((i.min(j), i.max(j))) {
+                    continue;
+                }
+                let (s1, s2) = (segments[i], segments[j]);
+                match contact(s1, s2, tolerance) {
+                    Contact::Overlap => overlaps += 1,
+                    Contact::Touch if !s1.is_adjacent(s2) => touches += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+    (touches, overlaps)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::dragon::DragonFractal;
+    use crate::turtle::TurtleStep;
+    use std::f64::consts::PI;
+
+    fn p(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn test_turtle_polylines() {
+        let mut turtle = PolylineTurtle::new();
+        for step in vec![
+            TurtleStep::Forward(1.0),
+            TurtleStep::TurnRad(PI / 2.0),
+            TurtleStep::Forward(1.0),
+            TurtleStep::Up,
+            TurtleStep::Forward(1.0),
+            TurtleStep::Down,
+            TurtleStep::Push,
+            TurtleStep::Forward(1.0),
+            TurtleStep::Pop,
+            TurtleStep::SetPenWidth(3.0),
+            TurtleStep::Forward(1.0),
+        ] {
+            turtle.perform(step);
+        }
+        let polylines = turtle.into_polylines();
+        assert_eq!(polylines.len(), 3);
+        assert_eq!(polylines[0].len(), 3);
+        assert_point_eq!(polylines[0][2], p(1.0, 1.0), 0.000000001);
+        assert_point_eq!(polylines[1][0], p(1.0, 2.0), 0.000000001);
+        assert_point_eq!(polylines[2][0], p(1.0, 2.0), 0.000000001);
+    }
+
+    #[test]
+    fn test_stats() {
+        // a closed square with a tail leaving from its first corner, and a separate line that
+        // overlaps its top edge
+        let polylines = vec![
+            vec![
+                p(0.0, 0.0),
+                p(2.0, 0.0),
+                p(2.0, 2.0),
+                p(0.0, 2.0),
+                p(0.0, 0.0),
+                p(1.0, -1.0),
+            ],
+            vec![p(1.0, 2.0), p(3.0, 2.0)],
+        ];
+        let stats = TurtleStats::new(&polylines);
+        assert_eq!(stats.polylines, 2);
+        assert_eq!(stats.segments, 6);
+        assert_approx_eq!(stats.length, 10.0 + 2f64.sqrt(), 0.000000001);
+        assert_eq!(
+            stats.bounds,
+            Some(BoundingBox {
+                min: p(0.0, -1.0),
+                max: p(3.0, 2.0),
+            })
+        );
+        assert_eq!(stats.start, Some(p(0.0, 0.0)));
+        assert_eq!(stats.end, Some(p(3.0, 2.0)));
+        // the square closing on itself, the tail touching the first edge, and the separate line
+        // touching the right edge
+        assert_eq!(stats.intersections, 3);
+        assert_eq!(stats.overlaps, 1);
+    }
+
+    #[test]
+    fn test_doubling_back() {
+        let stats = TurtleStats::new(&[vec![p(0.0, 0.0), p(2.0, 0.0), p(1.0, 0.0)]]);
+        assert_eq!(stats.intersections, 0);
+        assert_eq!(stats.overlaps, 1);
+    }
+
+    #[test]
+    fn test_long_segment() {
+        // many short segments along the X axis, and one long diagonal that never reaches them
+        let short: Polyline = (0..=10000).map(|x| p(x as f64, 0.0)).collect();
+        let long = vec![p(0.0, 1.0), p(10000.0, 10001.0)];
+        let stats = TurtleStats::new(&[short, long]);
+        assert_eq!(stats.segments, 10001);
+        assert_eq!(stats.intersections, 0);
+        assert_eq!(stats.overlaps, 0);
+    }
+
+    #[test]
+    fn test_empty() {
+        let stats = TurtleStats::new(&[]);
+        assert_eq!(stats.segments, 0);
+        assert_eq!(stats.bounds, None);
+        assert_eq!(stats.start, None);
+        assert_eq!(
+            stats.to_string(),
+            "polylines:     0\nsegments:      0\nlength:        0.000000\nbounds:        none\n\
+             start:         none\nend:           none\nintersections: 0\noverlaps:      0"
+        );
+    }
+
+    #[test]
+    fn test_dragon() {
+        // every iteration of the dragon curve ends at (1,0), grows in length by a factor of
+        // sqrt(2), and touches itself at its corners without ever overlapping itself
+        for iteration in 0..10 {
+            let stats = TurtleStats::of_program(&DragonFractal::new(iteration));
+            assert_eq!(stats.polylines, 1);
+            assert_eq!(stats.segments, 1 << iteration);
+            assert_approx_eq!(
+                stats.length,
+                2f64.sqrt().powi(iteration as i32),
+                0.000000001
+            );
+            assert_point_eq!(stats.start.unwrap(), p(0.0, 0.0), 0.000000001);
+            assert_point_eq!(stats.end.unwrap(), p(1.0, 0.0), 0.000000001);
+            assert_eq!(stats.overlaps, 0);
+            if iteration < 4 {
+                assert_eq!(stats.intersections, 0);
+            }
+        }
+        assert!(TurtleStats::of_program(&DragonFractal::new(4)).intersections > 0);
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering turtle programs as SVG documents.
+
+use super::bounds::{self, BoundingBox};
+use super::{Turtle, TurtleProgram, TurtleState};
+use crate::color::{ColorF32, ColorU8};
+use crate::geometry::{Point, Vec2d, Vector, ViewAreaTransformer};
+use std::fmt::Write;
+
+/// The size, in pixels, of the larger dimension of a document made by `render_svg()`.
+pub const SVG_SIZE: f64 = 1000.0;
+
+/// A run of connected line segments that are all drawn with the same pen.
+#[derive(Clone, Debug, PartialEq)]
+struct Polyline {
+    /// The points of the polyline, in pixels.
+    points: Vec<Vec2d>,
+    color: ColorF32,
+    width: f64,
+    dash: Vec<f64>,
+    dash_offset: f64,
+}
+
+/// A `Turtle` that records what it draws, and then writes it out as an SVG document.
+///
+/// Consecutive line segments are merged into a single `<polyline>` as long as the pen stays down
+/// and does not change.
+pub struct SvgTurtle {
+    state: TurtleState,
+    vat: ViewAreaTransformer,
+    size: Vec2d,
+    polylines: Vec<Polyline>,
+    /// Whether the next segment can be added to the last polyline.
+    continuing: bool,
+}
+
+impl SvgTurtle {
+    /// Creates a turtle that draws onto a `size` pixel document, which shows the `view` area of
+    /// the turtle's coordinate system.
+    pub fn new(view: BoundingBox, size: Vec2d) -> SvgTurtle {
+        SvgTurtle {
+            state: TurtleState::new(),
+            vat: view.view_area_transformer(size),
+            size,
+            polylines: Vec::new(),
+            continuing: false,
+        }
+    }
+
+    /// Writes everything that has been drawn so far as an SVG document with a white background.
+    pub fn to_svg(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\">",
+            w = number(self.size[0]),
+            h = number(self.size[1])
+        )
+        .unwrap();
+        out.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+        for polyline in &self.polylines {
+            write_polyline(&mut out, polyline);
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Ends the current polyline, so that the next segment starts a new one.
+    fn break_line(&mut self) {
+        self.continuing = false;
+    }
+}
+
+impl Turtle for SvgTurtle {
+    fn forward(&mut self, distance: f64) {
+        let old_pos = self.state.position;
+        let new_pos = old_pos.point_at(Vector {
+            direction: self.state.angle,
+            magnitude: distance,
+        });
+
+        if self.state.down {
+            let old_coords = self.vat.map_point_to_pixel(old_pos);
+            let new_coords = self.vat.map_point_to_pixel(new_pos);
+            match self.polylines.last_mut() {
+                Some(polyline) if self.continuing => polyline.points.push(new_coords),
+                _ => self.polylines.push(Polyline {
+                    points: vec![old_coords, new_coords],
+                    color: self.state.color,
+                    width: self.state.width,
+                    dash: self.state.dash.clone(),
+                    dash_offset: self.state.dash_offset,
+                }),
+            }
+            self.continuing = true;
+            let [dx, dy] = [new_coords[0] - old_coords[0], new_coords[1] - old_coords[1]];
+            self.state.advance_dash(dx.hypot(dy));
+        }
+
+        self.state.position = new_pos;
+    }
+
+    fn set_pos(&mut self, new_pos: Point) {
+        self.state.position = new_pos;
+        self.break_line();
+    }
+
+    fn set_rad(&mut self, new_rad: f64) {
+        self.state.angle = new_rad;
+    }
+
+    fn turn_rad(&mut self, radians: f64) {
+        self.state.angle += radians;
+    }
+
+    fn down(&mut self) {
+        self.state.down = true;
+    }
+
+    fn up(&mut self) {
+        self.state.down = false;
+        self.break_line();
+    }
+
+    fn set_pen_color(&mut self, color: ColorF32) {
+        if color != self.state.color {
+            self.state.color = color;
+            self.break_line();
+        }
+    }
+
+    fn set_pen_width(&mut self, width: f64) {
+        if width != self.state.width {
+            self.state.width = width;
+            self.break_line();
+        }
+    }
+
+    fn set_pen_dash(&mut self, pattern: Vec<f64>) {
+        self.state.set_dash(pattern);
+        self.break_line();
+    }
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        self.state.pop();
+        self.break_line();
+    }
+}
+
+/// Draws `program` as an SVG document, fit to a view with a margin (see `bounds::view_area()`).
+/// The larger dimension of the document is `SVG_SIZE` pixels.
+pub fn render_svg(program: &dyn TurtleProgram) -> String {
+    let view = bounds::view_area(program);
+    let scale = SVG_SIZE / view.width().max(view.height());
+    let mut turtle = SvgTurtle::new(
+        view,
+        [
+            (view.width() * scale).round(),
+            (view.height() * scale).round(),
+        ],
+    );
+    for step in program.init_turtle() {
+        turtle.perform(step);
+    }
+    for step in program.turtle_program_iter() {
+        turtle.perform(step);
+    }
+    turtle.to_svg()
+}
+
+fn write_polyline(out: &mut String, polyline: &Polyline) {
+    let ColorU8([r, g, b, a]) = ColorU8::from(polyline.color);
+    write!(
+        out,
+        "<polyline fill=\"none\" stroke=\"#{:02x}{:02x}{:02x}\"",
+        r, g, b
+    )
+    .unwrap();
+    if a != 255 {
+        write!(out, " stroke-opacity=\"{}\"", number(f64::from(a) / 255.0)).unwrap();
+    }
+    write!(
+        out,
+        " stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"",
+        number(polyline.width)
+    )
+    .unwrap();
+    if !polyline.dash.is_empty() {
+        let dash: Vec<String> = polyline.dash.iter().map(|len| number(*len)).collect();
+        write!(out, " stroke-dasharray=\"{}\"", dash.join(" ")).unwrap();
+        if polyline.dash_offset != 0.0 {
+            write!(
+                out,
+                " stroke-dashoffset=\"{}\"",
+                number(polyline.dash_offset)
+            )
+            .unwrap();
+        }
+    }
+    let points: Vec<String> = polyline
+        .points
+        .iter()
+        .map(|[x, y]| format!("{},{}", number(*x), number(*y)))
+        .collect();
+    writeln!(out, " points=\"{}\"/>", points.join(" ")).unwrap();
+}
+
+/// Formats a number with at most 3 decimal places, and without trailing zeros.
+pub(super) fn number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::turtle::TurtleStep;
+    use std::f64::consts::PI;
+
+    fn turtle() -> SvgTurtle {
+        // maps (0,0)-(10,10) onto a 100x100 pixel document
+        SvgTurtle::new(
+            BoundingBox {
+                min: Point { x: 0.0, y: 0.0 },
+                max: Point { x: 10.0, y: 10.0 },
+            },
+            [100.0, 100.0],
+        )
+    }
+
+    fn polylines(svg: &str) -> Vec<&str> {
+        svg.lines()
+            .filter(|line| line.starts_with("<polyline"))
+            .collect()
+    }
+
+    #[test]
+    fn test_document() {
+        let svg = turtle().to_svg();
+        assert_eq!(
+            svg,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"100\" \
+             viewBox=\"0 0 100 100\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn test_merges_segments() {
+        let mut turtle = turtle();
+        turtle.forward(5.0);
+        turtle.turn_rad(PI / 2.0);
+        turtle.forward(2.5);
+        let svg = turtle.to_svg();
+        assert_eq!(
+            polylines(&svg),
+            vec![
+                "<polyline fill=\"none\" stroke=\"#000000\" stroke-width=\"1\" \
+                 stroke-linecap=\"round\" stroke-linejoin=\"round\" \
+                 points=\"0,100 50,100 50,75\"/>"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pen_up_and_jumps() {
+        let mut turtle = turtle();
+        turtle.forward(1.0);
+        turtle.up();
+        turtle.forward(1.0);
+        turtle.down();
+        turtle.forward(1.0);
+        turtle.set_pos(Point { x: 5.0, y: 5.0 });
+        turtle.forward(1.0);
+        let svg = turtle.to_svg();
+        let lines = polylines(&svg);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("points=\"0,100 10,100\"/>"));
+        assert!(lines[1].ends_with("points=\"20,100 30,100\"/>"));
+        assert!(lines[2].ends_with("points=\"50,50 60,50\"/>"));
+    }
+
+    #[test]
+    fn test_pen_changes() {
+        let mut turtle = turtle();
+        turtle.perform(TurtleStep::Forward(1.0));
+        turtle.perform(TurtleStep::Push);
+        turtle.perform(TurtleStep::SetPenColor(ColorU8([255, 0, 0, 128]).into()));
+        turtle.perform(TurtleStep::SetPenWidth(2.5));
+        turtle.perform(TurtleStep::SetPenDash(vec![4.0, 2.0]));
+        turtle.perform(TurtleStep::Forward(1.0));
+        turtle.perform(TurtleStep::Pop);
+        turtle.perform(TurtleStep::Forward(1.0));
+        let svg = turtle.to_svg();
+        let lines = polylines(&svg);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "<polyline fill=\"none\" stroke=\"#ff0000\" stroke-opacity=\"0.502\" \
+             stroke-width=\"2.5\" stroke-linecap=\"round\" stroke-linejoin=\"round\" \
+             stroke-dasharray=\"4 2\" points=\"10,100 20,100\"/>"
+        );
+        // the pop returns to the end of the first line, with the original pen
+        assert!(
+            lines[2].starts_with("<polyline fill=\"none\" stroke=\"#000000\" stroke-width=\"1\"")
+        );
+        assert!(lines[2].ends_with("points=\"10,100 20,100\"/>"));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg(&crate::curves::dragon::DragonFractal::new(2));
+        // the curve is twice as wide as it is tall, plus the margin
+        assert!(svg.contains("width=\"1000\" height=\"545\""));
+        // the whole curve is connected, so it is a single polyline
+        assert_eq!(polylines(&svg).len(), 1);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(1.0), "1");
+        assert_eq!(number(0.25), "0.25");
+        assert_eq!(number(1.0 / 3.0), "0.333");
+        assert_eq!(number(-0.0001), "0");
+        assert_eq!(number(100.0), "100");
+    }
+}
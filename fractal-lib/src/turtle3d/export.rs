@@ -0,0 +1,182 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exports 3D turtle programs as line sets that 3D modeling tools can import.
+//!
+//! Both formats list each distinct vertex once, so branches that start from the same point share
+//! a vertex. Wavefront OBJ files describe each polyline as a single `l` element, while PLY files
+//! describe every segment as an `edge` element.
+
+use super::{record, Polyline3, Turtle3dProgram};
+use crate::geometry::Point3;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The file formats that `export()` can write.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum LineSetFormat {
+    /// Wavefront OBJ.
+    Obj,
+    /// ASCII Polygon File Format (also known as the Stanford Triangle Format).
+    Ply,
+}
+
+impl LineSetFormat {
+    /// Determines the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<LineSetFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Some(LineSetFormat::Obj),
+            "ply" => Some(LineSetFormat::Ply),
+            _ => None,
+        }
+    }
+}
+
+/// Runs `program` and writes the lines it draws in `format`.
+pub fn export(program: &dyn Turtle3dProgram, format: LineSetFormat) -> String {
+    let polylines = record(program);
+    match format {
+        LineSetFormat::Obj => write_obj(&polylines),
+        LineSetFormat::Ply => write_ply(&polylines),
+    }
+}
+
+/// Writes polylines as a Wavefront OBJ file.
+pub fn write_obj(polylines: &[Polyline3]) -> String {
+    let (vertices, indexed) = index_vertices(polylines);
+    let mut out = String::new();
+    for vertex in vertices {
+        writeln!(out, "v {}", vertex).unwrap();
+    }
+    for polyline in indexed {
+        // OBJ indices start at 1
+        let indices: Vec<String> = polyline.iter().map(|i| (i + 1).to_string()).collect();
+        writeln!(out, "l {}", indices.join(" ")).unwrap();
+    }
+    out
+}
+
+/// Writes polylines as an ASCII PLY file with `vertex` and `edge` elements.
+pub fn write_ply(polylines: &[Polyline3]) -> String {
+    let (vertices, indexed) = index_vertices(polylines);
+    let edges: Vec<(usize, usize)> = indexed
+        .iter()
+        .flat_map(|polyline| polyline.windows(2).map(|pair| (pair[0], pair[1])))
+        .collect();
+    let mut out = String::new();
+    out.push_str("ply\nformat ascii 1.0\n");
+    writeln!(out, "element vertex {}", vertices.len()).unwrap();
+    out.push_str("property float x\nproperty float y\nproperty float z\n");
+    writeln!(out, "element edge {}", edges.len()).unwrap();
+    out.push_str("property int vertex1\nproperty int vertex2\nend_header\n");
+    for vertex in vertices {
+        writeln!(out, "{}", vertex).unwrap();
+    }
+    for (from, to) in edges {
+        writeln!(out, "{} {}", from, to).unwrap();
+    }
+    out
+}
+
+/// Assigns each distinct point (as written to the file) an index, in the order they are first
+/// drawn. Returns the formatted vertices, and each polyline as a list of vertex indices.
+fn index_vertices(polylines: &[Polyline3]) -> (Vec<String>, Vec<Vec<usize>>) {
+    let mut vertices = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let indexed = polylines
+        .iter()
+        .map(|polyline| {
+            polyline
+                .iter()
+                .map(|point| {
+                    *indices.entry(vertex(*point)).or_insert_with_key(|key| {
+                        vertices.push(key.clone());
+                        vertices.len() - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    (vertices, indexed)
+}
+
+/// Formats a point's coordinates, separated by spaces, with at most 6 decimal places.
+fn vertex(point: Point3) -> String {
+    [point.x, point.y, point.z]
+        .iter()
+        .map(|value| {
+            let text = format!("{:.6}", value);
+            let text = text.trim_end_matches('0').trim_end_matches('.');
+            match text {
+                "-0" => "0".to_string(),
+                _ => text.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn corner() -> Vec<Polyline3> {
+        vec![
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 0.5, 0.0),
+            ],
+            vec![
+                Point3::new(1.0, 0.0, -0.0000001),
+                Point3::new(1.0, 0.0, 2.0),
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_obj() {
+        assert_eq!(
+            write_obj(&corner()),
+            "v 0 0 0\nv 1 0 0\nv 1 0.5 0\nv 1 0 2\nl 1 2 3\nl 2 4\n"
+        );
+    }
+
+    #[test]
+    fn test_ply() {
+        assert_eq!(
+            write_ply(&corner()),
+            "ply\nformat ascii 1.0\nelement vertex 4\nproperty float x\nproperty float y\n\
+             property float z\nelement edge 3\nproperty int vertex1\nproperty int vertex2\n\
+             end_header\n0 0 0\n1 0 0\n1 0.5 0\n1 0 2\n0 1\n1 2\n1 3\n"
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            LineSetFormat::from_path(Path::new("tree.OBJ")),
+            Some(LineSetFormat::Obj)
+        );
+        assert_eq!(
+            LineSetFormat::from_path(Path::new("tree.ply")),
+            Some(LineSetFormat::Ply)
+        );
+        assert_eq!(LineSetFormat::from_path(Path::new("tree.stl")), None);
+    }
+}
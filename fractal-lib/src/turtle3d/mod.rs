@@ -0,0 +1,476 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Turtle program abstractions for drawing in three dimensions.
+//!
+//! A 3D turtle's orientation is a frame of three perpendicular unit vectors: the heading it moves
+//! along, the direction to its left, and the direction above it. Following *The Algorithmic
+//! Beauty of Plants*, it can yaw (turn left or right about its up vector), pitch (tilt its nose
+//! up or down about its left vector), and roll (rotate about its heading).
+//!
+//! Programs can be exported as 3D line sets (see `export`), or projected onto a plane so that
+//! any 2D `Turtle` can draw them (see `projection`).
+
+pub mod export;
+pub mod projection;
+
+use crate::geometry::{deg2rad, Point3};
+use crate::turtle::ProgramSize;
+use std::mem;
+
+/// An orientation for a 3D turtle, as three perpendicular unit vectors where `up` is `heading`
+/// crossed with `left`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub heading: Point3,
+    pub left: Point3,
+    pub up: Point3,
+}
+
+impl Frame {
+    /// A frame heading along the positive X axis, with the positive Y axis to its left and the
+    /// positive Z axis above it. Yawing this frame behaves like turning a 2D turtle in the XY
+    /// plane.
+    pub fn new() -> Frame {
+        Frame {
+            heading: Point3::new(1.0, 0.0, 0.0),
+            left: Point3::new(0.0, 1.0, 0.0),
+            up: Point3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    /// Builds a frame from a heading and a rough left direction, which is made perpendicular to
+    /// the heading.
+    pub fn from_heading_left(heading: Point3, left: Point3) -> Frame {
+        Frame {
+            heading,
+            left,
+            up: heading.cross(left),
+        }
+        .orthonormalized()
+    }
+
+    /// Turns the heading towards `left` by `radians`, about `up`.
+    pub fn yaw(self, radians: f64) -> Frame {
+        let (sin, cos) = radians.sin_cos();
+        Frame {
+            heading: self.heading * cos + self.left * sin,
+            left: self.left * cos - self.heading * sin,
+            up: self.up,
+        }
+        .orthonormalized()
+    }
+
+    /// Tilts the heading towards `up` by `radians`, about `left`.
+    pub fn pitch(self, radians: f64) -> Frame {
+        let (sin, cos) = radians.sin_cos();
+        Frame {
+            heading: self.heading * cos + self.up * sin,
+            left: self.left,
+            up: self.up * cos - self.heading * sin,
+        }
+        .orthonormalized()
+    }
+
+    /// Rotates `left` towards `up` by `radians`, about the heading. Seen from behind the turtle,
+    /// positive angles roll it clockwise.
+    pub fn roll(self, radians: f64) -> Frame {
+        let (sin, cos) = radians.sin_cos();
+        Frame {
+            heading: self.heading,
+            left: self.left * cos + self.up * sin,
+            up: self.up * cos - self.left * sin,
+        }
+        .orthonormalized()
+    }
+
+    /// Corrects the rounding errors that build up over many rotations, keeping the heading and
+    /// making the other two vectors perpendicular to it.
+    fn orthonormalized(self) -> Frame {
+        let heading = self.heading.normalized();
+        let left = (self.left - heading * self.left.dot(heading)).normalized();
+        Frame {
+            heading,
+            left,
+            up: heading.cross(left),
+        }
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `Turtle3d` is the 3D counterpart to `turtle::Turtle`: it has a position and an orientation,
+/// and draws lines as it moves forward while its pen is down.
+pub trait Turtle3d {
+    /// How far the turtle should move forward along its heading.
+    fn forward(&mut self, distance: f64);
+
+    /// Move the turtle to the specified coordinates without drawing.
+    fn set_pos(&mut self, new_pos: Point3);
+
+    /// Set the turtle's orientation.
+    fn set_frame(&mut self, frame: Frame);
+
+    /// Turn left (or right, for negative angles) about the turtle's up vector.
+    fn yaw(&mut self, radians: f64);
+
+    /// Tilt the turtle's nose up (or down, for negative angles) about its left vector.
+    fn pitch(&mut self, radians: f64);
+
+    /// Roll the turtle clockwise (or counter-clockwise, for negative angles) about its heading.
+    fn roll(&mut self, radians: f64);
+
+    /// Touch the turtle's pen to the drawing surface.
+    fn down(&mut self);
+
+    /// Lift the turtle's pen off of the drawing surface.
+    fn up(&mut self);
+
+    /// Save the turtle's position, orientation, and pen onto a stack.
+    fn push(&mut self);
+
+    /// Restore the turtle's most recently pushed position, orientation, and pen.
+    fn pop(&mut self);
+
+    /// Perform the action represented by `step`.
+    fn perform(&mut self, step: Turtle3dStep) {
+        match step {
+            Turtle3dStep::Forward(dist) => self.forward(dist),
+            Turtle3dStep::SetPos(point) => self.set_pos(point),
+            Turtle3dStep::SetFrame(frame) => self.set_frame(frame),
+            Turtle3dStep::Yaw(angle) => self.yaw(angle),
+            Turtle3dStep::Pitch(angle) => self.pitch(angle),
+            Turtle3dStep::Roll(angle) => self.roll(angle),
+            Turtle3dStep::Down => self.down(),
+            Turtle3dStep::Up => self.up(),
+            Turtle3dStep::Push => self.push(),
+            Turtle3dStep::Pop => self.pop(),
+        }
+    }
+}
+
+/// Represents the possible actions that a `Turtle3dProgram` can perform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Turtle3dStep {
+    /// Make the turtle move forward some distance along its heading.
+    Forward(f64),
+    /// Move the turtle to the specified point without drawing.
+    SetPos(Point3),
+    /// Set the turtle's orientation.
+    SetFrame(Frame),
+    /// Turn about the turtle's up vector, in radians. Positive angles turn left.
+    Yaw(f64),
+    /// Tilt about the turtle's left vector, in radians. Positive angles pitch the nose up.
+    Pitch(f64),
+    /// Rotate about the turtle's heading, in radians. Positive angles roll clockwise.
+    Roll(f64),
+    /// Touch the turtle's pen to the drawing surface.
+    Down,
+    /// Lift the turtle's pen off of the drawing surface.
+    Up,
+    /// Save the turtle's position, orientation, and pen, such as at the start of a branch.
+    Push,
+    /// Return the turtle to the state saved by the matching `Push`, without drawing. Does
+    /// nothing if nothing has been pushed.
+    Pop,
+}
+
+impl Turtle3dStep {
+    /// Convenience for a yaw in degrees.
+    pub fn yaw_deg(degrees: f64) -> Turtle3dStep {
+        Turtle3dStep::Yaw(deg2rad(degrees))
+    }
+
+    /// Convenience for a pitch in degrees.
+    pub fn pitch_deg(degrees: f64) -> Turtle3dStep {
+        Turtle3dStep::Pitch(deg2rad(degrees))
+    }
+
+    /// Convenience for a roll in degrees.
+    pub fn roll_deg(degrees: f64) -> Turtle3dStep {
+        Turtle3dStep::Roll(deg2rad(degrees))
+    }
+}
+
+/// Internal state of a 3D turtle, which turtle implementations can use to track where they are.
+#[derive(Clone, Debug)]
+pub struct Turtle3dState {
+    pub position: Point3,
+    pub frame: Frame,
+    pub down: bool,
+    /// States saved by `push()`.
+    pub stack: Vec<Turtle3dState>,
+}
+
+impl Turtle3dState {
+    /// Initializes a new Turtle3dState at the origin, with the default `Frame`, and its pen down.
+    pub fn new() -> Turtle3dState {
+        Turtle3dState {
+            position: Point3::default(),
+            frame: Frame::new(),
+            down: true,
+            stack: Vec::new(),
+        }
+    }
+
+    /// The point `distance` along the turtle's heading.
+    pub fn ahead(&self, distance: f64) -> Point3 {
+        self.position + self.frame.heading * distance
+    }
+
+    /// Saves a copy of everything but the stack onto the stack.
+    pub fn push(&mut self) {
+        let stack = mem::take(&mut self.stack);
+        let saved = self.clone();
+        self.stack = stack;
+        self.stack.push(saved);
+    }
+
+    /// Restores the most recently pushed state. Returns false, without changing anything, if
+    /// the stack is empty.
+    pub fn pop(&mut self) -> bool {
+        match self.stack.pop() {
+            Some(saved) => {
+                let stack = mem::take(&mut self.stack);
+                *self = saved;
+                self.stack = stack;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for Turtle3dState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The 3D counterpart to `turtle::TurtleProgram`: an initializer that places the turtle into its
+/// starting state, and an iterator over the steps of the main program.
+pub trait Turtle3dProgram {
+    /// Returns a sequence of steps that initialize the turtle's starting position and
+    /// orientation.
+    fn init_turtle(&self) -> Vec<Turtle3dStep>;
+
+    /// Returns an iterator that yields each step the turtle will take. Like
+    /// `TurtleProgram::turtle_program_iter()`, it must not borrow from the program.
+    fn turtle_program_iter(&self) -> Box<dyn Iterator<Item = Turtle3dStep>>;
+
+    /// Predicts how big the program is without running it, like
+    /// `TurtleProgram::predicted_size()`. The default implementation returns `None`.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        None
+    }
+}
+
+impl<P: Turtle3dProgram + ?Sized> Turtle3dProgram for Box<P> {
+    fn init_turtle(&self) -> Vec<Turtle3dStep> {
+        (**self).init_turtle()
+    }
+
+    fn turtle_program_iter(&self) -> Box<dyn Iterator<Item = Turtle3dStep>> {
+        (**self).turtle_program_iter()
+    }
+
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        (**self).predicted_size()
+    }
+}
+
+/// A connected sequence of 3D line segments.
+pub type Polyline3 = Vec<Point3>;
+
+/// A `Turtle3d` that records the lines it draws as polylines. A polyline ends whenever the pen
+/// is lifted or the turtle jumps to another position.
+pub struct LineSetTurtle {
+    state: Turtle3dState,
+    polylines: Vec<Polyline3>,
+    continuing: bool,
+}
+
+impl LineSetTurtle {
+    pub fn new() -> LineSetTurtle {
+        LineSetTurtle {
+            state: Turtle3dState::new(),
+            polylines: Vec::new(),
+            continuing: false,
+        }
+    }
+
+    /// Returns the recorded polylines.
+    pub fn into_polylines(self) -> Vec<Polyline3> {
+        self.polylines
+    }
+}
+
+impl Default for LineSetTurtle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Turtle3d for LineSetTurtle {
+    fn forward(&mut self, distance: f64) {
+        let start = self.state.position;
+        self.state.position = self.state.ahead(distance);
+        if !self.state.down {
+            return;
+        }
+        if !self.continuing {
+            self.polylines.push(vec![start]);
+            self.continuing = true;
+        }
+        self.polylines.last_mut().unwrap().push(self.state.position);
+    }
+
+    fn set_pos(&mut self, new_pos: Point3) {
+        self.state.position = new_pos;
+        self.continuing = false;
+    }
+
+    fn set_frame(&mut self, frame: Frame) {
+        self.state.frame = frame;
+    }
+
+    fn yaw(&mut self, radians: f64) {
+        self.state.frame = self.state.frame.yaw(radians);
+    }
+
+    fn pitch(&mut self, radians: f64) {
+        self.state.frame = self.state.frame.pitch(radians);
+    }
+
+    fn roll(&mut self, radians: f64) {
+        self.state.frame = self.state.frame.roll(radians);
+    }
+
+    fn down(&mut self) {
+        self.state.down = true;
+    }
+
+    fn up(&mut self) {
+        self.state.down = false;
+        self.continuing = false;
+    }
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        if self.state.pop() {
+            self.continuing = false;
+        }
+    }
+}
+
+/// Runs `program` and returns the polylines it draws.
+pub fn record(program: &dyn Turtle3dProgram) -> Vec<Polyline3> {
+    let mut turtle = LineSetTurtle::new();
+    for step in program.init_turtle() {
+        turtle.perform(step);
+    }
+    for step in program.turtle_program_iter() {
+        turtle.perform(step);
+    }
+    turtle.into_polylines()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close(actual: Point3, expected: Point3) {
+        assert!(
+            actual.distance_to(expected) < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_frame_rotations() {
+        let frame = Frame::new();
+        // yawing left turns towards +Y, like a 2D turtle
+        assert_close(frame.yaw(FRAC_PI_2).heading, Point3::new(0.0, 1.0, 0.0));
+        assert_close(frame.pitch(FRAC_PI_2).heading, Point3::new(0.0, 0.0, 1.0));
+        assert_close(frame.pitch(FRAC_PI_2).up, Point3::new(-1.0, 0.0, 0.0));
+        assert_close(frame.roll(FRAC_PI_2).left, Point3::new(0.0, 0.0, 1.0));
+        assert_close(frame.roll(FRAC_PI_2).heading, frame.heading);
+
+        // rotations stay right-handed and orthonormal
+        let twisted = frame.yaw(0.3).pitch(1.1).roll(-2.0);
+        assert_close(twisted.heading.cross(twisted.left), twisted.up);
+        assert!((twisted.left.length() - 1.0).abs() < 1e-12);
+        assert!(twisted.heading.dot(twisted.left).abs() < 1e-12);
+
+        let built =
+            Frame::from_heading_left(Point3::new(0.0, 0.0, 2.0), Point3::new(1.0, 0.0, 1.0));
+        assert_close(built.heading, Point3::new(0.0, 0.0, 1.0));
+        assert_close(built.left, Point3::new(1.0, 0.0, 0.0));
+        assert_close(built.up, Point3::new(0.0, 1.0, 0.0));
+    }
+
+    struct Staple;
+
+    impl Turtle3dProgram for Staple {
+        fn init_turtle(&self) -> Vec<Turtle3dStep> {
+            vec![Turtle3dStep::SetPos(Point3::new(0.0, 0.0, 1.0))]
+        }
+
+        fn turtle_program_iter(&self) -> Box<dyn Iterator<Item = Turtle3dStep>> {
+            Box::new(
+                vec![
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::Push,
+                    Turtle3dStep::pitch_deg(-90.0),
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::Pop,
+                    Turtle3dStep::yaw_deg(90.0),
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::Up,
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::Down,
+                    Turtle3dStep::roll_deg(90.0),
+                    Turtle3dStep::pitch_deg(90.0),
+                    Turtle3dStep::Forward(1.0),
+                ]
+                .into_iter(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let polylines = record(&Staple);
+        assert_eq!(polylines.len(), 3);
+        assert_eq!(polylines[0].len(), 3);
+        assert_close(polylines[0][0], Point3::new(0.0, 0.0, 1.0));
+        assert_close(polylines[0][1], Point3::new(1.0, 0.0, 1.0));
+        assert_close(polylines[0][2], Point3::new(1.0, 0.0, 0.0));
+        assert_close(polylines[1][0], Point3::new(1.0, 0.0, 1.0));
+        assert_close(polylines[1][1], Point3::new(1.0, 1.0, 1.0));
+        // rolling clockwise turns the turtle's up vector from +Z to +X
+        assert_close(polylines[2][0], Point3::new(1.0, 2.0, 1.0));
+        assert_close(polylines[2][1], Point3::new(2.0, 2.0, 1.0));
+    }
+}
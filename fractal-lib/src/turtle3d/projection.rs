@@ -0,0 +1,379 @@
+// Copyright (c) 2015-2019 William (B.J.) Snow Orvis
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Projects 3D turtle programs onto a plane, so that the 2D renderers can draw them.
+//!
+//! `ProjectedTurtleProgram` is a `TurtleProgram` that follows a `Turtle3dProgram` and turns each
+//! 3D line into the equivalent 2D turtle steps, as seen by a `Camera` orbiting the drawing.
+
+use super::{record, Frame, Turtle3d, Turtle3dProgram, Turtle3dState, Turtle3dStep};
+use crate::geometry::{deg2rad, Point, Point3};
+use crate::turtle::{ProgramSize, TurtleProgram, TurtleProgramIterator, TurtleStep};
+use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+
+/// The camera's default angle around the Z axis, in degrees.
+pub const DEFAULT_AZIMUTH: f64 = 30.0;
+
+/// The camera's default angle above the XY plane, in degrees.
+pub const DEFAULT_ELEVATION: f64 = 20.0;
+
+/// How far a perspective camera is from the center of the drawing, in multiples of the radius of
+/// a sphere that encloses the drawing.
+pub const PERSPECTIVE_DISTANCE: f64 = 3.0;
+
+/// How 3D points are flattened onto the camera's view plane.
+#[derive(Copy, Clone, Debug, PartialEq, EnumString, EnumIter, IntoStaticStr, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum Projection {
+    /// Parallel lines stay parallel, and distant lines are as long as near ones.
+    Orthographic,
+    /// Distant lines appear smaller, as they would to an eye or a real camera.
+    Perspective,
+}
+
+/// Where the drawing is viewed from. The camera orbits the center of the drawing and always
+/// looks towards it, with the positive Z axis pointing up on the screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub projection: Projection,
+    /// The camera's angle around the Z axis in radians, where 0 is on the positive X axis.
+    pub azimuth: f64,
+    /// The camera's angle above the XY plane in radians.
+    pub elevation: f64,
+}
+
+impl Camera {
+    pub fn new(projection: Projection, azimuth: f64, elevation: f64) -> Camera {
+        Camera {
+            projection,
+            azimuth,
+            elevation,
+        }
+    }
+}
+
+impl Default for Camera {
+    /// An orthographic camera at `DEFAULT_AZIMUTH` and `DEFAULT_ELEVATION`.
+    fn default() -> Self {
+        Camera::new(
+            Projection::Orthographic,
+            deg2rad(DEFAULT_AZIMUTH),
+            deg2rad(DEFAULT_ELEVATION),
+        )
+    }
+}
+
+/// A camera placed relative to a particular drawing.
+#[derive(Copy, Clone, Debug)]
+struct Projector {
+    center: Point3,
+    /// Unit vectors in the directions of the screen's X and Y axes, and towards the camera.
+    right: Point3,
+    up: Point3,
+    towards_camera: Point3,
+    /// For a perspective projection, how far the camera is from `center`.
+    distance: Option<f64>,
+}
+
+impl Projector {
+    fn new(camera: Camera, center: Point3, radius: f64) -> Projector {
+        let (sin_azimuth, cos_azimuth) = camera.azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = camera.elevation.sin_cos();
+        Projector {
+            center,
+            right: Point3::new(-sin_azimuth, cos_azimuth, 0.0),
+            up: Point3::new(
+                -sin_elevation * cos_azimuth,
+                -sin_elevation * sin_azimuth,
+                cos_elevation,
+            ),
+            towards_camera: Point3::new(
+                cos_elevation * cos_azimuth,
+                cos_elevation * sin_azimuth,
+                sin_elevation,
+            ),
+            distance: match camera.projection {
+                Projection::Orthographic => None,
+                Projection::Perspective => Some(PERSPECTIVE_DISTANCE * radius),
+            },
+        }
+    }
+
+    /// Finds where `point` appears on the screen. Points at the center of the drawing appear at
+    /// the same scale for both projections.
+    fn project(&self, point: Point3) -> Point {
+        let offset = point - self.center;
+        let scale = match self.distance {
+            Some(distance) => distance / (distance - offset.dot(self.towards_camera)),
+            None => 1.0,
+        };
+        Point {
+            x: offset.dot(self.right) * scale,
+            y: offset.dot(self.up) * scale,
+        }
+    }
+
+    /// Converts a 3D step into the 2D steps that draw its projection, updating `turtle`.
+    fn project_step(&self, turtle: &mut StateTurtle, step: Turtle3dStep) -> Vec<TurtleStep> {
+        let start = turtle.0.position;
+        let was_down = turtle.0.down;
+        let popped = step == Turtle3dStep::Pop && !turtle.0.stack.is_empty();
+        turtle.perform(step);
+        let state = &turtle.0;
+        match step {
+            Turtle3dStep::Forward(_) if state.down => {
+                let start = self.project(start);
+                let end = self.project(state.position);
+                vec![
+                    TurtleStep::SetRad((end.y - start.y).atan2(end.x - start.x)),
+                    TurtleStep::Forward(start.distance_to(end)),
+                ]
+            }
+            Turtle3dStep::Forward(_) | Turtle3dStep::SetPos(_) => {
+                vec![TurtleStep::SetPos(self.project(state.position))]
+            }
+            Turtle3dStep::Pop if popped => {
+                let mut steps = vec![TurtleStep::SetPos(self.project(state.position))];
+                if state.down != was_down {
+                    steps.push(pen_step(state.down));
+                }
+                steps
+            }
+            Turtle3dStep::Down | Turtle3dStep::Up => vec![pen_step(state.down)],
+            _ => vec![],
+        }
+    }
+}
+
+fn pen_step(down: bool) -> TurtleStep {
+    if down {
+        TurtleStep::Down
+    } else {
+        TurtleStep::Up
+    }
+}
+
+/// A `TurtleProgram` that draws the projection of a `Turtle3dProgram`.
+pub struct ProjectedTurtleProgram<P: Turtle3dProgram> {
+    program: P,
+    projector: Projector,
+}
+
+impl<P: Turtle3dProgram> ProjectedTurtleProgram<P> {
+    /// Projects `program` as seen by `camera`. This runs the program once, to find the center of
+    /// the drawing for the camera to orbit.
+    pub fn new(program: P, camera: Camera) -> ProjectedTurtleProgram<P> {
+        let mut points: Vec<Point3> = record(&program).into_iter().flatten().collect();
+        if points.is_empty() {
+            points.push(initial_turtle(&program).0.position);
+        }
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (
+                Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        });
+        let center = (min + max) * 0.5;
+        let radius = points
+            .iter()
+            .map(|point| point.distance_to(center))
+            .fold(0.0, f64::max);
+        ProjectedTurtleProgram {
+            program,
+            projector: Projector::new(camera, center, if radius > 0.0 { radius } else { 1.0 }),
+        }
+    }
+}
+
+/// A 3D turtle that has been initialized by `program`.
+fn initial_turtle(program: &dyn Turtle3dProgram) -> StateTurtle {
+    let mut turtle = StateTurtle(Turtle3dState::new());
+    for step in program.init_turtle() {
+        turtle.perform(step);
+    }
+    turtle
+}
+
+impl<P: Turtle3dProgram> TurtleProgram for ProjectedTurtleProgram<P> {
+    fn init_turtle(&self) -> Vec<TurtleStep> {
+        let state = initial_turtle(&self.program).0;
+        vec![
+            TurtleStep::SetPos(self.projector.project(state.position)),
+            TurtleStep::SetRad(0.0),
+            pen_step(state.down),
+        ]
+    }
+
+    fn turtle_program_iter(&self) -> TurtleProgramIterator {
+        let projector = self.projector;
+        let mut turtle = initial_turtle(&self.program);
+        TurtleProgramIterator::new(Box::new(
+            self.program
+                .turtle_program_iter()
+                .flat_map(move |step| projector.project_step(&mut turtle, step)),
+        ))
+    }
+
+    /// Each 3D step is projected into at most two steps, and only moves forward draw segments.
+    fn predicted_size(&self) -> Option<ProgramSize> {
+        let size = self.program.predicted_size()?;
+        Some(ProgramSize {
+            steps: size.steps.saturating_mul(2),
+            ..size
+        })
+    }
+}
+
+/// A `Turtle3d` that only keeps track of where it is, without drawing anything.
+struct StateTurtle(Turtle3dState);
+
+impl Turtle3d for StateTurtle {
+    fn forward(&mut self, distance: f64) {
+        self.0.position = self.0.ahead(distance);
+    }
+
+    fn set_pos(&mut self, new_pos: Point3) {
+        self.0.position = new_pos;
+    }
+
+    fn set_frame(&mut self, frame: Frame) {
+        self.0.frame = frame;
+    }
+
+    fn yaw(&mut self, radians: f64) {
+        self.0.frame = self.0.frame.yaw(radians);
+    }
+
+    fn pitch(&mut self, radians: f64) {
+        self.0.frame = self.0.frame.pitch(radians);
+    }
+
+    fn roll(&mut self, radians: f64) {
+        self.0.frame = self.0.frame.roll(radians);
+    }
+
+    fn down(&mut self) {
+        self.0.down = true;
+    }
+
+    fn up(&mut self) {
+        self.0.down = false;
+    }
+
+    fn push(&mut self) {
+        self.0.push();
+    }
+
+    fn pop(&mut self) {
+        self.0.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::turtle::polyline;
+
+    /// The 12 edges of a unit cube, centered on the origin.
+    struct Cube;
+
+    impl Turtle3dProgram for Cube {
+        fn init_turtle(&self) -> Vec<Turtle3dStep> {
+            vec![
+                Turtle3dStep::Up,
+                Turtle3dStep::SetPos(Point3::new(-0.5, -0.5, -0.5)),
+            ]
+        }
+
+        fn turtle_program_iter(&self) -> Box<dyn Iterator<Item = Turtle3dStep>> {
+            let mut steps = vec![Turtle3dStep::Down];
+            // a square around the bottom, with a pillar up from each corner
+            for _ in 0..4 {
+                steps.extend(vec![
+                    Turtle3dStep::Push,
+                    Turtle3dStep::pitch_deg(90.0),
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::pitch_deg(-90.0),
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::Pop,
+                    Turtle3dStep::Forward(1.0),
+                    Turtle3dStep::yaw_deg(90.0),
+                ]);
+            }
+            Box::new(steps.into_iter())
+        }
+    }
+
+    fn projected(camera: Camera) -> Vec<Vec<Point>> {
+        polyline::record(&ProjectedTurtleProgram::new(Cube, camera))
+    }
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            actual.distance_to(expected) < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_orthographic() {
+        // looking along the X axis, the cube is a square
+        let polylines = projected(Camera::new(Projection::Orthographic, 0.0, 0.0));
+        // popping back to the bottom square jumps without drawing, which splits the lines
+        assert_eq!(polylines.len(), 5);
+        for polyline in polylines {
+            for point in polyline {
+                assert!((point.x.abs() - 0.5).abs() < 1e-9);
+                assert!((point.y.abs() - 0.5).abs() < 1e-9);
+            }
+        }
+
+        // looking down from above, +X is down the screen and +Y is to the right
+        let polylines = projected(Camera::new(Projection::Orthographic, 0.0, deg2rad(90.0)));
+        assert_close(polylines[0][0], Point { x: -0.5, y: 0.5 });
+        assert_close(polylines[1][0], Point { x: -0.5, y: 0.5 });
+        assert_close(polylines[1][1], Point { x: -0.5, y: -0.5 });
+    }
+
+    #[test]
+    fn test_perspective() {
+        let polylines = projected(Camera::new(Projection::Perspective, 0.0, 0.0));
+        // the far face of the cube appears smaller than the near face
+        let sizes: Vec<f64> = polylines
+            .iter()
+            .flatten()
+            .map(|point| point.x.abs())
+            .collect();
+        let smallest = sizes.iter().cloned().fold(f64::MAX, f64::min);
+        let largest = sizes.iter().cloned().fold(0.0, f64::max);
+        assert!(smallest < 0.5 && largest > 0.5);
+    }
+
+    #[test]
+    fn test_projected_steps() {
+        let program = ProjectedTurtleProgram::new(Cube, Camera::default());
+        let steps: Vec<TurtleStep> = program.turtle_program_iter().collect();
+        // each 3D line becomes a SetRad and a Forward
+        assert_eq!(
+            steps
+                .iter()
+                .filter(|step| matches!(step, TurtleStep::Forward(_)))
+                .count(),
+            12
+        );
+        assert_eq!(program.init_turtle()[2], TurtleStep::Up);
+    }
+}
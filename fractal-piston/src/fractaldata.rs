@@ -18,18 +18,47 @@ use super::pistonrendering;
 use fractal_lib::chaosgame::barnsleyfern;
 use fractal_lib::chaosgame::sierpinski::SierpinskiChaosGame;
 use fractal_lib::chaosgame::ChaosGameMoveIterator;
+use fractal_lib::color;
+use fractal_lib::color::palette::{NamedPalette, Palette};
+use fractal_lib::color::palettefile::{self, PaletteError, PaletteFormat};
 use fractal_lib::curves::cesaro::CesaroFractal;
 use fractal_lib::curves::cesarotri::CesaroTriFractal;
 use fractal_lib::curves::dragon::DragonFractal;
+use fractal_lib::curves::folding::{FoldPattern, FoldingCurve};
+use fractal_lib::curves::fractalplant::FractalPlant;
+use fractal_lib::curves::gosper::GosperCurve;
+use fractal_lib::curves::hilbert::HilbertCurve;
+use fractal_lib::curves::hilbert3d::Hilbert3d;
 use fractal_lib::curves::kochcurve::KochCurve;
 use fractal_lib::curves::levyccurve::LevyCCurve;
+use fractal_lib::curves::moore::MooreCurve;
+use fractal_lib::curves::parametrictree::ParametricTree;
+use fractal_lib::curves::peano::PeanoCurve;
+use fractal_lib::curves::quadratickoch::QuadraticKochCurve;
+use fractal_lib::curves::sierpinskiarrowhead::SierpinskiArrowhead;
+use fractal_lib::curves::sierpinskicurve::SierpinskiCurve;
 use fractal_lib::curves::terdragon::TerdragonFractal;
+use fractal_lib::curves::tree3d::Tree3d;
 use fractal_lib::escapetime::burningship::*;
+use fractal_lib::escapetime::coloring::ColoringMode;
 use fractal_lib::escapetime::mandelbrot::Mandelbrot;
 use fractal_lib::escapetime::EscapeTime;
-use fractal_lib::lindenmayer::LindenmayerSystemTurtleProgram;
-use fractal_lib::turtle::TurtleProgram;
+use fractal_lib::geometry::deg2rad;
+use fractal_lib::lindenmayer::dynamic::{DynamicLSystem, DynamicLSystemError};
+use fractal_lib::lindenmayer::parametric::{is_parametric, ParametricLSystem};
+use fractal_lib::lindenmayer::{
+    ExpansionStrategy, LindenmayerSystemTurtle3dProgram, LindenmayerSystemTurtleProgram,
+};
+use fractal_lib::turtle::coloring::{ColoredTurtleProgram, SegmentColoring, SegmentColoringMode};
+use fractal_lib::turtle::plotter::{self, PlotterFormat, PlotterSettings};
+use fractal_lib::turtle::polyline::TurtleStats;
+use fractal_lib::turtle::svg;
+use fractal_lib::turtle::{ProgramSize, TurtleProgram};
+use fractal_lib::turtle3d::export::{self, LineSetFormat};
+use fractal_lib::turtle3d::projection::{Camera, ProjectedTurtleProgram, Projection};
+use fractal_lib::turtle3d::Turtle3dProgram;
 use fractal_lib::{FractalCategory, SelectedFractal};
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
@@ -57,6 +86,29 @@ where
     }
 }
 
+/// Resolves the `--palette` argument, which is either a palette file or the name of one of the
+/// built-in palettes. A file takes precedence over a built-in palette with the same name.
+fn parse_palette(value: &str) -> Result<Palette, String> {
+    let path = Path::new(value);
+    if !path.exists() {
+        if let Ok(named) = NamedPalette::from_str(value) {
+            return Ok(named.palette());
+        }
+        if PaletteFormat::from_path(path).is_none() {
+            return Err(format!(
+                "Error parsing palette: {:?} is not a palette file or one of the built-in \
+                 palettes: {}",
+                value,
+                <NamedPalette as strum::VariantNames>::VARIANTS.join(", ")
+            ));
+        }
+    }
+    palettefile::load_palette(path).map_err(|e| match e {
+        PaletteError::Parse { .. } => format!("Error loading palette {}: {}", value, e),
+        _ => format!("Error loading palette: {}", e),
+    })
+}
+
 fn run_chaos_game<E, F>(ctor: &F, matches: &clap::ArgMatches) -> Result<(), String>
 where
     E: ChaosGameMoveIterator + 'static,
@@ -80,31 +132,363 @@ where
     // .unwrap_or_else(|| return Err("Must specify a MAX_ITERATIONS of 1 or greater!"));
     let power = (extract!(matches, "POWER"))?;
     // .unwrap_or_else(|| return Err("Must specify a POWER of 1 or greater!"));
+    let palette = parse_palette(matches.get_one::<String>("palette").unwrap())?;
+    let coloring: ColoringMode = extract!(matches, "coloring")?;
+    let smooth = matches.get_flag("smooth");
 
     let et = Arc::new((ctor)(max_iterations, power));
     // TODO: `et` when passed in here wants E to be constraint by `'static`. Why?
-    let mut handler = pistonrendering::escapetime::EscapeTimeWindowHandler::new(et);
+    let mut handler =
+        pistonrendering::escapetime::EscapeTimeWindowHandler::new(et, palette, coloring, smooth);
     pistonrendering::run(&mut handler);
 
     Ok(())
 }
 
-fn run_turtle<E, F>(ctor: &F, matches: &clap::ArgMatches) -> Result<(), String>
-where
-    E: TurtleProgram + 'static,
-    F: Fn(u64) -> E,
-{
-    let drawrate = (extract!(matches, "drawrate"))?;
-    let iteration = (extract!(matches, "ITERATION"))?;
+/// Constructs the turtle program for `iteration` of a turtle curve, or `None` if `fractal` is not
+/// a turtle curve. 3D turtle curves are projected with the default camera.
+fn turtle_program(fractal: SelectedFractal, iteration: u64) -> Option<Box<dyn TurtleProgram>> {
+    let program: Box<dyn TurtleProgram> = match fractal {
+        SelectedFractal::Cesaro => Box::new(LindenmayerSystemTurtleProgram::new(
+            CesaroFractal::new(iteration),
+        )),
+        // grows quickly enough that caching every iteration exhausts memory
+        SelectedFractal::CesaroTri => Box::new(
+            LindenmayerSystemTurtleProgram::new(CesaroTriFractal::new(iteration))
+                .with_expansion(ExpansionStrategy::Streaming),
+        ),
+        SelectedFractal::Dragon => Box::new(DragonFractal::new(iteration)),
+        SelectedFractal::FractalPlant => Box::new(LindenmayerSystemTurtleProgram::new(
+            FractalPlant::new(iteration),
+        )),
+        SelectedFractal::Gosper => Box::new(LindenmayerSystemTurtleProgram::new(GosperCurve::new(
+            iteration,
+        ))),
+        SelectedFractal::Hilbert => Box::new(LindenmayerSystemTurtleProgram::new(
+            HilbertCurve::new(iteration),
+        )),
+        SelectedFractal::Moore => Box::new(LindenmayerSystemTurtleProgram::new(MooreCurve::new(
+            iteration,
+        ))),
+        SelectedFractal::PaperFolding => Box::new(FoldingCurve::new(
+            iteration,
+            FoldPattern::alternate(),
+            std::f64::consts::FRAC_PI_2,
+        )),
+        SelectedFractal::ParametricTree => Box::new(LindenmayerSystemTurtleProgram::new(
+            ParametricTree::new(iteration),
+        )),
+        SelectedFractal::Peano => Box::new(LindenmayerSystemTurtleProgram::new(PeanoCurve::new(
+            iteration,
+        ))),
+        SelectedFractal::QuadraticKoch => Box::new(LindenmayerSystemTurtleProgram::new(
+            QuadraticKochCurve::new(iteration),
+        )),
+        SelectedFractal::SierpinskiArrowhead => Box::new(LindenmayerSystemTurtleProgram::new(
+            SierpinskiArrowhead::new(iteration),
+        )),
+        SelectedFractal::SierpinskiCurve => Box::new(LindenmayerSystemTurtleProgram::new(
+            SierpinskiCurve::new(iteration),
+        )),
+        SelectedFractal::KochCurve => Box::new(LindenmayerSystemTurtleProgram::new(
+            KochCurve::new(iteration),
+        )),
+        SelectedFractal::LevyCCurve => Box::new(LindenmayerSystemTurtleProgram::new(
+            LevyCCurve::new(iteration),
+        )),
+        // like CesaroTri, streamed instead of cached
+        SelectedFractal::TerDragon => Box::new(
+            LindenmayerSystemTurtleProgram::new(TerdragonFractal::new(iteration))
+                .with_expansion(ExpansionStrategy::Streaming),
+        ),
+        _ => match fractal.preset() {
+            Some(preset) => Box::new(LindenmayerSystemTurtleProgram::new(
+                preset.system(iteration),
+            )),
+            None => Box::new(ProjectedTurtleProgram::new(
+                turtle_program_3d(fractal, iteration)?,
+                Camera::default(),
+            )),
+        },
+    };
+    Some(program)
+}
+
+/// Constructs the turtle program for `iteration` of a turtle curve like `turtle_program()`, unless
+/// `check_size()` refuses it. Paperfolding curves are folded as `--folds` and `--fold-angle` say.
+/// 3D turtle curves are checked before they are projected, since projecting them runs them once.
+fn checked_turtle_program(
+    fractal: SelectedFractal,
+    iteration: u64,
+    matches: &clap::ArgMatches,
+) -> Result<Box<dyn TurtleProgram>, String> {
+    if matches!(fractal, SelectedFractal::PaperFolding) {
+        let program = parse_folding_curve(matches, iteration)?;
+        check_size(program.predicted_size(), matches)?;
+        return Ok(Box::new(program));
+    }
+    for name in ["folds", "fold-angle"] {
+        if matches.value_source(name) == Some(clap::parser::ValueSource::CommandLine) {
+            return Err(format!("--{} only applies to paperfolding", name));
+        }
+    }
+    if let Some(program_3d) = turtle_program_3d(fractal, iteration) {
+        check_size(program_3d.predicted_size(), matches)?;
+        return Ok(Box::new(ProjectedTurtleProgram::new(
+            program_3d,
+            Camera::default(),
+        )));
+    }
+    let program = turtle_program(fractal, iteration).unwrap();
+    check_size(program.predicted_size(), matches)?;
+    Ok(program)
+}
+
+/// Constructs the 3D turtle program for `iteration` of a 3D turtle curve, or `None` if `fractal`
+/// is not a 3D turtle curve.
+fn turtle_program_3d(fractal: SelectedFractal, iteration: u64) -> Option<Box<dyn Turtle3dProgram>> {
+    let program: Box<dyn Turtle3dProgram> = match fractal {
+        SelectedFractal::Hilbert3d => Box::new(LindenmayerSystemTurtle3dProgram::new(
+            Hilbert3d::new(iteration),
+        )),
+        SelectedFractal::Tree3d => Box::new(LindenmayerSystemTurtle3dProgram::new(Tree3d::new(
+            iteration,
+        ))),
+        _ => return None,
+    };
+    Some(program)
+}
+
+/// Parses the `--projection`, `--azimuth`, and `--elevation` arguments of a 3D turtle curve.
+fn parse_camera(matches: &clap::ArgMatches) -> Result<Camera, String> {
+    let projection: Projection = extract!(matches, "projection")?;
+    let azimuth: f64 = extract!(matches, "azimuth")?;
+    let elevation: f64 = extract!(matches, "elevation")?;
+    Ok(Camera::new(
+        projection,
+        deg2rad(azimuth),
+        deg2rad(elevation),
+    ))
+}
+
+/// Parses the `--folds` and `--fold-angle` arguments of a paperfolding curve.
+fn parse_folding_curve(matches: &clap::ArgMatches, iteration: u64) -> Result<FoldingCurve, String> {
+    let pattern: FoldPattern = extract!(matches, "folds")?;
+    let angle: f64 = extract!(matches, "fold-angle")?;
+    if angle.abs() >= 180.0 {
+        return Err(format!(
+            "Error parsing fold-angle: {} is not strictly between -180 and 180 degrees",
+            angle
+        ));
+    }
+    Ok(FoldingCurve::new(iteration, pattern, deg2rad(angle)))
+}
+
+fn run_turtle(fractal: SelectedFractal, matches: &clap::ArgMatches) -> Result<(), String> {
+    let iteration = match fractal.preset() {
+        // presets are drawn at their recommended iteration unless asked otherwise
+        Some(preset) if !matches.contains_id("ITERATION") => preset.iteration,
+        _ => (extract!(matches, "ITERATION"))?,
+    };
     // .unwrap_or_else(|| Err("Must specify an ITERATION of 1 or greater!"));
+    let program = match turtle_program_3d(fractal, iteration) {
+        Some(program_3d) => {
+            // projecting runs the program once, so check it before then
+            check_size(program_3d.predicted_size(), matches)?;
+            if let Some(path) = matches.get_one::<String>("export") {
+                let format = LineSetFormat::from_path(Path::new(path)).ok_or_else(|| {
+                    format!(
+                        "Error choosing export format: {:?} does not end in .obj or .ply",
+                        path
+                    )
+                })?;
+                return std::fs::write(path, export::export(&program_3d, format))
+                    .map_err(|e| format!("Error writing {}: {}", path, e));
+            }
+            Box::new(ProjectedTurtleProgram::new(
+                program_3d,
+                parse_camera(matches)?,
+            ))
+        }
+        None => {
+            let program: Box<dyn TurtleProgram> = match fractal {
+                SelectedFractal::PaperFolding => Box::new(parse_folding_curve(matches, iteration)?),
+                _ => turtle_program(fractal, iteration).unwrap(),
+            };
+            check_size(program.predicted_size(), matches)?;
+            program
+        }
+    };
+    render_turtle(program, matches)
+}
+
+/// Draws a turtle program as the arguments from `turtle_args()` ask, in a window or to an SVG
+/// file. The program's size should already have been checked with `check_size()`.
+fn render_turtle(
+    program: Box<dyn TurtleProgram>,
+    matches: &clap::ArgMatches,
+) -> Result<(), String> {
+    let drawrate = (extract!(matches, "drawrate"))?;
+    let start = (extract!(matches, "start"))?;
+    let palette = parse_palette(matches.get_one::<String>("palette").unwrap())?;
+    let mode: SegmentColoringMode = extract!(matches, "coloring")?;
 
-    let program = (ctor)(iteration);
-    let mut handler = pistonrendering::turtle::construct_turtle_window_handler(&program, drawrate);
+    let coloring = SegmentColoring::new(mode, color::BLACK_F32, palette);
+    let program = ColoredTurtleProgram::new(program, coloring);
+
+    if let Some(path) = matches.get_one::<String>("svg") {
+        return std::fs::write(path, svg::render_svg(&program))
+            .map_err(|e| format!("Error writing SVG {}: {}", path, e));
+    }
+    let mut handler =
+        pistonrendering::turtle::construct_turtle_window_handler(&program, drawrate, start);
     pistonrendering::run(&mut *handler);
 
     Ok(())
 }
 
+/// The arguments that every turtle curve accepts, except for which curve and iteration to draw.
+fn turtle_args() -> Vec<clap::Arg> {
+    vec![
+        clap::Arg::new("drawrate")
+            .num_args(1)
+            .help("The number of points to draw per frame")
+            .long("drawrate")
+            .value_name("MPF")
+            .default_value("1"),
+        clap::Arg::new("start")
+            .num_args(1)
+            .help("Start the animation after this many line segments, without drawing them")
+            .long("start")
+            .value_name("SEGMENTS")
+            .default_value("0"),
+        clap::Arg::new("coloring")
+            .num_args(1)
+            .help(
+                "How line segments are colored. progress is a gradient along the curve, symbol \
+                 and depth color segments by the L-system symbol that drew them or the \
+                 iteration that created them",
+            )
+            .long("coloring")
+            .value_name("MODE")
+            .value_parser(clap::builder::PossibleValuesParser::new(
+                <SegmentColoringMode as strum::VariantNames>::VARIANTS,
+            ))
+            .default_value("solid"),
+        clap::Arg::new("palette")
+            .num_args(1)
+            .help(
+                "A palette file (.map, .ggr, or .toml) or the name of a built-in palette, used \
+                 by every coloring except solid",
+            )
+            .long("palette")
+            .value_name("FILE")
+            .default_value("viridis"),
+        clap::Arg::new("svg")
+            .num_args(1)
+            .help("Write the curve to an SVG file instead of opening a window")
+            .long("svg")
+            .value_name("OUT"),
+        force_arg(),
+    ]
+}
+
+/// Turtle programs that are predicted to take more steps than this are refused, unless the
+/// `--force` argument is given.
+const MAX_PREDICTED_STEPS: u128 = 100_000_000;
+
+fn force_arg() -> clap::Arg {
+    clap::Arg::new("force")
+        .help(format!(
+            "Draw the curve even if it is predicted to take more than {} steps",
+            MAX_PREDICTED_STEPS
+        ))
+        .long("force")
+        .action(clap::ArgAction::SetTrue)
+}
+
+/// Refuses programs whose predicted `size` is more than `MAX_PREDICTED_STEPS`, before any time or
+/// memory is spent on them, unless `--force` is given.
+fn check_size(size: Option<ProgramSize>, matches: &clap::ArgMatches) -> Result<(), String> {
+    if matches.get_flag("force") {
+        return Ok(());
+    }
+    match size {
+        Some(size) if size.steps > MAX_PREDICTED_STEPS => Err(format!(
+            "The curve would take {} steps and draw {} line segments{}, more than the limit of {} \
+             steps. Use --force to draw it anyway",
+            size.steps,
+            size.moves,
+            match size.growth_rate {
+                Some(rate) => format!(", growing {:.2} times with each iteration", rate),
+                None => String::new(),
+            },
+            MAX_PREDICTED_STEPS
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The arguments that 3D turtle curves accept in addition to those of every turtle curve.
+fn camera_args() -> Vec<clap::Arg> {
+    let degrees = |name: &'static str, help: &'static str, default: &'static str| {
+        clap::Arg::new(name)
+            .num_args(1)
+            .help(help)
+            .long(name)
+            .value_name("DEG")
+            .allow_negative_numbers(true)
+            .default_value(default)
+    };
+    vec![
+        clap::Arg::new("projection")
+            .num_args(1)
+            .help("How the curve is flattened onto the screen")
+            .long("projection")
+            .value_name("PROJECTION")
+            .value_parser(clap::builder::PossibleValuesParser::new(
+                <Projection as strum::VariantNames>::VARIANTS,
+            ))
+            .default_value("orthographic"),
+        degrees(
+            "azimuth",
+            "The camera's angle around the Z axis, from the positive X axis",
+            "30",
+        ),
+        degrees("elevation", "The camera's angle above the XY plane", "20"),
+        clap::Arg::new("export")
+            .num_args(1)
+            .help(
+                "Write the curve's 3D lines to a Wavefront OBJ (.obj) or PLY (.ply) file instead \
+                 of opening a window",
+            )
+            .long("export")
+            .value_name("OUT3D"),
+    ]
+}
+
+/// The arguments that paperfolding curves accept in addition to those of every turtle curve.
+fn folding_args() -> Vec<clap::Arg> {
+    vec![
+        clap::Arg::new("folds")
+            .num_args(1)
+            .help(
+                "The direction of each fold, repeated for as many folds as needed: regular, \
+                 alternate, or a word of L and R such as LLR. regular draws the dragon curve",
+            )
+            .long("folds")
+            .value_name("PATTERN")
+            .default_value("alternate"),
+        clap::Arg::new("fold-angle")
+            .num_args(1)
+            .help("How far the curve turns at each crease")
+            .long("fold-angle")
+            .value_name("DEG")
+            .allow_negative_numbers(true)
+            .default_value("90"),
+    ]
+}
+
 trait SelectedFractalExt {
     fn clap_subcommand<'a>(&self) -> clap::builder::Command;
     fn run(&self, matches: &clap::ArgMatches) -> Result<(), String>;
@@ -141,20 +525,58 @@ impl SelectedFractalExt for SelectedFractal {
                         .required(true)
                         .index(2)
                         .help("The exponent used in the escape time function (positive integer)"),
-                ),
-            FractalCategory::TurtleCurves => subcommand
+                )
+                .arg(
+                    clap::Arg::new("palette")
+                        .num_args(1)
+                        .help(
+                            "A palette file (.map, .ggr, or .toml) or the name of a built-in \
+                             palette",
+                        )
+                        .long("palette")
+                        .value_name("FILE")
+                        .default_value("greyscale"),
+                )
                 .arg(
-                    clap::Arg::new("drawrate")
+                    clap::Arg::new("coloring")
                         .num_args(1)
-                        .help("The number of points to draw per frame")
-                        .long("drawrate")
-                        .value_name("MPF")
-                        .default_value("1"),
+                        .help(
+                            "How escape times are mapped onto the palette. histogram spreads \
+                             the most common escape times across more of the palette",
+                        )
+                        .long("coloring")
+                        .value_name("MODE")
+                        .value_parser(clap::builder::PossibleValuesParser::new(
+                            <ColoringMode as strum::VariantNames>::VARIANTS,
+                        ))
+                        .default_value("linear"),
                 )
+                .arg(
+                    clap::Arg::new("smooth")
+                        .help("Use continuous escape times instead of whole iterations")
+                        .long("smooth")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+            FractalCategory::TurtleCurves => subcommand
+                .args(turtle_args())
                 .arg(clap::Arg::new("ITERATION").required(true).index(1).help(
                     "Which iteration of the underlying curve to draw. This usually \
                             causes an exponential growth in required computation",
-                )),
+                ))
+                .args(if self.is_3d() { camera_args() } else { vec![] })
+                .args(if matches!(self, SelectedFractal::PaperFolding) {
+                    folding_args()
+                } else {
+                    vec![]
+                }),
+            FractalCategory::LSystemPresets => {
+                subcommand
+                    .args(turtle_args())
+                    .arg(clap::Arg::new("ITERATION").index(1).help(format!(
+                        "Which iteration of the preset to draw [default: {}]",
+                        self.preset().unwrap().iteration
+                    )))
+            }
         }
     }
 
@@ -173,45 +595,257 @@ impl SelectedFractalExt for SelectedFractal {
             ),
             SelectedFractal::BurningMandel => run_escape_time(&BurningMandel::new, matches),
             SelectedFractal::BurningShip => run_escape_time(&BurningShip::new, matches),
-            SelectedFractal::Cesaro => run_turtle(
-                &LindenmayerSystemTurtleProgram::build(CesaroFractal::new),
-                matches,
-            ),
-            SelectedFractal::CesaroTri => run_turtle(
-                &LindenmayerSystemTurtleProgram::build(CesaroTriFractal::new),
-                matches,
-            ),
-            SelectedFractal::Dragon => run_turtle(&DragonFractal::new, matches),
-            SelectedFractal::KochCurve => run_turtle(
-                &|iteration| LindenmayerSystemTurtleProgram::new(KochCurve::new(iteration)),
-                matches,
-            ),
-            SelectedFractal::LevyCCurve => run_turtle(
-                &LindenmayerSystemTurtleProgram::build(LevyCCurve::new),
-                matches,
-            ),
+            SelectedFractal::Cesaro
+            | SelectedFractal::CesaroTri
+            | SelectedFractal::Dragon
+            | SelectedFractal::FractalPlant
+            | SelectedFractal::Gosper
+            | SelectedFractal::Hilbert
+            | SelectedFractal::Moore
+            | SelectedFractal::PaperFolding
+            | SelectedFractal::ParametricTree
+            | SelectedFractal::Peano
+            | SelectedFractal::QuadraticKoch
+            | SelectedFractal::SierpinskiArrowhead
+            | SelectedFractal::SierpinskiCurve
+            | SelectedFractal::Hilbert3d
+            | SelectedFractal::KochCurve
+            | SelectedFractal::LevyCCurve
+            | SelectedFractal::TerDragon
+            | SelectedFractal::Tree3d => run_turtle(*self, matches),
+            SelectedFractal::AntiSnowflake
+            | SelectedFractal::Board
+            | SelectedFractal::Bush
+            | SelectedFractal::Bush2
+            | SelectedFractal::Bush3
+            | SelectedFractal::Crystal
+            | SelectedFractal::HexSnowflake
+            | SelectedFractal::Islands
+            | SelectedFractal::KochIsland
+            | SelectedFractal::Pentaplexity
+            | SelectedFractal::PlantA
+            | SelectedFractal::PlantB
+            | SelectedFractal::PlantC
+            | SelectedFractal::PlantD
+            | SelectedFractal::PlantE
+            | SelectedFractal::PlantF
+            | SelectedFractal::Rings
+            | SelectedFractal::StochasticPlant
+            | SelectedFractal::TallPlant
+            | SelectedFractal::Tiles
+            | SelectedFractal::Weed => run_turtle(*self, matches),
             SelectedFractal::Mandelbrot => run_escape_time(&Mandelbrot::new, matches),
             SelectedFractal::RoadRunner => run_escape_time(&RoadRunner::new, matches),
             SelectedFractal::Sierpinski => run_chaos_game(&SierpinskiChaosGame::new, matches),
-            SelectedFractal::TerDragon => run_turtle(
-                &LindenmayerSystemTurtleProgram::build(TerdragonFractal::new),
-                matches,
-            ),
         }
     }
 }
 
+/// Adds the `FRACTAL` and `ITERATION` positional arguments shared by subcommands that operate on
+/// any turtle curve, along with the arguments for folding a paperfolding curve.
+fn turtle_curve_args(
+    command: clap::builder::Command,
+    help: &'static str,
+) -> clap::builder::Command {
+    let turtle_curves: Vec<&'static str> = SelectedFractal::iter()
+        .filter(|fractal| fractal.is_turtle())
+        .map(|fractal| fractal.into())
+        .collect();
+    command
+        .arg(
+            clap::Arg::new("FRACTAL")
+                .required(true)
+                .index(1)
+                .help(help)
+                .value_parser(clap::builder::PossibleValuesParser::new(turtle_curves)),
+        )
+        .arg(clap::Arg::new("ITERATION").required(true).index(2).help(
+            "Which iteration of the underlying curve to draw. This usually causes an \
+             exponential growth in required computation",
+        ))
+        .arg(force_arg())
+        .args(
+            folding_args()
+                .into_iter()
+                .map(|arg| arg.help_heading("Paperfolding options")),
+        )
+}
+
+/// Constructs the clap subcommand that prints statistics about a turtle curve.
+fn describe_subcommand() -> clap::builder::Command {
+    turtle_curve_args(
+        clap::Command::new("describe")
+            .about("Prints geometric statistics about a turtle curve without drawing it"),
+        "The turtle curve to describe",
+    )
+}
+
+fn run_describe(matches: &clap::ArgMatches) -> Result<(), String> {
+    let fractal: SelectedFractal = extract!(matches, "FRACTAL")?;
+    let iteration = extract!(matches, "ITERATION")?;
+    let program = checked_turtle_program(fractal, iteration, matches)?;
+    println!("{}", TurtleStats::of_program(&program));
+    if let Some(rate) = program.predicted_size().and_then(|size| size.growth_rate) {
+        println!("growth rate:   {:.6}", rate);
+    }
+    Ok(())
+}
+
+/// Constructs the clap subcommand that writes a turtle curve to a pen plotter file.
+fn plot_subcommand() -> clap::builder::Command {
+    let millimeters = |name: &'static str, help: &'static str, default: &'static str| {
+        clap::Arg::new(name)
+            .num_args(1)
+            .help(help)
+            .long(name)
+            .value_name("MM")
+            .default_value(default)
+    };
+    turtle_curve_args(
+        clap::Command::new("plot")
+            .about("Writes a turtle curve to an HPGL or G-code file for a pen plotter"),
+        "The turtle curve to plot",
+    )
+    .arg(
+        clap::Arg::new("OUT")
+            .required(true)
+            .index(3)
+            .help("The file to write"),
+    )
+    .arg(
+        clap::Arg::new("format")
+            .num_args(1)
+            .help(
+                "The format to write. Defaults to the format matching OUT's extension (.hpgl, \
+                     .plt, .gcode, or .nc)",
+            )
+            .long("format")
+            .value_name("FORMAT")
+            .value_parser(clap::builder::PossibleValuesParser::new(
+                <PlotterFormat as strum::VariantNames>::VARIANTS,
+            )),
+    )
+    .arg(millimeters("paper-width", "The width of the paper", "297"))
+    .arg(millimeters(
+        "paper-height",
+        "The height of the paper",
+        "210",
+    ))
+    .arg(millimeters(
+        "margin",
+        "Space to leave empty around the edges of the paper",
+        "10",
+    ))
+    .arg(
+        millimeters(
+            "feed-rate",
+            "How fast to move the pen while drawing, in mm per minute",
+            "3000",
+        )
+        .value_name("MM_PER_MIN"),
+    )
+    .arg(millimeters(
+        "pen-lift",
+        "How far G-code raises the pen on the Z axis",
+        "2",
+    ))
+}
+
+fn run_plot(matches: &clap::ArgMatches) -> Result<(), String> {
+    let fractal: SelectedFractal = extract!(matches, "FRACTAL")?;
+    let iteration = extract!(matches, "ITERATION")?;
+    let out = matches.get_one::<String>("OUT").unwrap();
+    let format = match matches.get_one::<String>("format") {
+        Some(format) => parse_arg("format", format)?,
+        None => PlotterFormat::from_path(Path::new(out)).ok_or_else(|| {
+            format!(
+                "Error choosing plotter format: {:?} does not end in a known extension, use \
+                 --format",
+                out
+            )
+        })?,
+    };
+    let settings = PlotterSettings {
+        paper_width: extract!(matches, "paper-width")?,
+        paper_height: extract!(matches, "paper-height")?,
+        margin: extract!(matches, "margin")?,
+        feed_rate: extract!(matches, "feed-rate")?,
+        pen_lift: extract!(matches, "pen-lift")?,
+    };
+
+    let program = checked_turtle_program(fractal, iteration, matches)?;
+    std::fs::write(out, plotter::plot(&program, format, &settings))
+        .map_err(|e| format!("Error writing plot {}: {}", out, e))
+}
+
+/// Constructs the clap subcommand that draws an L-system read from a grammar file.
+fn lsystem_subcommand() -> clap::builder::Command {
+    clap::Command::new("lsystem")
+        .about("Draws an L-system defined by a grammar file")
+        .arg(
+            clap::Arg::new("GRAMMAR")
+                .required(true)
+                .index(1)
+                .help("A TOML file with the L-system's axiom, rules, and how to draw its symbols"),
+        )
+        .arg(clap::Arg::new("ITERATION").required(true).index(2).help(
+            "Which iteration of the L-system to draw. This usually causes an exponential growth \
+             in required computation",
+        ))
+        .args(turtle_args())
+        .arg(
+            clap::Arg::new("seed")
+                .num_args(1)
+                .help(
+                    "Choose between the successors of stochastic rules with this seed instead of \
+                     the grammar's",
+                )
+                .long("seed")
+                .value_name("SEED"),
+        )
+}
+
+fn run_lsystem(matches: &clap::ArgMatches) -> Result<(), String> {
+    let grammar = matches.get_one::<String>("GRAMMAR").unwrap();
+    let iteration = extract!(matches, "ITERATION")?;
+    let text = std::fs::read_to_string(grammar)
+        .map_err(|e| format!("Error loading L-system: {}: {}", grammar, e))?;
+    let parse_error = |e: DynamicLSystemError| format!("Error loading L-system {}: {}", grammar, e);
+    let program: Box<dyn TurtleProgram> = if is_parametric(&text) {
+        Box::new(LindenmayerSystemTurtleProgram::new(
+            ParametricLSystem::parse(&text, iteration).map_err(parse_error)?,
+        ))
+    } else {
+        let system = DynamicLSystem::parse(&text, iteration).map_err(parse_error)?;
+        let system = match matches.get_one::<String>("seed") {
+            Some(seed) => system.with_seed(parse_arg("seed", seed)?),
+            None => system,
+        };
+        Box::new(LindenmayerSystemTurtleProgram::new(system))
+    };
+    check_size(program.predicted_size(), matches)?;
+    render_turtle(program, matches)
+}
+
 pub fn add_subcommands<'a>(app: clap::builder::Command) -> clap::builder::Command {
     let mut app = app;
     for fractal in SelectedFractal::iter() {
         app = app.subcommand(fractal.clap_subcommand());
     }
-    app
+    app.subcommand(plot_subcommand())
+        .subcommand(describe_subcommand())
+        .subcommand(lsystem_subcommand())
 }
 
 pub fn run_subcommand(app_argmatches: &clap::ArgMatches) -> Result<(), String> {
     if let Some((name, args)) = app_argmatches.subcommand() {
-        if let Ok(fractal) = SelectedFractal::from_str(name) {
+        if name == "plot" {
+            run_plot(args)
+        } else if name == "describe" {
+            run_describe(args)
+        } else if name == "lsystem" {
+            run_lsystem(args)
+        } else if let Ok(fractal) = SelectedFractal::from_str(name) {
             fractal.run(&args)
         } else {
             Err("Unknown subcommand".to_string())
@@ -18,11 +18,13 @@ use super::super::work_multiplexer::{
 use super::{RenderContext, WindowHandler};
 use ::image::{ImageBuffer, Rgba};
 use fractal_lib::color;
+use fractal_lib::color::palette::Palette;
+use fractal_lib::escapetime::coloring::{self, ColoringMode, PaletteMapping};
 use fractal_lib::escapetime::EscapeTime;
 use fractal_lib::geometry::{Point, ViewAreaTransformer};
 use graphics::math::Vec2d;
 use num::complex::Complex64;
-use std::cmp;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
 type FractalImageBuffer = ImageBuffer<Rgba<u8>, Vec<u8>>;
@@ -31,6 +33,12 @@ type FractalImageBuffer = ImageBuffer<Rgba<u8>, Vec<u8>>;
 /// plane.
 pub struct EscapeTimeWindowHandler {
     etsystem: Arc<dyn EscapeTime + Send + Sync>,
+    /// The palette that escape times are mapped onto.
+    palette: Arc<Palette>,
+    /// How escape times are mapped onto the palette.
+    coloring: ColoringMode,
+    /// Whether to use continuous escape times instead of whole numbers of iterations.
+    smooth: bool,
     screen_size: Vec2d,
     view_area: [Point; 2],
     vat: Arc<ViewAreaTransformer>,
@@ -42,13 +50,21 @@ pub struct EscapeTimeWindowHandler {
 }
 
 impl EscapeTimeWindowHandler {
-    pub fn new(etsystem: Arc<dyn EscapeTime + Send + Sync>) -> EscapeTimeWindowHandler {
+    pub fn new(
+        etsystem: Arc<dyn EscapeTime + Send + Sync>,
+        palette: Palette,
+        coloring: ColoringMode,
+        smooth: bool,
+    ) -> EscapeTimeWindowHandler {
         let canvas = Arc::new(RwLock::new(FractalImageBuffer::new(800, 600)));
         let view_area_c = etsystem.default_view_area();
         let view_area = [Point::from(view_area_c[0]), Point::from(view_area_c[1])];
 
         EscapeTimeWindowHandler {
             etsystem,
+            palette: Arc::new(palette),
+            coloring,
+            smooth,
             screen_size: [800.0, 600.0],
             view_area,
             vat: Arc::new(ViewAreaTransformer::new(
@@ -81,11 +97,19 @@ impl EscapeTimeWindowHandler {
             self.screen_size[1] as u32,
             self.vat.map_pixel_to_point(self.screen_size)
         );
-        let colors = Arc::new(color::color_range_linear(
-            color::BLACK_U8,
-            color::WHITE_U8,
-            cmp::min(self.etsystem.max_iterations(), 50) as usize,
-        ));
+        let linear = Arc::new(PaletteMapping::linear(self.etsystem.max_iterations()));
+        let width = self.screen_size[0] as u32;
+        let height = self.screen_size[1] as u32;
+        // Histogram coloring needs every escape value in the frame before it can color anything,
+        // so the frame is first drawn using linear coloring while the escape values are
+        // collected. Whichever thread finishes the last column then recolors the whole frame.
+        let escape_values = match self.coloring {
+            ColoringMode::Linear => None,
+            ColoringMode::Histogram => {
+                Some(Arc::new(RwLock::new(vec![None; (width * height) as usize])))
+            }
+        };
+        let finished_columns = Arc::new(AtomicUsize::new(0));
 
         self.canvas = Arc::new(RwLock::new(FractalImageBuffer::new(
             self.screen_size[0] as u32,
@@ -96,7 +120,9 @@ impl EscapeTimeWindowHandler {
             let shared_canvas = (&self.canvas).clone();
             let vat = Arc::clone(&self.vat);
             let etsystem = Arc::clone(&self.etsystem);
-            let colors = Arc::clone(&colors);
+            let palette = Arc::clone(&self.palette);
+            let linear = Arc::clone(&linear);
+            let smooth = self.smooth;
             let tl = [0.0, 0.0];
             let br = self.screen_size;
 
@@ -116,17 +142,16 @@ impl EscapeTimeWindowHandler {
                             log::debug!("{}: Remote side disconnected", name);
                             break;
                         }
-                        let y_colors = ((tl[1] as u32)..(br[1] as u32))
+                        let y_values = ((tl[1] as u32)..(br[1] as u32))
                             .map(|y| {
                                 let c: Complex64 =
                                     vat.map_pixel_to_point([f64::from(x), f64::from(y)]).into();
-                                let (attracted, time) = etsystem.test_point(c);
-                                if attracted {
-                                    Rgba(color::AEBLUE_U8.0)
-                                } else {
-                                    Rgba(colors[cmp::min(time, 50 - 1) as usize].0)
-                                }
+                                coloring::escape_value(&*etsystem, c, smooth)
                             })
+                            .collect::<Vec<Option<f64>>>();
+                        let y_colors = y_values
+                            .iter()
+                            .map(|value| pixel_color(&palette, &linear, *value))
                             .collect::<Vec<Rgba<u8>>>();
                         // only lock the canvas while writing to it
                         {
@@ -139,6 +164,27 @@ impl EscapeTimeWindowHandler {
                                 canvas.put_pixel(x, y as u32, color);
                             }
                         }
+                        if let Some(escape_values) = &escape_values {
+                            let start = (x * height) as usize;
+                            escape_values.write().unwrap()[start..start + y_values.len()]
+                                .copy_from_slice(&y_values);
+                        }
+                        if finished_columns.fetch_add(1, Ordering::SeqCst) + 1 == width as usize {
+                            if let Some(escape_values) = &escape_values {
+                                log::debug!("{}: Recoloring the frame", name);
+                                let escape_values = escape_values.read().unwrap();
+                                let mapping = PaletteMapping::new(
+                                    ColoringMode::Histogram,
+                                    etsystem.max_iterations(),
+                                    escape_values.iter().flatten().cloned(),
+                                );
+                                let mut canvas = shared_canvas.write().unwrap();
+                                for (index, value) in escape_values.iter().enumerate() {
+                                    let (x, y) = (index as u32 / height, index as u32 % height);
+                                    canvas.put_pixel(x, y, pixel_color(&palette, &mapping, *value));
+                                }
+                            }
+                        }
                     }
                 });
             self.threads = Some(work_muxer);
@@ -146,6 +192,14 @@ impl EscapeTimeWindowHandler {
     }
 }
 
+/// Looks up the color of a pixel from its escape value.
+fn pixel_color(palette: &Palette, mapping: &PaletteMapping, escape_value: Option<f64>) -> Rgba<u8> {
+    match escape_value {
+        None => Rgba(color::AEBLUE_U8.0),
+        Some(value) => Rgba(palette.color_at(mapping.position(value)).0),
+    }
+}
+
 impl WindowHandler for EscapeTimeWindowHandler {
     fn window_resized(&mut self, new_size: Vec2d, window: &mut piston_window::PistonWindow) {
         // Set the new size
@@ -15,20 +15,29 @@
 //! Window handlers for drawing `TurtleProgram`s.
 
 use super::{RenderContext, WhichFrame, WindowHandler};
-use fractal_lib::color;
-use fractal_lib::geometry::{Point, Vector, ViewAreaTransformer};
-use fractal_lib::turtle::{Turtle, TurtleCollectToNextForwardIterator, TurtleProgram, TurtleState};
+use fractal_lib::color::{self, ColorF32};
+use fractal_lib::geometry::{Point, Vector};
+use fractal_lib::turtle::bounds::{self, BoundingBox};
+use fractal_lib::turtle::{
+    Turtle, TurtleCollectToNextForwardIterator, TurtleProgram, TurtleSeek, TurtleState,
+};
 use graphics::math::Vec2d;
 use std::fmt;
 
+/// Constructs a window handler that draws `program` all at once, or that animates it `animate`
+/// line segments per frame. An animation starts after the first `start` line segments, without
+/// drawing them.
 // The lifetimes are needed here to make the boxed window handlers happy.
 pub fn construct_turtle_window_handler<'a>(
     program: &'a dyn TurtleProgram,
     animate: u64,
+    start: u64,
 ) -> Box<dyn WindowHandler + 'a> {
     match animate {
         0 => Box::new(DoubleBufferedWindowHandler::new(program)),
-        _ => Box::new(DoubleBufferedAnimatedWindowHandler::new(program, animate)),
+        _ => Box::new(DoubleBufferedAnimatedWindowHandler::new(
+            program, animate, start,
+        )),
     }
 }
 
@@ -41,6 +50,8 @@ where
     gfx: &'a mut G,
 
     state: &'a mut TurtleState,
+    /// The area of the turtle's coordinate system that is fit to the window.
+    view: BoundingBox,
 }
 
 impl<'a, G> PistonTurtle<'a, G>
@@ -49,6 +60,7 @@ where
 {
     pub fn new(
         state: &'a mut TurtleState,
+        view: BoundingBox,
         context: graphics::Context,
         gfx: &'a mut G,
     ) -> PistonTurtle<'a, G> {
@@ -56,6 +68,7 @@ where
             context,
             gfx,
             state,
+            view,
         }
     }
 }
@@ -72,20 +85,9 @@ where
         });
 
         if self.state.down {
-            let view_size = self.context.get_view_size();
-            let screen_width = view_size[0];
-            let screen_height = view_size[1];
-
-            // let startx = screen_width / 4f64;
-            // let starty = screen_height / 2f64;
-            // let endx = 3f64 * screen_width / 4f64;
-            // let endy = (screen_height / 2) as f64;
-
-            let turtle_vat = ViewAreaTransformer::new(
-                [screen_width, screen_height],
-                Point { x: -0.5, y: -0.75 },
-                Point { x: 1.5, y: 0.75 },
-            );
+            let turtle_vat = self
+                .view
+                .view_area_transformer(self.context.get_view_size());
             let old_coords = turtle_vat.map_point_to_pixel(old_pos);
             let new_coords = turtle_vat.map_point_to_pixel(new_pos);
             log::debug!(
@@ -95,12 +97,30 @@ where
                 new_coords[1]
             );
 
-            piston_window::Line::new(color::BLACK_F32.0, 0.5).draw(
-                [old_coords[0], old_coords[1], new_coords[0], new_coords[1]],
-                &graphics::draw_state::DrawState::default(),
-                self.context.transform,
-                self.gfx,
-            );
+            // Piston can't draw dashed lines, so each dash is drawn as its own line.
+            let dx = new_coords[0] - old_coords[0];
+            let dy = new_coords[1] - old_coords[1];
+            let length = dx.hypot(dy);
+            let line = piston_window::Line::new(self.state.color.0, self.state.width / 2.0);
+            for [start, end] in self.state.dash_intervals(length) {
+                let (start, end) = if length > 0.0 {
+                    (start / length, end / length)
+                } else {
+                    (0.0, 1.0)
+                };
+                line.draw(
+                    [
+                        old_coords[0] + dx * start,
+                        old_coords[1] + dy * start,
+                        old_coords[0] + dx * end,
+                        old_coords[1] + dy * end,
+                    ],
+                    &graphics::draw_state::DrawState::default(),
+                    self.context.transform,
+                    self.gfx,
+                );
+            }
+            self.state.advance_dash(length);
         }
 
         self.state.position = new_pos;
@@ -126,12 +146,33 @@ where
     fn up(&mut self) {
         self.state.down = false;
     }
+
+    fn set_pen_color(&mut self, color: ColorF32) {
+        self.state.color = color;
+    }
+
+    fn set_pen_width(&mut self, width: f64) {
+        self.state.width = width;
+    }
+
+    fn set_pen_dash(&mut self, pattern: Vec<f64>) {
+        self.state.set_dash(pattern);
+    }
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        self.state.pop();
+    }
 }
 
 /// `WindowHandler` that renders an entire turtle program per-frame, and optimizes re-renders by
 /// only rendering twice (once for each buffer).
 pub struct DoubleBufferedWindowHandler<'a> {
     program: &'a dyn TurtleProgram,
+    view: BoundingBox,
     /// Whether we need to re-render for double-buffered frames.
     redraw: [bool; 2],
 }
@@ -140,6 +181,7 @@ impl<'a> DoubleBufferedWindowHandler<'a> {
     pub fn new(program: &dyn TurtleProgram) -> DoubleBufferedWindowHandler {
         DoubleBufferedWindowHandler {
             program,
+            view: bounds::view_area(program),
             redraw: [true; 2],
         }
     }
@@ -181,8 +223,12 @@ impl<'a> WindowHandler for DoubleBufferedWindowHandler<'a> {
             piston_window::clear(color::WHITE_F32.0, render_context.gfx);
 
             let mut state = TurtleState::new();
-            let mut turtle =
-                PistonTurtle::new(&mut state, render_context.context, render_context.gfx);
+            let mut turtle = PistonTurtle::new(
+                &mut state,
+                self.view,
+                render_context.context,
+                render_context.gfx,
+            );
             DoubleBufferedWindowHandler::turtledraw(self.program, &mut turtle);
 
             log::debug!("Done redrawing frame");
@@ -195,12 +241,15 @@ impl<'a> WindowHandler for DoubleBufferedWindowHandler<'a> {
 /// frame.
 pub struct DoubleBufferedAnimatedWindowHandler<'a> {
     program: &'a dyn TurtleProgram,
+    view: BoundingBox,
     /// stored turtle state for each turtle. double-buffered means we need to animate the curve
     /// "twice".
     turtles: [TurtleState; 2],
     /// Two iterators.
     iters: [TurtleCollectToNextForwardIterator; 2],
     lines_per_frame: u64,
+    /// How many line segments to skip before animating.
+    start: u64,
     /// Which frame we are rendering. We need to perform the initial steps for the first frame,
     /// and we need perform the initial steps and do one extra move forward for the second frame
     /// (to stagger the double buffer). The rest of the frames then just move forward.
@@ -220,23 +269,35 @@ impl<'a> fmt::Debug for DoubleBufferedAnimatedWindowHandler<'a> {
 impl<'a> DoubleBufferedAnimatedWindowHandler<'a> {
     /// Initialize a new DoubleBufferedAnimatedWindowHandler.
     ///
-    /// `lines_per_frame` specifies how many line segments the turtle should draw per frame.
+    /// `lines_per_frame` specifies how many line segments the turtle should draw per frame, and
+    /// `start` specifies how many line segments to skip (using `TurtleProgram::seek()`) before
+    /// the animation starts drawing.
     pub fn new(
         program: &'a dyn TurtleProgram,
         lines_per_frame: u64,
+        start: u64,
     ) -> DoubleBufferedAnimatedWindowHandler<'a> {
         DoubleBufferedAnimatedWindowHandler {
             program,
+            view: bounds::view_area(program),
             turtles: [TurtleState::new(), TurtleState::new()],
             iters: [
                 TurtleCollectToNextForwardIterator::new_null_iter(),
                 TurtleCollectToNextForwardIterator::new_null_iter(),
             ],
             lines_per_frame,
+            start,
             which_frame: WhichFrame::FirstFrame,
         }
     }
 
+    /// Moves buffer `bufnum`'s turtle and iterator to where the animation starts.
+    fn seek(&mut self, bufnum: usize) {
+        let TurtleSeek { state, rest } = self.program.seek(self.start);
+        self.turtles[bufnum] = state;
+        self.iters[bufnum] = rest.collect_to_next_forward();
+    }
+
     fn draw_one_move<G>(
         turtle: &mut PistonTurtle<G>,
         program_iter: &mut TurtleCollectToNextForwardIterator,
@@ -267,28 +328,18 @@ impl<'a> WindowHandler for DoubleBufferedAnimatedWindowHandler<'a> {
                 // turtle and also use it elsewhere, this would trigger the static analysis.
                 // This could be worked around by placing gfx into a RefCell.
                 piston_window::clear(color::WHITE_F32.0, render_context.gfx);
-                let mut turtle = PistonTurtle::new(
-                    &mut self.turtles[bufnum],
-                    render_context.context,
-                    render_context.gfx,
-                );
-                for action in self.program.init_turtle() {
-                    turtle.perform(action)
-                }
-                self.iters[bufnum] = self.program.turtle_program_iter().collect_to_next_forward();
+                self.seek(bufnum);
                 self.which_frame = WhichFrame::SecondFrame;
             }
             WhichFrame::SecondFrame => {
                 piston_window::clear(color::WHITE_F32.0, render_context.gfx);
+                self.seek(bufnum);
                 let mut turtle = PistonTurtle::new(
                     &mut self.turtles[bufnum],
+                    self.view,
                     render_context.context,
                     render_context.gfx,
                 );
-                for action in self.program.init_turtle() {
-                    turtle.perform(action)
-                }
-                self.iters[bufnum] = self.program.turtle_program_iter().collect_to_next_forward();
                 // if we are the second frame, then we need to stagger our buffer from the first
                 // buffer.
                 if let Some(one_move) = self.iters[bufnum].next() {
@@ -301,6 +352,7 @@ impl<'a> WindowHandler for DoubleBufferedAnimatedWindowHandler<'a> {
             _ => {
                 let mut turtle = PistonTurtle::new(
                     &mut self.turtles[bufnum],
+                    self.view,
                     render_context.context,
                     render_context.gfx,
                 );
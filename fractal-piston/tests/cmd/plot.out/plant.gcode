@@ -0,0 +1,34 @@
+G21 ; millimeters
+G90 ; absolute positioning
+G0 Z2
+G0 X10.587 Y5
+G1 Z0 F1500
+G1 X16.411 Y17.488 F1500
+G1 X22.234 Y29.977 F1500
+G1 X22.234 Y43.756 F1500
+G1 X22.234 Y57.535 F1500
+G1 X28.057 Y70.023 F1500
+G0 Z2
+G0 X33.881 Y68.732
+G1 Z0 F1500
+G1 X33.881 Y54.953 F1500
+G0 Z2
+G0 X44.436 Y63.81
+G1 Z0 F1500
+G1 X33.881 Y54.953 F1500
+G1 X28.057 Y42.465 F1500
+G1 X22.234 Y29.977 F1500
+G1 X28.057 Y42.465 F1500
+G1 X33.881 Y54.953 F1500
+G1 X44.436 Y63.81 F1500
+G1 X54.992 Y72.667 F1500
+G1 X65.547 Y81.525 F1500
+G1 X76.103 Y90.382 F1500
+G1 X89.413 Y93.948 F1500
+G0 Z2
+G0 X39.704 Y95
+G1 Z0 F1500
+G1 X33.881 Y82.512 F1500
+G1 X33.881 Y68.732 F1500
+G0 Z2
+G0 X0 Y0
@@ -14,10 +14,11 @@
 
 use super::FractalAnimation;
 use fractal_lib::color;
+use fractal_lib::color::palette::Palette;
+use fractal_lib::escapetime::coloring::{self, ColoringMode, PaletteMapping};
 use fractal_lib::escapetime::EscapeTime;
 use fractal_lib::geometry;
 use num::complex::Complex64;
-use std::cmp;
 use wasm_bindgen::Clamped;
 use web_sys::{CanvasRenderingContext2d, ImageData};
 
@@ -28,6 +29,15 @@ pub struct EscapeTimeAnimation {
     /// Which EscapeTime system is being animated. Boxed to encapsulate/avoid generics.
     etsystem: Box<dyn EscapeTime>,
 
+    /// The palette that escape times are mapped onto.
+    palette: Palette,
+
+    /// How escape times are mapped onto the palette.
+    coloring: ColoringMode,
+
+    /// Whether to use continuous escape times instead of whole numbers of iterations.
+    smooth: bool,
+
     /// The current part of the fractal we're viewing.
     view_area: [geometry::Point; 2],
 }
@@ -36,6 +46,9 @@ impl EscapeTimeAnimation {
     pub fn new(
         ctx: CanvasRenderingContext2d,
         etsystem: Box<dyn EscapeTime>,
+        palette: Palette,
+        coloring: ColoringMode,
+        smooth: bool,
     ) -> EscapeTimeAnimation {
         let view_area_c = etsystem.default_view_area();
         let view_area = [
@@ -45,6 +58,9 @@ impl EscapeTimeAnimation {
         EscapeTimeAnimation {
             ctx,
             etsystem,
+            palette,
+            coloring,
+            smooth,
             view_area,
         }
     }
@@ -66,32 +82,31 @@ impl EscapeTimeAnimation {
             vat.map_pixel_to_point([screen_width.into(), screen_height.into()])
         );
 
-        log::debug!("build color range");
-        let colors = color::color_range_linear(
-            color::BLACK_U8,
-            color::WHITE_U8,
-            cmp::min(self.etsystem.max_iterations(), 50) as usize,
+        log::debug!("compute escape values");
+        let vat = &vat;
+        let escape_values = (0..screen_height)
+            .flat_map(|y| {
+                (0..screen_width).map(move |x| {
+                    let c: Complex64 = vat.map_pixel_to_point([f64::from(x), f64::from(y)]).into();
+                    coloring::escape_value(&*self.etsystem, c, self.smooth)
+                })
+            })
+            .collect::<Vec<Option<f64>>>();
+
+        log::debug!("build palette mapping");
+        let mapping = PaletteMapping::new(
+            self.coloring,
+            self.etsystem.max_iterations(),
+            escape_values.iter().flatten().cloned(),
         );
 
         log::debug!("build image pixels");
-        let image_pixels = (0..screen_height)
-            .map(|y| {
-                (0..screen_width)
-                    .map(|x| {
-                        let c: Complex64 =
-                            vat.map_pixel_to_point([f64::from(x), f64::from(y)]).into();
-                        let (attracted, time) = self.etsystem.test_point(c);
-                        if attracted {
-                            color::AEBLUE_U8.0.iter()
-                        } else {
-                            colors[cmp::min(time, 50 - 1) as usize].0.iter()
-                        }
-                    })
-                    .flatten()
-                    .collect::<Vec<&u8>>()
+        let image_pixels = escape_values
+            .iter()
+            .flat_map(|value| match value {
+                None => color::AEBLUE_U8.0,
+                Some(value) => self.palette.color_at(mapping.position(*value)).0,
             })
-            .flatten()
-            .cloned()
             .collect::<Vec<u8>>();
 
         // Construct a Clamped Uint8 Array
@@ -1,19 +1,39 @@
 use fractal_lib::chaosgame::barnsleyfern;
 use fractal_lib::chaosgame::sierpinski;
 use fractal_lib::chaosgame::ChaosGameMoveIterator;
+use fractal_lib::color;
+use fractal_lib::color::palette::NamedPalette;
 use fractal_lib::curves::cesaro;
 use fractal_lib::curves::cesarotri;
 use fractal_lib::curves::dragon;
+use fractal_lib::curves::folding;
+use fractal_lib::curves::fractalplant;
+use fractal_lib::curves::gosper;
+use fractal_lib::curves::hilbert;
+use fractal_lib::curves::hilbert3d;
 use fractal_lib::curves::kochcurve;
 use fractal_lib::curves::levyccurve;
+use fractal_lib::curves::moore;
+use fractal_lib::curves::parametrictree;
+use fractal_lib::curves::peano;
+use fractal_lib::curves::quadratickoch;
+use fractal_lib::curves::sierpinskiarrowhead;
+use fractal_lib::curves::sierpinskicurve;
 use fractal_lib::curves::terdragon;
+use fractal_lib::curves::tree3d;
 use fractal_lib::escapetime::burningship::{BurningMandel, BurningShip, RoadRunner};
+use fractal_lib::escapetime::coloring::ColoringMode;
 use fractal_lib::escapetime::mandelbrot::Mandelbrot;
 use fractal_lib::escapetime::EscapeTime;
-use fractal_lib::lindenmayer::LindenmayerSystemTurtleProgram;
+use fractal_lib::lindenmayer::{
+    ExpansionStrategy, LindenmayerSystemTurtle3dProgram, LindenmayerSystemTurtleProgram,
+};
+use fractal_lib::turtle::coloring::{ColoredTurtleProgram, SegmentColoring, SegmentColoringMode};
 use fractal_lib::turtle::TurtleProgram;
+use fractal_lib::turtle3d::projection::{Camera, ProjectedTurtleProgram};
 use fractal_lib::FractalCategory;
 use fractal_lib::SelectedFractal;
+use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::JsValue;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
@@ -36,10 +56,19 @@ impl SelectedFractalExt for SelectedFractal {
     fn default_config(self) -> FractalConfig {
         match self.category() {
             FractalCategory::ChaosGames => FractalConfig::NoConfig,
-            FractalCategory::TurtleCurves => FractalConfig::TurtleCurveConfig { iteration: 1 },
+            FractalCategory::TurtleCurves => FractalConfig::TurtleCurveConfig {
+                iteration: 1,
+                coloring: SegmentColoringMode::Solid,
+            },
+            FractalCategory::LSystemPresets => FractalConfig::TurtleCurveConfig {
+                iteration: self.preset().unwrap().iteration,
+                coloring: SegmentColoringMode::Solid,
+            },
             FractalCategory::EscapeTimeFractals => FractalConfig::EscapeTimeConfig {
                 max_iterations: 100,
                 power: 2,
+                coloring: ColoringMode::Linear,
+                smooth: false,
             },
         }
     }
@@ -81,7 +110,11 @@ impl SelectedFractalExt for SelectedFractal {
             SelectedFractal::CesaroTri => Box::new(animated_turtle(
                 canvas,
                 config,
-                &LindenmayerSystemTurtleProgram::build(cesarotri::CesaroTriFractal::new),
+                // grows quickly enough that caching every iteration exhausts memory
+                &|iteration| {
+                    LindenmayerSystemTurtleProgram::new(cesarotri::CesaroTriFractal::new(iteration))
+                        .with_expansion(ExpansionStrategy::Streaming)
+                },
                 self.name(),
             )),
             SelectedFractal::Dragon => Box::new(animated_turtle(
@@ -90,6 +123,85 @@ impl SelectedFractalExt for SelectedFractal {
                 &dragon::DragonFractal::new,
                 self.name(),
             )),
+            SelectedFractal::FractalPlant => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(fractalplant::FractalPlant::new),
+                self.name(),
+            )),
+            SelectedFractal::Gosper => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(gosper::GosperCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::Hilbert => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(hilbert::HilbertCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::Moore => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(moore::MooreCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::PaperFolding => Box::new(animated_turtle(
+                canvas,
+                config,
+                &|iteration| {
+                    folding::FoldingCurve::new(
+                        iteration,
+                        folding::FoldPattern::alternate(),
+                        std::f64::consts::FRAC_PI_2,
+                    )
+                },
+                self.name(),
+            )),
+            SelectedFractal::ParametricTree => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(parametrictree::ParametricTree::new),
+                self.name(),
+            )),
+            SelectedFractal::Peano => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(peano::PeanoCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::QuadraticKoch => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(quadratickoch::QuadraticKochCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::SierpinskiArrowhead => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(
+                    sierpinskiarrowhead::SierpinskiArrowhead::new,
+                ),
+                self.name(),
+            )),
+            SelectedFractal::SierpinskiCurve => Box::new(animated_turtle(
+                canvas,
+                config,
+                &LindenmayerSystemTurtleProgram::build(sierpinskicurve::SierpinskiCurve::new),
+                self.name(),
+            )),
+            SelectedFractal::Hilbert3d => Box::new(animated_turtle(
+                canvas,
+                config,
+                &|iteration| {
+                    ProjectedTurtleProgram::new(
+                        LindenmayerSystemTurtle3dProgram::new(hilbert3d::Hilbert3d::new(iteration)),
+                        Camera::default(),
+                    )
+                },
+                self.name(),
+            )),
             SelectedFractal::KochCurve => Box::new(animated_turtle(
                 canvas,
                 config,
@@ -122,9 +234,53 @@ impl SelectedFractalExt for SelectedFractal {
             SelectedFractal::TerDragon => Box::new(animated_turtle(
                 canvas,
                 config,
-                &LindenmayerSystemTurtleProgram::build(terdragon::TerdragonFractal::new),
+                // like CesaroTri, streamed instead of cached
+                &|iteration| {
+                    LindenmayerSystemTurtleProgram::new(terdragon::TerdragonFractal::new(iteration))
+                        .with_expansion(ExpansionStrategy::Streaming)
+                },
                 self.name(),
             )),
+            SelectedFractal::Tree3d => Box::new(animated_turtle(
+                canvas,
+                config,
+                &|iteration| {
+                    ProjectedTurtleProgram::new(
+                        LindenmayerSystemTurtle3dProgram::new(tree3d::Tree3d::new(iteration)),
+                        Camera::default(),
+                    )
+                },
+                self.name(),
+            )),
+            SelectedFractal::AntiSnowflake
+            | SelectedFractal::Board
+            | SelectedFractal::Bush
+            | SelectedFractal::Bush2
+            | SelectedFractal::Bush3
+            | SelectedFractal::Crystal
+            | SelectedFractal::HexSnowflake
+            | SelectedFractal::Islands
+            | SelectedFractal::KochIsland
+            | SelectedFractal::Pentaplexity
+            | SelectedFractal::PlantA
+            | SelectedFractal::PlantB
+            | SelectedFractal::PlantC
+            | SelectedFractal::PlantD
+            | SelectedFractal::PlantE
+            | SelectedFractal::PlantF
+            | SelectedFractal::Rings
+            | SelectedFractal::StochasticPlant
+            | SelectedFractal::TallPlant
+            | SelectedFractal::Tiles
+            | SelectedFractal::Weed => {
+                let preset = self.preset().unwrap();
+                Box::new(animated_turtle(
+                    canvas,
+                    config,
+                    &|iteration| LindenmayerSystemTurtleProgram::new(preset.system(iteration)),
+                    self.name(),
+                ))
+            }
         }
     }
 }
@@ -132,11 +288,21 @@ impl SelectedFractalExt for SelectedFractal {
 #[derive(Debug)]
 pub enum FractalConfig {
     NoConfig,
-    EscapeTimeConfig { max_iterations: u64, power: u64 },
-    TurtleCurveConfig { iteration: u64 },
+    EscapeTimeConfig {
+        max_iterations: u64,
+        power: u64,
+        coloring: ColoringMode,
+        smooth: bool,
+    },
+    TurtleCurveConfig {
+        iteration: u64,
+        coloring: SegmentColoringMode,
+    },
 }
 
 impl FractalConfig {
+    /// Updates a single field. Fields that are not numbers are encoded as one: `coloring` is the
+    /// index of a `ColoringMode` or `SegmentColoringMode` variant, and `smooth` is 0 or 1.
     pub fn apply_change(&mut self, field: String, new_value: u64) {
         log::debug!("apply_change {:?}", self);
         match self {
@@ -144,16 +310,32 @@ impl FractalConfig {
             FractalConfig::EscapeTimeConfig {
                 ref mut max_iterations,
                 ref mut power,
+                ref mut coloring,
+                ref mut smooth,
             } => {
                 match field.as_str() {
                     "max_iterations" => *max_iterations = new_value,
                     "power" => *power = new_value,
+                    "coloring" => {
+                        *coloring = ColoringMode::iter()
+                            .nth(new_value as usize)
+                            .unwrap_or(ColoringMode::Linear)
+                    }
+                    "smooth" => *smooth = new_value != 0,
                     _ => panic!("{:?} does not have a {}", self, field),
                 };
                 log::debug!("{:?}", self);
             }
-            FractalConfig::TurtleCurveConfig { ref mut iteration } => match field.as_str() {
+            FractalConfig::TurtleCurveConfig {
+                ref mut iteration,
+                ref mut coloring,
+            } => match field.as_str() {
                 "iteration" => *iteration = new_value,
+                "coloring" => {
+                    *coloring = SegmentColoringMode::iter()
+                        .nth(new_value as usize)
+                        .unwrap_or(SegmentColoringMode::Solid)
+                }
                 _ => panic!("{:?} does not have a {}", self, field),
             },
         }
@@ -171,14 +353,22 @@ where
     F: Fn(u64) -> E,
 {
     match config {
-        FractalConfig::TurtleCurveConfig { iteration } => {
+        FractalConfig::TurtleCurveConfig {
+            iteration,
+            coloring,
+        } => {
             log::debug!("Starting animation {}", name);
             let ctx = JsValue::from(canvas.get_context("2d").unwrap().unwrap())
                 .dyn_into::<CanvasRenderingContext2d>()
                 .unwrap();
             ctx.clear_rect(0.0, 0.0, canvas.width().into(), canvas.height().into());
 
-            turtle::TurtleAnimation::new(ctx, &(ctor(*iteration)))
+            let coloring =
+                SegmentColoring::new(*coloring, color::BLACK_F32, NamedPalette::Viridis.palette());
+            turtle::TurtleAnimation::new(
+                ctx,
+                Box::new(ColoredTurtleProgram::new(ctor(*iteration), coloring)),
+            )
         }
         _ => panic!("{} needs a TurtleCurveConfig", stringify!($name)),
     }
@@ -217,6 +407,8 @@ where
         FractalConfig::EscapeTimeConfig {
             max_iterations,
             power,
+            coloring,
+            smooth,
         } => {
             log::debug!("Starting animation {}", name);
             let ctx = JsValue::from(canvas.get_context("2d").unwrap().unwrap())
@@ -225,7 +417,13 @@ where
 
             ctx.clear_rect(0.0, 0.0, canvas.width().into(), canvas.height().into());
 
-            escapetime::EscapeTimeAnimation::new(ctx, Box::new(ctor(*max_iterations, *power)))
+            escapetime::EscapeTimeAnimation::new(
+                ctx,
+                Box::new(ctor(*max_iterations, *power)),
+                NamedPalette::Greyscale.palette(),
+                *coloring,
+                *smooth,
+            )
         }
         _ => panic!("{} needs a EscapeTimeconfig", stringify!($name)),
     }
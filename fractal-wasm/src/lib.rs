@@ -14,8 +14,11 @@
 
 //! A Seed application that runs and renders various fractal curves.
 
+use fractal_lib::escapetime::coloring::ColoringMode;
+use fractal_lib::turtle::coloring::SegmentColoringMode;
 use fractal_lib::SelectedFractal;
 use std::str::FromStr;
+use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlCanvasElement, HtmlElement, HtmlInputElement, PointerEvent};
@@ -50,6 +53,14 @@ pub trait FractalAnimation {
     /// this usually causes the FractalAnimation to transform the coordinate system to apply the
     /// zoom, and then returning true to indicate the fractal needs to be re-rendered.
     fn zoom(&mut self, _x1: f64, _y1: f64, _x2: f64, _y2: f64) -> bool;
+    /// Optional: continue the animation from one of its later (or earlier) frames, such as a
+    /// turtle curve's `segment`th line segment.
+    ///
+    /// Should return true if the animation jumped, or false if the fractal doesn't support
+    /// jumping.
+    fn jump_to(&mut self, _segment: u64) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -77,6 +88,7 @@ fn init(_url: Url, _orders: &mut impl Orders<Msg>) -> Model {
         cursor_coords: [0, 0],
         fractal_coords: None,
         zoom_start_coords: None,
+        jump_segment: 0,
     }
 }
 
@@ -100,6 +112,8 @@ struct Model {
     fractal_coords: Option<[f64; 2]>,
     /// When selecting an area of the canvas, this is where the cursor first clicked ("mousedown")
     zoom_start_coords: Option<[i32; 2]>,
+    /// Which segment of a turtle curve the animation should jump to.
+    jump_segment: u64,
 }
 
 enum Msg {
@@ -116,6 +130,10 @@ enum Msg {
     PauseClicked,
     /// Whether to resume the currently running animation.
     ResumeClicked,
+    /// Indicates which segment the animation should jump to.
+    JumpSegmentChanged(u64),
+    /// Whether to continue the current animation from `jump_segment`.
+    JumpClicked,
     CursorCoordsCanged(i32, i32),
     CursorDown,
     CursorUp,
@@ -165,6 +183,18 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             model.current_animation_status = FractalAnimationStatus::Animating;
             orders.after_next_render(|_| Msg::AnimationFrameRequested);
         }
+        Msg::JumpSegmentChanged(segment) => {
+            model.jump_segment = segment;
+        }
+        Msg::JumpClicked => {
+            log::debug!("clicked jump to {}", model.jump_segment);
+            if let Some(animation) = &mut model.current_animation {
+                if animation.jump_to(model.jump_segment) {
+                    model.current_animation_status = FractalAnimationStatus::Animating;
+                    orders.after_next_render(|_| Msg::AnimationFrameRequested);
+                }
+            }
+        }
         Msg::AnimationFrameRequested => {
             log::debug!("animation frame requested");
             match &mut model.current_animation {
@@ -286,6 +316,25 @@ fn view(model: &Model) -> Node<Msg> {
                     button!["Start a new fractal", ev(Ev::Click, |_| Msg::RunClicked)]
                 ]
             ),
+            IF!(
+                matches!(
+                    model.current_animation_status,
+                    FractalAnimationStatus::Paused | FractalAnimationStatus::Done
+                ) && matches!(model.current_config, FractalConfig::TurtleCurveConfig { .. }) => div![
+                    input![
+                        attrs! {
+                            At::Id => "jump_segment",
+                            At::Type => "number",
+                            At::Value => model.jump_segment,
+                            At::Min => 0,
+                        },
+                        input_ev(Ev::Input, |value| {
+                            value.parse::<u64>().ok().map(Msg::JumpSegmentChanged)
+                        }),
+                    ],
+                    button!["Continue from segment", ev(Ev::Click, |_| Msg::JumpClicked)]
+                ]
+            ),
         ]
     ]
 }
@@ -346,7 +395,10 @@ fn view_config(config: &FractalConfig) -> Node<Msg> {
         attrs! {At::Id => "config"},
         match config {
             FractalConfig::NoConfig => div!["No configuration for this fractal"],
-            FractalConfig::TurtleCurveConfig { iteration } => div![
+            FractalConfig::TurtleCurveConfig {
+                iteration,
+                coloring,
+            } => div![
                 div![
                     label![attrs! {At::For => "iteration"}, "Iterations"],
                     input![
@@ -360,6 +412,25 @@ fn view_config(config: &FractalConfig) -> Node<Msg> {
                         ev(Ev::Input, validate_input),
                     ]
                 ],
+                div![
+                    label![attrs! {At::For => "coloring"}, "Coloring"],
+                    select![
+                        attrs! {At::Id => "coloring"},
+                        SegmentColoringMode::iter().enumerate().map(|(index, mode)| {
+                            option![
+                                attrs! {At::Value => index},
+                                IF!(mode == *coloring => attrs! {At::Selected => true}),
+                                <&'static str>::from(mode)
+                            ]
+                        }),
+                        input_ev(Ev::Input, |value| {
+                            value
+                                .parse::<u64>()
+                                .ok()
+                                .map(|index| Msg::ConfigChanged("coloring".to_string(), index))
+                        }),
+                    ],
+                ],
                 p![
                     "Draws the fractal using a turtle animation."
                 ],
@@ -367,6 +438,8 @@ fn view_config(config: &FractalConfig) -> Node<Msg> {
             FractalConfig::EscapeTimeConfig {
                 max_iterations,
                 power,
+                coloring,
+                smooth,
             } => div![
                 div![
                     label![attrs! {At::For => "max_iterations"}, "Max Iterations"],
@@ -394,6 +467,43 @@ fn view_config(config: &FractalConfig) -> Node<Msg> {
                         ev(Ev::Input, validate_input),
                     ],
                 ],
+                div![
+                    label![attrs! {At::For => "coloring"}, "Coloring"],
+                    select![
+                        attrs! {At::Id => "coloring"},
+                        ColoringMode::iter().enumerate().map(|(index, mode)| {
+                            option![
+                                attrs! {At::Value => index},
+                                IF!(mode == *coloring => attrs! {At::Selected => true}),
+                                <&'static str>::from(mode)
+                            ]
+                        }),
+                        input_ev(Ev::Input, |value| {
+                            value
+                                .parse::<u64>()
+                                .ok()
+                                .map(|index| Msg::ConfigChanged("coloring".to_string(), index))
+                        }),
+                    ],
+                ],
+                div![
+                    label![attrs! {At::For => "smooth"}, "Smooth"],
+                    input![
+                        attrs! {
+                            At::Id => "smooth",
+                            At::Type => "checkbox",
+                            At::Checked => smooth.as_at_value(),
+                        },
+                        ev(Ev::Change, |event| {
+                            let target = event
+                                .target()
+                                .unwrap()
+                                .dyn_into::<HtmlInputElement>()
+                                .unwrap();
+                            Msg::ConfigChanged("smooth".to_string(), u64::from(target.checked()))
+                        }),
+                    ],
+                ],
                 p![
                     "Renders the escape time fractal using the provided parameters. After the fractal renders, you can use a pointer to select an area to zoom in on."
                 ],
@@ -13,36 +13,42 @@
 // limitations under the License.
 //
 use super::FractalAnimation;
+use fractal_lib::color::{ColorF32, ColorU8};
 use fractal_lib::geometry::{Point, Vector, ViewAreaTransformer};
-use fractal_lib::turtle::{Turtle, TurtleCollectToNextForwardIterator, TurtleProgram, TurtleState};
+use fractal_lib::turtle::bounds::{self, BoundingBox};
+use fractal_lib::turtle::{
+    Turtle, TurtleCollectToNextForwardIterator, TurtleProgram, TurtleSeek, TurtleState,
+};
+use wasm_bindgen::JsValue;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 
 /// Constructs a ViewAreaTransformer for converting between a canvas pixel-coordinate and the
 /// coordinate system used by Turtle curves.
 ///
-/// The Turtle curves expect a view area that has positive values going up and to the right, and
-/// that center on both (0.0, 0.0) and (1.0, 0.0). to achieve this, the we need a view area from
-/// -0.5 to 1.5 on the X axis, and -0.75 and 0.75 on the Y axis.
-fn turtle_vat(canvas: &HtmlCanvasElement) -> ViewAreaTransformer {
+/// The Turtle curves use a coordinate system that has positive values going up and to the
+/// right. `view` is the area of that coordinate system to fit to the canvas, usually computed
+/// with `bounds::view_area()`.
+fn turtle_vat(canvas: &HtmlCanvasElement, view: &BoundingBox) -> ViewAreaTransformer {
     let screen_width = f64::from(canvas.width());
     let screen_height = f64::from(canvas.height());
 
-    ViewAreaTransformer::new(
-        [screen_width, screen_height],
-        Point { x: -0.5, y: -0.75 },
-        Point { x: 1.5, y: 0.75 },
-    )
+    view.view_area_transformer([screen_width, screen_height])
 }
 
 /// A turtle that can draw to an HTML Canvas.
 struct CanvasTurtle {
     state: TurtleState,
+    view: BoundingBox,
     pub ctx: CanvasRenderingContext2d,
 }
 
 impl CanvasTurtle {
-    pub fn new(state: TurtleState, ctx: CanvasRenderingContext2d) -> CanvasTurtle {
-        CanvasTurtle { state, ctx }
+    pub fn new(
+        state: TurtleState,
+        view: BoundingBox,
+        ctx: CanvasRenderingContext2d,
+    ) -> CanvasTurtle {
+        CanvasTurtle { state, view, ctx }
     }
 }
 
@@ -55,7 +61,7 @@ impl Turtle for CanvasTurtle {
         });
 
         if self.state.down {
-            let turtle_vat = turtle_vat(&self.ctx.canvas().unwrap());
+            let turtle_vat = turtle_vat(&self.ctx.canvas().unwrap(), &self.view);
 
             let old_coords = turtle_vat.map_point_to_pixel(old_pos);
             let new_coords = turtle_vat.map_point_to_pixel(new_pos);
@@ -71,11 +77,32 @@ impl Turtle for CanvasTurtle {
             //     &new_coords[1],
             // );
 
-            self.ctx.set_line_width(1.0f64);
+            let [r, g, b, a] = ColorU8::from(self.state.color).0;
+            self.ctx.set_stroke_style_str(&format!(
+                "rgba({}, {}, {}, {})",
+                r,
+                g,
+                b,
+                f64::from(a) / 255.0
+            ));
+            self.ctx.set_line_width(self.state.width);
+            let dash: js_sys::Array = self
+                .state
+                .dash
+                .iter()
+                .map(|&len| JsValue::from(len))
+                .collect();
+            // if the canvas rejects the pattern, the line is drawn with the previous one
+            if let Err(e) = self.ctx.set_line_dash(&dash) {
+                log::warn!("Ignoring dash pattern {:?}: {:?}", self.state.dash, e);
+            }
+            self.ctx.set_line_dash_offset(self.state.dash_offset);
             self.ctx.begin_path();
             self.ctx.move_to(old_coords[0], old_coords[1]);
             self.ctx.line_to(new_coords[0], new_coords[1]);
             self.ctx.stroke();
+            self.state
+                .advance_dash((new_coords[0] - old_coords[0]).hypot(new_coords[1] - old_coords[1]));
         }
 
         self.state.position = new_pos;
@@ -101,6 +128,26 @@ impl Turtle for CanvasTurtle {
     fn up(&mut self) {
         self.state.down = false;
     }
+
+    fn set_pen_color(&mut self, color: ColorF32) {
+        self.state.color = color;
+    }
+
+    fn set_pen_width(&mut self, width: f64) {
+        self.state.width = width;
+    }
+
+    fn set_pen_dash(&mut self, pattern: Vec<f64>) {
+        self.state.set_dash(pattern);
+    }
+
+    fn push(&mut self) {
+        self.state.push();
+    }
+
+    fn pop(&mut self) {
+        self.state.pop();
+    }
 }
 
 /// Represents everything needed to render a turtle a piece at a time to a canvas.
@@ -108,6 +155,7 @@ impl Turtle for CanvasTurtle {
 /// It holds onto a turtle program, which is then used to eventually initialize an iterator over
 /// that program.
 pub struct TurtleAnimation {
+    program: Box<dyn TurtleProgram>,
     turtle: CanvasTurtle,
     iter: TurtleCollectToNextForwardIterator,
 }
@@ -115,10 +163,10 @@ pub struct TurtleAnimation {
 impl TurtleAnimation {
     /// Build a TurtleAnimation from a canvas element and a boxed turtle program.
     ///
-    /// The TurtleProgram is copied and boxed (via its `turtle_program_iter`) to to avoid
-    /// TurtleAnimation being generic.
-    pub fn new(ctx: CanvasRenderingContext2d, program: &dyn TurtleProgram) -> TurtleAnimation {
-        let mut turtle = CanvasTurtle::new(TurtleState::new(), ctx);
+    /// The TurtleProgram is boxed to avoid TurtleAnimation being generic. It is kept around so
+    /// that the animation can jump to other segments.
+    pub fn new(ctx: CanvasRenderingContext2d, program: Box<dyn TurtleProgram>) -> TurtleAnimation {
+        let mut turtle = CanvasTurtle::new(TurtleState::new(), bounds::view_area(&program), ctx);
 
         let init_turtle_steps = program.init_turtle();
         for action in init_turtle_steps {
@@ -127,7 +175,11 @@ impl TurtleAnimation {
 
         let iter = program.turtle_program_iter().collect_to_next_forward();
 
-        TurtleAnimation { turtle, iter }
+        TurtleAnimation {
+            program,
+            turtle,
+            iter,
+        }
     }
 }
 
@@ -153,11 +205,20 @@ impl FractalAnimation for TurtleAnimation {
     /// See turtle::turtle_vat for more information on the coordinate system for turtle curves.
     fn pixel_to_coordinate(&self, x: f64, y: f64) -> [f64; 2] {
         let canvas = self.turtle.ctx.canvas().unwrap();
-        let pos_point = turtle_vat(&canvas).map_pixel_to_point([x, y]);
+        let pos_point = turtle_vat(&canvas, &self.turtle.view).map_pixel_to_point([x, y]);
         pos_point.into()
     }
 
     fn zoom(&mut self, _x1: f64, _y1: f64, _x2: f64, _y2: f64) -> bool {
         false
     }
+
+    /// Continues the animation from `segment`, leaving whatever has already been drawn on the
+    /// canvas.
+    fn jump_to(&mut self, segment: u64) -> bool {
+        let TurtleSeek { state, rest } = self.program.seek(segment);
+        self.turtle.state = state;
+        self.iter = rest.collect_to_next_forward();
+        true
+    }
 }